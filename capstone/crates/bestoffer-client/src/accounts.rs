@@ -0,0 +1,197 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use bestoffer::{
+    AcceptedMint, BuyingIntent, Config, Dispute, EncryptedDeliveryInformation, Offer, Reputation,
+    TrackingDetails, Treasury,
+};
+
+use crate::error::{ClientError, Result};
+use crate::pda::{
+    accepted_mint_address, buying_intent_address, config_address, dispute_address,
+    encrypted_delivery_information_address, reputation_address, tracking_details_address,
+    treasury_address,
+};
+
+// Anything able to return raw account data, None when the account does not exist
+pub trait AccountFetcher {
+    fn get_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>>;
+}
+
+// Decode raw account data, checking the Anchor discriminator
+pub fn decode<T: AccountDeserialize + Discriminator>(data: &[u8]) -> Result<T> {
+    if !data.starts_with(T::DISCRIMINATOR) {
+        return Err(ClientError::InvalidAccountData(std::any::type_name::<T>()));
+    }
+
+    T::try_deserialize(&mut &data[..])
+        .map_err(|_| ClientError::InvalidAccountData(std::any::type_name::<T>()))
+}
+
+pub fn fetch<T: AccountDeserialize + Discriminator>(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Option<T>> {
+    fetcher
+        .get_account_data(address)?
+        .map(|data| decode(&data))
+        .transpose()
+}
+
+// Same as fetch, a missing account is an error
+pub fn fetch_required<T: AccountDeserialize + Discriminator>(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<T> {
+    fetch(fetcher, address)?.ok_or(ClientError::AccountNotFound(*address))
+}
+
+pub fn fetch_config(fetcher: &impl AccountFetcher) -> Result<Config> {
+    fetch_required(fetcher, &config_address().0)
+}
+
+pub fn fetch_treasury(fetcher: &impl AccountFetcher) -> Result<Option<Treasury>> {
+    fetch(fetcher, &treasury_address().0)
+}
+
+pub fn fetch_buying_intent(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Option<BuyingIntent>> {
+    fetch(fetcher, address)
+}
+
+pub fn fetch_offer(fetcher: &impl AccountFetcher, address: &Pubkey) -> Result<Option<Offer>> {
+    fetch(fetcher, address)
+}
+
+// Delivery information, tracking details and dispute are kept per accepted offer
+
+pub fn fetch_encrypted_delivery_information(
+    fetcher: &impl AccountFetcher,
+    offer: &Pubkey,
+) -> Result<Option<EncryptedDeliveryInformation>> {
+    fetch(fetcher, &encrypted_delivery_information_address(offer).0)
+}
+
+pub fn fetch_tracking_details(
+    fetcher: &impl AccountFetcher,
+    offer: &Pubkey,
+    shipment: u16,
+) -> Result<Option<TrackingDetails>> {
+    fetch(fetcher, &tracking_details_address(offer, shipment).0)
+}
+
+pub fn fetch_dispute(fetcher: &impl AccountFetcher, offer: &Pubkey) -> Result<Option<Dispute>> {
+    fetch(fetcher, &dispute_address(offer).0)
+}
+
+// None when the mint is not accepted for payments
+pub fn fetch_accepted_mint(
+    fetcher: &impl AccountFetcher,
+    mint: &Pubkey,
+) -> Result<Option<AcceptedMint>> {
+    fetch(fetcher, &accepted_mint_address(mint).0)
+}
+
+// None until the wallet created its first buying intent or offer
+pub fn fetch_reputation(
+    fetcher: &impl AccountFetcher,
+    wallet: &Pubkey,
+) -> Result<Option<Reputation>> {
+    fetch(fetcher, &reputation_address(wallet).0)
+}
+
+// Address the next create_buying_intent of this buyer will use, with its id
+pub fn next_buying_intent_address(
+    fetcher: &impl AccountFetcher,
+    buyer: &Pubkey,
+) -> Result<(Pubkey, u64)> {
+    let id = fetch_config(fetcher)?.buying_intent_increment;
+    Ok((buying_intent_address(buyer, id).0, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use bestoffer::OfferState;
+    use std::collections::HashMap;
+
+    struct Accounts(HashMap<Pubkey, Vec<u8>>);
+
+    impl AccountFetcher for Accounts {
+        fn get_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.get(address).cloned())
+        }
+    }
+
+    fn serialize<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn offer() -> Offer {
+        Offer {
+            id: 1,
+            buying_intent: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            offer_price: 440,
+            shipping_price: 0,
+            price_commitment: None,
+            mint: Pubkey::new_unique(),
+            state: OfferState::PUBLISHED,
+            quantity: 0,
+            escrow_amount: 0,
+            fee: 0,
+            released_amount: 0,
+            accepted_at: None,
+            shipped_at: None,
+            last_shipped_at: None,
+            shipment_count: 0,
+            shipped_units: 0,
+            delivered_units: 0,
+            expires_at: 1_700_000_000,
+            bond_amount: 0,
+            bond_in_tokens: false,
+            bump: 254,
+        }
+    }
+
+    #[test]
+    fn decodes_fetched_accounts() {
+        let address = Pubkey::new_unique();
+        let offer = offer();
+        let accounts = Accounts(HashMap::from([(address, serialize(&offer))]));
+
+        let fetched = fetch_offer(&accounts, &address).unwrap().unwrap();
+        assert_eq!(fetched.seller, offer.seller);
+        assert_eq!(fetched.offer_price, 440);
+
+        assert!(fetch_offer(&accounts, &Pubkey::new_unique())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn rejects_other_account_types() {
+        let data = serialize(&offer());
+
+        assert!(matches!(
+            decode::<BuyingIntent>(&data),
+            Err(ClientError::InvalidAccountData(_))
+        ));
+    }
+
+    #[test]
+    fn missing_config_is_an_error() {
+        let accounts = Accounts(HashMap::new());
+
+        assert!(matches!(
+            next_buying_intent_address(&accounts, &Pubkey::new_unique()),
+            Err(ClientError::AccountNotFound(_))
+        ));
+    }
+}
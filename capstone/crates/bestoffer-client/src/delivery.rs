@@ -0,0 +1,326 @@
+use anchor_lang::prelude::{borsh, Pubkey};
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use bestoffer::{ArbiterSealedKey, EncryptedDeliveryInformation, DELIVERY_INFORMATION_VERSION};
+use crypto_box::{PublicKey, SecretKey};
+use crypto_secretbox::aead::{Aead, AeadCore, KeyInit, OsRng};
+use crypto_secretbox::{Key, Nonce, XSalsa20Poly1305};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha512};
+
+use crate::error::{ClientError, Result};
+
+// Envelope v1: the Borsh serialized DeliveryAddress is encrypted with a random
+// crypto_secretbox key, and that key is sealed (crypto_box_seal) to the X25519
+// key of each recipient, converted from its ed25519 wallet. All primitives are
+// libsodium compatible.
+
+// crypto_secretbox MAC prepended to the address ciphertext
+pub const MAC_LEN: usize = 16;
+
+// Largest serialized address fitting the on-chain account
+pub const MAX_ADDRESS_LEN: usize =
+    EncryptedDeliveryInformation::MAX_ENCRYPTED_ADDRESS_LEN - MAC_LEN;
+
+#[derive(Debug, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DeliveryAddress {
+    pub lastname: String,
+    pub firstname: String,
+    pub address_line_1: String,
+    pub address_line_2: Option<String>,
+    pub city: String,
+    pub postal_code: String,
+    pub country_code: String,
+    pub state_code: Option<String>,
+}
+
+// X25519 public key of a wallet (seller or arbiter), from its ed25519 address
+pub fn encryption_public_key(wallet: &Pubkey) -> Result<PublicKey> {
+    let point = CompressedEdwardsY(wallet.to_bytes())
+        .decompress()
+        .ok_or(ClientError::InvalidEncryptionKey(*wallet))?;
+
+    Ok(PublicKey::from(point.to_montgomery().to_bytes()))
+}
+
+// X25519 secret key of a wallet, from the 32 bytes ed25519 seed (first half of a Solana keypair)
+pub fn encryption_secret_key(ed25519_seed: &[u8; 32]) -> SecretKey {
+    let hash = Sha512::digest(ed25519_seed);
+
+    let mut scalar = [0u8; 32];
+    scalar.copy_from_slice(&hash[..32]);
+    SecretKey::from(scalar)
+}
+
+fn seal_key(key: &[u8], recipient: &Pubkey) -> Result<[u8; 80]> {
+    encryption_public_key(recipient)?
+        .seal(&mut OsRng, key)
+        .map_err(|_| ClientError::Encryption)?
+        .try_into()
+        .map_err(|_| ClientError::Encryption)
+}
+
+// Build the accept_offer envelope for the seller, and optionally a configured arbiter
+pub fn encrypt_delivery_address(
+    address: &DeliveryAddress,
+    seller: &Pubkey,
+    arbiter: Option<&Pubkey>,
+) -> Result<EncryptedDeliveryInformation> {
+    let mut blob = Vec::new();
+    address.serialize(&mut blob)?;
+
+    if blob.len() > MAX_ADDRESS_LEN {
+        return Err(ClientError::AddressTooLong {
+            len: blob.len(),
+            max: MAX_ADDRESS_LEN,
+        });
+    }
+
+    let key = XSalsa20Poly1305::generate_key(&mut OsRng);
+    let nonce = XSalsa20Poly1305::generate_nonce(&mut OsRng);
+    let encrypted_address = XSalsa20Poly1305::new(&key)
+        .encrypt(&nonce, blob.as_slice())
+        .map_err(|_| ClientError::Encryption)?;
+
+    Ok(EncryptedDeliveryInformation {
+        version: DELIVERY_INFORMATION_VERSION,
+        nonce: nonce.into(),
+        encrypted_address,
+        seller_sealed_key: seal_key(&key, seller)?,
+        arbiter_sealed_key: arbiter
+            .map(|arbiter| -> Result<ArbiterSealedKey> {
+                Ok(ArbiterSealedKey {
+                    arbiter: *arbiter,
+                    sealed_key: seal_key(&key, arbiter)?,
+                })
+            })
+            .transpose()?,
+    })
+}
+
+// Recover the symmetric key, as the seller or the arbiter it was sealed to
+fn unseal_key(information: &EncryptedDeliveryInformation, secret: &SecretKey) -> Result<Key> {
+    if information.version != DELIVERY_INFORMATION_VERSION {
+        return Err(ClientError::UnsupportedVersion(information.version));
+    }
+
+    let key = std::iter::once(&information.seller_sealed_key)
+        .chain(
+            information
+                .arbiter_sealed_key
+                .as_ref()
+                .map(|arbiter_sealed_key| &arbiter_sealed_key.sealed_key),
+        )
+        .find_map(|sealed_key| secret.unseal(sealed_key).ok())
+        .ok_or(ClientError::Decryption)?;
+
+    if key.len() != 32 {
+        return Err(ClientError::Decryption);
+    }
+
+    Ok(*Key::from_slice(&key))
+}
+
+pub fn decrypt_delivery_information(
+    information: &EncryptedDeliveryInformation,
+    secret: &SecretKey,
+) -> Result<DeliveryAddress> {
+    let key = unseal_key(information, secret)?;
+
+    let blob = XSalsa20Poly1305::new(&key)
+        .decrypt(
+            Nonce::from_slice(&information.nonce),
+            information.encrypted_address.as_slice(),
+        )
+        .map_err(|_| ClientError::Decryption)?;
+
+    DeliveryAddress::try_from_slice(&blob).map_err(|_| ClientError::Decryption)
+}
+
+// Seal the key again to another recipient (e.g. the arbiter handling a dispute),
+// the address ciphertext is left untouched
+pub fn reseal_delivery_key(
+    information: &EncryptedDeliveryInformation,
+    secret: &SecretKey,
+    recipient: &Pubkey,
+) -> Result<[u8; 80]> {
+    seal_key(&unseal_key(information, secret)?, recipient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::edwards::EdwardsPoint;
+
+    // Wallet from an ed25519 seed, as a Solana keypair does it
+    fn wallet(seed: [u8; 32]) -> (Pubkey, SecretKey) {
+        let hash = Sha512::digest(seed);
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&hash[..32]);
+
+        let public = EdwardsPoint::mul_base_clamped(scalar).compress();
+        (
+            Pubkey::new_from_array(public.to_bytes()),
+            encryption_secret_key(&seed),
+        )
+    }
+
+    fn hex(value: &str) -> Vec<u8> {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn address() -> DeliveryAddress {
+        DeliveryAddress {
+            lastname: "Doe".to_string(),
+            firstname: "Jane".to_string(),
+            address_line_1: "1 rue de la Paix".to_string(),
+            address_line_2: Some("Bâtiment B".to_string()),
+            city: "Paris".to_string(),
+            postal_code: "75002".to_string(),
+            country_code: "FR".to_string(),
+            state_code: None,
+        }
+    }
+
+    // libsodium test/default/ed25519_convert vector
+    #[test]
+    fn matches_libsodium_key_conversion() {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&hex(
+            "421151a459faeade3d247115f94aedae42318124095afabe4d1451a559faedee",
+        ));
+        let (wallet, secret) = wallet(seed);
+
+        let expected = hex("f1814f0e8ff1043d8a44d25babff3cedcae6c22c3edaa48f857ae70de2baae50");
+        assert_eq!(
+            encryption_public_key(&wallet).unwrap().as_bytes(),
+            expected.as_slice()
+        );
+        assert_eq!(secret.public_key().as_bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn seller_decrypts_what_buyer_encrypted() {
+        let (seller, secret) = wallet([1; 32]);
+
+        let information = encrypt_delivery_address(&address(), &seller, None).unwrap();
+        assert_eq!(information.version, DELIVERY_INFORMATION_VERSION);
+        assert!(information.arbiter_sealed_key.is_none());
+
+        assert_eq!(
+            decrypt_delivery_information(&information, &secret).unwrap(),
+            address()
+        );
+    }
+
+    #[test]
+    fn arbiter_decrypts_the_same_envelope() {
+        let (seller, seller_secret) = wallet([2; 32]);
+        let (arbiter, arbiter_secret) = wallet([3; 32]);
+
+        let information = encrypt_delivery_address(&address(), &seller, Some(&arbiter)).unwrap();
+        assert_eq!(
+            information.arbiter_sealed_key.as_ref().unwrap().arbiter,
+            arbiter
+        );
+
+        assert_eq!(
+            decrypt_delivery_information(&information, &seller_secret).unwrap(),
+            address()
+        );
+        assert_eq!(
+            decrypt_delivery_information(&information, &arbiter_secret).unwrap(),
+            address()
+        );
+    }
+
+    #[test]
+    fn other_keys_cannot_decrypt() {
+        let (seller, _) = wallet([4; 32]);
+        let (arbiter, _) = wallet([5; 32]);
+        let (_, other_secret) = wallet([6; 32]);
+
+        let information = encrypt_delivery_address(&address(), &seller, Some(&arbiter)).unwrap();
+
+        assert!(matches!(
+            decrypt_delivery_information(&information, &other_secret),
+            Err(ClientError::Decryption)
+        ));
+    }
+
+    #[test]
+    fn resealed_key_opens_the_envelope() {
+        let (seller, seller_secret) = wallet([7; 32]);
+        let (arbiter, arbiter_secret) = wallet([8; 32]);
+
+        let mut information = encrypt_delivery_address(&address(), &seller, None).unwrap();
+        let sealed_key = reseal_delivery_key(&information, &seller_secret, &arbiter).unwrap();
+        information.arbiter_sealed_key = Some(ArbiterSealedKey {
+            arbiter,
+            sealed_key,
+        });
+
+        assert_eq!(
+            decrypt_delivery_information(&information, &arbiter_secret).unwrap(),
+            address()
+        );
+    }
+
+    #[test]
+    fn respects_account_size() {
+        let (seller, secret) = wallet([9; 32]);
+
+        let mut largest = address();
+        let mut blob = Vec::new();
+        largest.serialize(&mut blob).unwrap();
+        largest.address_line_1 += &"a".repeat(MAX_ADDRESS_LEN - blob.len());
+
+        // The largest address fills the on-chain space exactly
+        let information = encrypt_delivery_address(&largest, &seller, None).unwrap();
+        assert_eq!(
+            information.encrypted_address.len(),
+            EncryptedDeliveryInformation::MAX_ENCRYPTED_ADDRESS_LEN
+        );
+        assert_eq!(
+            decrypt_delivery_information(&information, &secret).unwrap(),
+            largest
+        );
+
+        // One more byte is rejected
+        largest.address_line_1.push('a');
+        assert!(matches!(
+            encrypt_delivery_address(&largest, &seller, None),
+            Err(ClientError::AddressTooLong {
+                max: MAX_ADDRESS_LEN,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let (seller, secret) = wallet([10; 32]);
+
+        let mut information = encrypt_delivery_address(&address(), &seller, None).unwrap();
+        information.version = DELIVERY_INFORMATION_VERSION + 1;
+
+        assert!(matches!(
+            decrypt_delivery_information(&information, &secret),
+            Err(ClientError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_off_curve_wallet() {
+        // y = 2 is not on the ed25519 curve
+        let mut bytes = [0u8; 32];
+        bytes[0] = 2;
+
+        assert!(matches!(
+            encryption_public_key(&Pubkey::new_from_array(bytes)),
+            Err(ClientError::InvalidEncryptionKey(_))
+        ));
+    }
+}
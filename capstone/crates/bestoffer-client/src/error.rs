@@ -0,0 +1,40 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Base64 error: {0}")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("Invalid pubkey: {0}")]
+    InvalidPubkey(String),
+
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account data is not a {0} account")]
+    InvalidAccountData(&'static str),
+
+    #[error("{0} is not a valid ed25519 public key")]
+    InvalidEncryptionKey(Pubkey),
+
+    #[error("Serialized delivery address is {len} bytes, max {max}")]
+    AddressTooLong { len: usize, max: usize },
+
+    #[error("Unsupported delivery information version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Failed to encrypt the delivery address")]
+    Encryption,
+
+    #[error("Failed to decrypt the delivery address")]
+    Decryption,
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,885 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token;
+use bestoffer::{
+    accounts, instruction, EncryptedDeliveryInformation, Offer, SealedBidPeriods, NATIVE_SOL_MINT,
+};
+
+use crate::pda::{
+    accepted_mint_address, associated_token_address, bond_vault_address, buying_intent_address,
+    config_address, dispute_address, encrypted_delivery_information_address, offer_address,
+    reputation_address, sol_vault_address, tracking_details_address, treasury_address,
+    vault_address,
+};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: bestoffer::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// Optional accounts of a bond held in the payment mint, all None for a lamport bond
+struct BondAccounts {
+    mint: Option<Pubkey>,
+    owner_ata: Option<Pubkey>,
+    bond_vault: Option<Pubkey>,
+    token_program: Option<Pubkey>,
+}
+
+fn bond_accounts(owner: &Pubkey, offer: &Pubkey, token_bond: Option<TokenBond>) -> BondAccounts {
+    BondAccounts {
+        mint: token_bond.map(|token_bond| token_bond.mint),
+        owner_ata: token_bond.map(|token_bond| {
+            associated_token_address(owner, &token_bond.mint, &token_bond.token_program)
+        }),
+        bond_vault: token_bond.map(|_| bond_vault_address(offer).0),
+        token_program: token_bond.map(|token_bond| token_bond.token_program),
+    }
+}
+
+// Payment mint of an offer whose bond is held in tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBond {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl TokenBond {
+    fn for_offer(mint: &Pubkey, token_program: Option<Pubkey>) -> Option<Self> {
+        token_program.map(|token_program| Self {
+            mint: *mint,
+            token_program,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateBuyingIntentArgs {
+    pub gtin: u64,
+    pub product_name: String,
+    pub shipping_country_code: String,
+    pub shipping_state_code: Option<String>,
+    pub quantity: u16,
+    pub sealed_bid: Option<SealedBidPeriods>,
+    // Defaults to 30 days after offers become acceptable
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateOfferArgs {
+    pub url: String,
+    pub public_price: u64,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub mint: Pubkey,
+    // Defaults to the buying intent expiry
+    pub expires_at: Option<i64>,
+    // Set when the config asks for a bond in the payment mint
+    pub bond_token_program: Option<Pubkey>,
+}
+
+// Kept by the seller between commit_offer and reveal_offer, the salt must stay secret
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedOfferArgs {
+    pub url: String,
+    pub public_price: u64,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub salt: [u8; 32],
+    pub mint: Pubkey,
+    pub expires_at: Option<i64>,
+    pub bond_token_program: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackingDetailsArgs {
+    pub carrier_name: String,
+    pub tracking_url: String,
+    pub tracking_code: String,
+    pub units: u16,
+}
+
+pub fn create_config(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            admin: *admin,
+            config: config_address().0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateConfig {},
+    )
+}
+
+// `id` must be the current config buying intent counter
+pub fn create_buying_intent(buyer: &Pubkey, id: u64, args: CreateBuyingIntentArgs) -> Instruction {
+    build(
+        accounts::CreateBuyingIntent {
+            buyer: *buyer,
+            buying_intent: buying_intent_address(buyer, id).0,
+            config: config_address().0,
+            buyer_reputation: reputation_address(buyer).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateBuyingIntent {
+            gtin: args.gtin,
+            product_name: args.product_name,
+            shipping_country_code: args.shipping_country_code,
+            shipping_state_code: args.shipping_state_code,
+            quantity: args.quantity,
+            sealed_bid: args.sealed_bid,
+            expires_at: args.expires_at,
+        },
+    )
+}
+
+// Fails if the seller already has a live offer on this buying intent, use update_offer
+pub fn create_offer(seller: &Pubkey, buying_intent: &Pubkey, args: CreateOfferArgs) -> Instruction {
+    let offer = offer_address(buying_intent, seller).0;
+    let bond = bond_accounts(
+        seller,
+        &offer,
+        TokenBond::for_offer(&args.mint, args.bond_token_program),
+    );
+
+    build(
+        accounts::CreateOffer {
+            seller: *seller,
+            config: config_address().0,
+            buying_intent: *buying_intent,
+            offer,
+            seller_reputation: reputation_address(seller).0,
+            accepted_mint: accepted_mint_address(&args.mint).0,
+            bond_mint: bond.mint,
+            seller_ata: bond.owner_ata,
+            bond_vault: bond.bond_vault,
+            token_program: bond.token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CreateOffer {
+            url: args.url,
+            public_price: args.public_price,
+            offer_price: args.offer_price,
+            shipping_price: args.shipping_price,
+            mint: args.mint,
+            expires_at: args.expires_at,
+        },
+    )
+}
+
+// Only the hash of the prices goes on-chain until reveal_offer
+pub fn commit_offer(
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    args: &SealedOfferArgs,
+) -> Instruction {
+    let offer = offer_address(buying_intent, seller).0;
+    let bond = bond_accounts(
+        seller,
+        &offer,
+        TokenBond::for_offer(&args.mint, args.bond_token_program),
+    );
+
+    build(
+        accounts::CommitOffer {
+            seller: *seller,
+            config: config_address().0,
+            buying_intent: *buying_intent,
+            offer,
+            seller_reputation: reputation_address(seller).0,
+            accepted_mint: accepted_mint_address(&args.mint).0,
+            bond_mint: bond.mint,
+            seller_ata: bond.owner_ata,
+            bond_vault: bond.bond_vault,
+            token_program: bond.token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CommitOffer {
+            url: args.url.clone(),
+            public_price: args.public_price,
+            price_commitment: Offer::price_commitment(
+                &offer,
+                args.offer_price,
+                args.shipping_price,
+                &args.salt,
+            ),
+            mint: args.mint,
+            expires_at: args.expires_at,
+        },
+    )
+}
+
+pub fn reveal_offer(
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    args: &SealedOfferArgs,
+) -> Instruction {
+    build(
+        accounts::RevealOffer {
+            seller: *seller,
+            config: config_address().0,
+            buying_intent: *buying_intent,
+            offer: offer_address(buying_intent, seller).0,
+            seller_reputation: reputation_address(seller).0,
+            accepted_mint: accepted_mint_address(&args.mint).0,
+        },
+        instruction::RevealOffer {
+            offer_price: args.offer_price,
+            shipping_price: args.shipping_price,
+            salt: args.salt,
+        },
+    )
+}
+
+// `offers` must list every revealed offer still open on the buying intent
+pub fn highlight_best_offer(
+    buying_intent: &Pubkey,
+    mint: &Pubkey,
+    offers: &[Pubkey],
+) -> Instruction {
+    let mut ix = build(
+        accounts::HighlightBestOffer {
+            buying_intent: *buying_intent,
+        },
+        instruction::HighlightBestOffer { mint: *mint },
+    );
+    ix.accounts.extend(
+        offers
+            .iter()
+            .map(|offer| AccountMeta::new_readonly(*offer, false)),
+    );
+    ix
+}
+
+// Anyone can crank it once expired, the rent goes back to the buyer
+pub fn expire_buying_intent(payer: &Pubkey, buyer: &Pubkey, buying_intent: &Pubkey) -> Instruction {
+    build(
+        accounts::ExpireBuyingIntent {
+            payer: *payer,
+            buyer: *buyer,
+            buying_intent: *buying_intent,
+        },
+        instruction::ExpireBuyingIntent {},
+    )
+}
+
+// Anyone can crank it once expired, the rent and the bond go back to the seller
+pub fn expire_offer(
+    payer: &Pubkey,
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    token_bond: Option<TokenBond>,
+) -> Instruction {
+    let offer = offer_address(buying_intent, seller).0;
+    let bond = bond_accounts(seller, &offer, token_bond);
+
+    build(
+        accounts::ExpireOffer {
+            payer: *payer,
+            seller: *seller,
+            buying_intent: *buying_intent,
+            offer,
+            bond_mint: bond.mint,
+            seller_ata: bond.owner_ata,
+            bond_vault: bond.bond_vault,
+            token_program: bond.token_program,
+        },
+        instruction::ExpireOffer {},
+    )
+}
+
+// Stops accepting offers, the buying intent keeps the units already covered
+pub fn confirm_buying_intent(buyer: &Pubkey, buying_intent: &Pubkey) -> Instruction {
+    build(
+        accounts::ConfirmBuyingIntent {
+            buyer: *buyer,
+            buying_intent: *buying_intent,
+        },
+        instruction::ConfirmBuyingIntent {},
+    )
+}
+
+// Escrows the total of `quantity` units from the buyer token account into the offer vault,
+// see delivery::encrypt_delivery_address for the envelope
+pub fn accept_offer(
+    buyer: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    quantity: u16,
+    delivery_information: EncryptedDeliveryInformation,
+) -> Instruction {
+    build(
+        accounts::AcceptOffer {
+            buyer: *buyer,
+            config: config_address().0,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            encrypted_delivery_information: encrypted_delivery_information_address(offer).0,
+            mint: *mint,
+            accepted_mint: accepted_mint_address(mint).0,
+            buyer_ata: associated_token_address(buyer, mint, token_program),
+            vault: vault_address(offer, mint, token_program),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptOffer {
+            offer: *offer,
+            quantity,
+            version: delivery_information.version,
+            nonce: delivery_information.nonce,
+            encrypted_address: delivery_information.encrypted_address,
+            seller_sealed_key: delivery_information.seller_sealed_key,
+            arbiter_sealed_key: delivery_information.arbiter_sealed_key,
+        },
+    )
+}
+
+// Escrows the total of `quantity` units in lamports, plus the SOL vault rent refunded at cleanup
+pub fn accept_offer_sol(
+    buyer: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+    quantity: u16,
+    delivery_information: EncryptedDeliveryInformation,
+) -> Instruction {
+    build(
+        accounts::AcceptOfferSol {
+            buyer: *buyer,
+            config: config_address().0,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            encrypted_delivery_information: encrypted_delivery_information_address(offer).0,
+            accepted_mint: accepted_mint_address(&NATIVE_SOL_MINT).0,
+            sol_vault: sol_vault_address(offer).0,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptOfferSol {
+            offer: *offer,
+            quantity,
+            version: delivery_information.version,
+            nonce: delivery_information.nonce,
+            encrypted_address: delivery_information.encrypted_address,
+            seller_sealed_key: delivery_information.seller_sealed_key,
+            arbiter_sealed_key: delivery_information.arbiter_sealed_key,
+        },
+    )
+}
+
+// `shipment` must be the current offer shipment count
+pub fn create_tracking_details(
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+    shipment: u16,
+    args: TrackingDetailsArgs,
+) -> Instruction {
+    build(
+        accounts::CreateTrackingDetails {
+            seller: *seller,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            tracking_details: tracking_details_address(offer, shipment).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateTrackingDetails {
+            carrier_name: args.carrier_name,
+            tracking_url: args.tracking_url,
+            tracking_code: args.tracking_code,
+            units: args.units,
+        },
+    )
+}
+
+// Releases the escrow to the seller, minus the marketplace fee, and returns the bond
+pub fn accept_delivery(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    bond_in_tokens: bool,
+) -> Instruction {
+    let treasury = treasury_address().0;
+
+    build(
+        accounts::AcceptDelivery {
+            buyer: *buyer,
+            seller: *seller,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            buyer_reputation: reputation_address(buyer).0,
+            seller_reputation: reputation_address(seller).0,
+            treasury,
+            mint: *mint,
+            vault: vault_address(offer, mint, token_program),
+            treasury_ata: associated_token_address(&treasury, mint, token_program),
+            seller_ata: associated_token_address(seller, mint, token_program),
+            bond_vault: bond_in_tokens.then(|| bond_vault_address(offer).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptDelivery {},
+    )
+}
+
+// Releases the SOL vault to the seller, the fee goes to the treasury PDA
+pub fn accept_delivery_sol(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::AcceptDeliverySol {
+            buyer: *buyer,
+            seller: *seller,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            buyer_reputation: reputation_address(buyer).0,
+            seller_reputation: reputation_address(seller).0,
+            treasury: treasury_address().0,
+            sol_vault: sol_vault_address(offer).0,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptDeliverySol {},
+    )
+}
+
+// Releases the pro-rata part of the escrow for one parcel, minus the marketplace fee.
+// The last parcel also returns the bond.
+#[allow(clippy::too_many_arguments)]
+pub fn accept_shipment(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    buying_intent: &Pubkey,
+    offer: &Pubkey,
+    shipment: u16,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    bond_in_tokens: bool,
+) -> Instruction {
+    let treasury = treasury_address().0;
+
+    build(
+        accounts::AcceptShipment {
+            buyer: *buyer,
+            seller: *seller,
+            buying_intent: *buying_intent,
+            offer: *offer,
+            tracking_details: tracking_details_address(offer, shipment).0,
+            buyer_reputation: reputation_address(buyer).0,
+            seller_reputation: reputation_address(seller).0,
+            treasury,
+            mint: *mint,
+            vault: vault_address(offer, mint, token_program),
+            treasury_ata: associated_token_address(&treasury, mint, token_program),
+            seller_ata: associated_token_address(seller, mint, token_program),
+            bond_vault: bond_in_tokens.then(|| bond_vault_address(offer).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::AcceptShipment { shipment },
+    )
+}
+
+// Tracking details of every shipment of an offer, in the order cleanup expects them
+fn tracking_details_metas(offer: &Pubkey, shipment_count: u16) -> Vec<AccountMeta> {
+    (0..shipment_count)
+        .map(|shipment| AccountMeta::new(tracking_details_address(offer, shipment).0, false))
+        .collect()
+}
+
+// Closes a delivered or cancelled offer, and the buying intent with the last one.
+// Anyone can pay the fees, `accepted` is the fetched offer, it lists the tracking
+// details to close.
+pub fn cleanup_buying_intent(
+    payer: &Pubkey,
+    buyer: &Pubkey,
+    offer: &Pubkey,
+    accepted: &Offer,
+    token_program: &Pubkey,
+) -> Instruction {
+    let mint = &accepted.mint;
+    let bond = bond_accounts(
+        &accepted.seller,
+        offer,
+        TokenBond::for_offer(mint, accepted.bond_in_tokens.then_some(*token_program)),
+    );
+
+    let mut ix = build(
+        accounts::CleanupBuyingIntent {
+            payer: *payer,
+            buyer: *buyer,
+            seller: accepted.seller,
+            buying_intent: accepted.buying_intent,
+            offer: *offer,
+            encrypted_delivery_information: encrypted_delivery_information_address(offer).0,
+            dispute: dispute_address(offer).0,
+            mint: *mint,
+            vault: vault_address(offer, mint, token_program),
+            buyer_ata: associated_token_address(buyer, mint, token_program),
+            seller_ata: bond.owner_ata,
+            bond_vault: bond.bond_vault,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CleanupBuyingIntent {},
+    );
+    ix.accounts
+        .extend(tracking_details_metas(offer, accepted.shipment_count));
+    ix
+}
+
+// Closes a delivered or cancelled native SOL offer, the vault lamports go back to the buyer
+pub fn cleanup_buying_intent_sol(
+    payer: &Pubkey,
+    buyer: &Pubkey,
+    offer: &Pubkey,
+    accepted: &Offer,
+) -> Instruction {
+    let mut ix = build(
+        accounts::CleanupBuyingIntentSol {
+            payer: *payer,
+            buyer: *buyer,
+            seller: accepted.seller,
+            buying_intent: accepted.buying_intent,
+            offer: *offer,
+            encrypted_delivery_information: encrypted_delivery_information_address(offer).0,
+            dispute: dispute_address(offer).0,
+            sol_vault: sol_vault_address(offer).0,
+            system_program: system_program::ID,
+        },
+        instruction::CleanupBuyingIntentSol {},
+    );
+    ix.accounts
+        .extend(tracking_details_metas(offer, accepted.shipment_count));
+    ix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AnchorDeserialize, Discriminator};
+    use anchor_spl::token;
+
+    #[test]
+    fn create_buying_intent_encodes_args() {
+        let buyer = Pubkey::new_unique();
+        let args = CreateBuyingIntentArgs {
+            gtin: 3544056897834,
+            product_name: "Focal Bathys MG".to_string(),
+            shipping_country_code: "US".to_string(),
+            shipping_state_code: Some("CA".to_string()),
+            quantity: 2,
+            sealed_bid: Some(SealedBidPeriods {
+                commit_period: 86_400,
+                reveal_period: 3_600,
+            }),
+            expires_at: None,
+        };
+
+        let ix = create_buying_intent(&buyer, 3, args.clone());
+
+        assert_eq!(ix.program_id, bestoffer::ID);
+        assert!(ix
+            .data
+            .starts_with(instruction::CreateBuyingIntent::DISCRIMINATOR));
+
+        let decoded = instruction::CreateBuyingIntent::try_from_slice(
+            &ix.data[instruction::CreateBuyingIntent::DISCRIMINATOR.len()..],
+        )
+        .unwrap();
+        assert_eq!(decoded.gtin, args.gtin);
+        assert_eq!(decoded.product_name, args.product_name);
+        assert_eq!(decoded.shipping_state_code, args.shipping_state_code);
+        assert_eq!(decoded.quantity, args.quantity);
+        assert_eq!(decoded.sealed_bid, args.sealed_bid);
+
+        // buyer, buying intent, config, buyer reputation, system program
+        assert_eq!(ix.accounts.len(), 5);
+        assert_eq!(ix.accounts[0].pubkey, buyer);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[1].pubkey, buying_intent_address(&buyer, 3).0);
+        assert_eq!(ix.accounts[2].pubkey, config_address().0);
+        assert!(ix.accounts[3].is_writable);
+        assert_eq!(ix.accounts[3].pubkey, reputation_address(&buyer).0);
+    }
+
+    #[test]
+    fn create_offer_derives_offer_address() {
+        let seller = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+
+        let ix = create_offer(
+            &seller,
+            &buying_intent,
+            CreateOfferArgs {
+                url: "https://www.focal.com".to_string(),
+                public_price: 599,
+                offer_price: 440,
+                shipping_price: 0,
+                mint: Pubkey::new_unique(),
+                expires_at: Some(1_700_000_000),
+                bond_token_program: None,
+            },
+        );
+
+        let offer = offer_address(&buying_intent, &seller).0;
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == offer && meta.is_writable));
+    }
+
+    #[test]
+    fn reveal_opens_the_committed_prices() {
+        let seller = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+        let args = SealedOfferArgs {
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            offer_price: 440,
+            shipping_price: 10,
+            salt: [3; 32],
+            mint: Pubkey::new_unique(),
+            expires_at: None,
+            bond_token_program: None,
+        };
+
+        let commit = commit_offer(&seller, &buying_intent, &args);
+        let reveal = reveal_offer(&seller, &buying_intent, &args);
+
+        let committed = instruction::CommitOffer::try_from_slice(
+            &commit.data[instruction::CommitOffer::DISCRIMINATOR.len()..],
+        )
+        .unwrap();
+        let revealed = instruction::RevealOffer::try_from_slice(
+            &reveal.data[instruction::RevealOffer::DISCRIMINATOR.len()..],
+        )
+        .unwrap();
+
+        // The commitment hides the prices the reveal opens
+        assert_eq!(
+            committed.price_commitment,
+            Offer::price_commitment(
+                &reveal.accounts[3].pubkey,
+                revealed.offer_price,
+                revealed.shipping_price,
+                &revealed.salt
+            )
+        );
+        assert_eq!(commit.accounts[3].pubkey, reveal.accounts[3].pubkey);
+        assert_eq!(
+            reveal.accounts[3].pubkey,
+            offer_address(&buying_intent, &seller).0
+        );
+    }
+
+    #[test]
+    fn token_bond_is_locked_in_the_bond_vault() {
+        let seller = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let args = CreateOfferArgs {
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            offer_price: 440,
+            shipping_price: 0,
+            mint,
+            expires_at: None,
+            bond_token_program: Some(token::ID),
+        };
+
+        let offer = offer_address(&buying_intent, &seller).0;
+        let ix = create_offer(&seller, &buying_intent, args.clone());
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == bond_vault_address(&offer).0 && meta.is_writable));
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == associated_token_address(&seller, &mint, &token::ID)));
+
+        // Lamport bonds leave the optional accounts to the program id placeholder
+        let ix = create_offer(
+            &seller,
+            &buying_intent,
+            CreateOfferArgs {
+                bond_token_program: None,
+                ..args
+            },
+        );
+        assert!(!ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == bond_vault_address(&offer).0));
+    }
+
+    #[test]
+    fn highlight_lists_revealed_offers() {
+        let buying_intent = Pubkey::new_unique();
+        let offers = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let ix = highlight_best_offer(&buying_intent, &Pubkey::new_unique(), &offers);
+
+        assert_eq!(ix.accounts.len(), 3);
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[1].pubkey, offers[0]);
+        assert!(!ix.accounts[2].is_writable);
+    }
+
+    #[test]
+    fn accept_offer_uses_escrow_accounts() {
+        let buyer = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let ix = accept_offer(
+            &buyer,
+            &buying_intent,
+            &offer,
+            &mint,
+            &token::ID,
+            2,
+            EncryptedDeliveryInformation {
+                version: bestoffer::DELIVERY_INFORMATION_VERSION,
+                nonce: [1; 24],
+                encrypted_address: vec![2; 64],
+                seller_sealed_key: [3; 80],
+                arbiter_sealed_key: None,
+            },
+        );
+
+        let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&encrypted_delivery_information_address(&offer).0));
+        assert!(keys.contains(&associated_token_address(&buyer, &mint, &token::ID)));
+        assert!(keys.contains(&vault_address(&offer, &mint, &token::ID)));
+
+        let decoded = instruction::AcceptOffer::try_from_slice(
+            &ix.data[instruction::AcceptOffer::DISCRIMINATOR.len()..],
+        )
+        .unwrap();
+        assert_eq!(decoded.offer, offer);
+        assert_eq!(decoded.quantity, 2);
+        assert_eq!(decoded.nonce, [1; 24]);
+        assert_eq!(decoded.encrypted_address, vec![2; 64]);
+    }
+
+    #[test]
+    fn accept_delivery_pays_seller_and_treasury() {
+        let buyer = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let ix = accept_delivery(
+            &buyer,
+            &seller,
+            &buying_intent,
+            &Pubkey::new_unique(),
+            &mint,
+            &token::ID,
+            false,
+        );
+
+        let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&associated_token_address(&seller, &mint, &token::ID)));
+        assert!(keys.contains(&associated_token_address(
+            &treasury_address().0,
+            &mint,
+            &token::ID
+        )));
+        assert_eq!(
+            ix.accounts.iter().filter(|meta| meta.is_signer).count(),
+            1,
+            "only the buyer signs"
+        );
+    }
+
+    #[test]
+    fn cleanup_lists_every_shipment() {
+        let offer = Pubkey::new_unique();
+        let accepted = Offer {
+            id: 0,
+            buying_intent: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            offer_price: 440,
+            shipping_price: 0,
+            price_commitment: None,
+            mint: Pubkey::new_unique(),
+            state: bestoffer::OfferState::DELIVERED,
+            quantity: 3,
+            escrow_amount: 1_320,
+            fee: 0,
+            released_amount: 1_320,
+            accepted_at: None,
+            shipped_at: None,
+            last_shipped_at: None,
+            shipment_count: 3,
+            shipped_units: 3,
+            delivered_units: 3,
+            expires_at: 0,
+            bond_amount: 0,
+            bond_in_tokens: false,
+            bump: 255,
+        };
+
+        let ix = cleanup_buying_intent(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &offer,
+            &accepted,
+            &token::ID,
+        );
+
+        let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&accepted.buying_intent));
+        assert!(keys.contains(&vault_address(&offer, &accepted.mint, &token::ID)));
+
+        let tracking_details: Vec<_> = ix.accounts[ix.accounts.len() - 3..]
+            .iter()
+            .map(|meta| (meta.pubkey, meta.is_writable))
+            .collect();
+        assert_eq!(
+            tracking_details,
+            (0..3)
+                .map(|shipment| (tracking_details_address(&offer, shipment).0, true))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn native_sol_deal_uses_sol_vault() {
+        let buyer = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+        let buying_intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+
+        let ix = accept_delivery_sol(&buyer, &seller, &buying_intent, &offer);
+
+        let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&sol_vault_address(&offer).0));
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == treasury_address().0 && meta.is_writable));
+        assert!(ix
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == seller && meta.is_writable));
+    }
+}
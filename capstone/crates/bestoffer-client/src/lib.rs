@@ -0,0 +1,13 @@
+pub mod accounts;
+pub mod delivery;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod rpc;
+
+pub use accounts::*;
+pub use delivery::*;
+pub use error::*;
+pub use instructions::*;
+pub use pda::*;
+pub use rpc::*;
@@ -0,0 +1,166 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+// Program derived addresses of the bestoffer program, each returns (address, bump)
+
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &bestoffer::ID)
+}
+
+pub fn treasury_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury"], &bestoffer::ID)
+}
+
+// `id` is the config buying intent counter at creation time
+pub fn buying_intent_address(buyer: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"buy_intent", buyer.as_ref(), id.to_le_bytes().as_ref()],
+        &bestoffer::ID,
+    )
+}
+
+// A seller has at most one live offer per buying intent
+pub fn offer_address(buying_intent: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"offer", buying_intent.as_ref(), seller.as_ref()],
+        &bestoffer::ID,
+    )
+}
+
+// Accounts below are created per accepted offer
+
+pub fn encrypted_delivery_information_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"encrypted_delivery_information", offer.as_ref()],
+        &bestoffer::ID,
+    )
+}
+
+// One per parcel, `shipment` counts from 0 up to the offer shipment count
+pub fn tracking_details_address(offer: &Pubkey, shipment: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"tracking_details",
+            offer.as_ref(),
+            shipment.to_le_bytes().as_ref(),
+        ],
+        &bestoffer::ID,
+    )
+}
+
+pub fn dispute_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"dispute", offer.as_ref()], &bestoffer::ID)
+}
+
+// Registry entry of a payment mint, with its fee tier
+pub fn accepted_mint_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"accepted_mint", mint.as_ref()], &bestoffer::ID)
+}
+
+// Track record of a wallet, as buyer and as seller
+pub fn reputation_address(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reputation", wallet.as_ref()], &bestoffer::ID)
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+// Escrow token account owned by the accepted offer
+pub fn vault_address(offer: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    associated_token_address(offer, mint, token_program)
+}
+
+// Escrow of an offer paid in native SOL, a system account owned by no one
+pub fn sol_vault_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sol_vault", offer.as_ref()], &bestoffer::ID)
+}
+
+// Seller bond held in the payment mint, a token account owned by the offer
+pub fn bond_vault_address(offer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"bond_vault", offer.as_ref()], &bestoffer::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buying_intents_are_unique_per_id() {
+        let buyer = Pubkey::new_unique();
+
+        let (first, _) = buying_intent_address(&buyer, 0);
+        let (second, _) = buying_intent_address(&buyer, 1);
+
+        assert_ne!(first, second);
+        assert_eq!(buying_intent_address(&buyer, 0).0, first);
+    }
+
+    #[test]
+    fn addresses_match_program_seeds() {
+        let buying_intent = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+
+        let (offer, bump) = offer_address(&buying_intent, &seller);
+        let expected = Pubkey::create_program_address(
+            &[b"offer", buying_intent.as_ref(), seller.as_ref(), &[bump]],
+            &bestoffer::ID,
+        )
+        .unwrap();
+        assert_eq!(offer, expected);
+
+        let mint = Pubkey::new_unique();
+        let (accepted_mint, bump) = accepted_mint_address(&mint);
+        let expected = Pubkey::create_program_address(
+            &[b"accepted_mint", mint.as_ref(), &[bump]],
+            &bestoffer::ID,
+        )
+        .unwrap();
+        assert_eq!(accepted_mint, expected);
+
+        let (reputation, bump) = reputation_address(&seller);
+        let expected = Pubkey::create_program_address(
+            &[b"reputation", seller.as_ref(), &[bump]],
+            &bestoffer::ID,
+        )
+        .unwrap();
+        assert_eq!(reputation, expected);
+
+        let (tracking_details, bump) = tracking_details_address(&offer, 2);
+        let expected = Pubkey::create_program_address(
+            &[
+                b"tracking_details",
+                offer.as_ref(),
+                2_u16.to_le_bytes().as_ref(),
+                &[bump],
+            ],
+            &bestoffer::ID,
+        )
+        .unwrap();
+        assert_eq!(tracking_details, expected);
+    }
+
+    #[test]
+    fn per_offer_accounts_do_not_collide() {
+        let first_offer = Pubkey::new_unique();
+        let second_offer = Pubkey::new_unique();
+
+        let addresses = [
+            encrypted_delivery_information_address(&first_offer).0,
+            tracking_details_address(&first_offer, 0).0,
+            tracking_details_address(&first_offer, 1).0,
+            dispute_address(&first_offer).0,
+            sol_vault_address(&first_offer).0,
+            encrypted_delivery_information_address(&second_offer).0,
+            tracking_details_address(&second_offer, 0).0,
+            dispute_address(&second_offer).0,
+            sol_vault_address(&second_offer).0,
+        ];
+
+        for (i, first) in addresses.iter().enumerate() {
+            for second in &addresses[i + 1..] {
+                assert_ne!(first, second);
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bestoffer::Offer;
+use serde_json::{json, Value};
+
+use crate::accounts::{decode, AccountFetcher};
+use crate::error::{ClientError, Result};
+
+// Offset of Offer.buying_intent: discriminator + id
+const OFFER_BUYING_INTENT_OFFSET: usize = 8 + 8;
+
+// Minimal JSON-RPC client for reading bestoffer accounts
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|e| ClientError::Rpc(e.to_string()))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(ClientError::Rpc(error.to_string()));
+        }
+
+        Ok(response["result"].clone())
+    }
+
+    // Every offer made on a buying intent
+    pub fn offers_for_buying_intent(&self, buying_intent: &Pubkey) -> Result<Vec<(Pubkey, Offer)>> {
+        let result = self.call(
+            "getProgramAccounts",
+            json!([bestoffer::ID.to_string(), {
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "filters": [
+                    { "memcmp": { "offset": 0, "bytes": STANDARD.encode(Offer::DISCRIMINATOR), "encoding": "base64" } },
+                    { "memcmp": { "offset": OFFER_BUYING_INTENT_OFFSET, "bytes": buying_intent.to_string() } },
+                ],
+            }]),
+        )?;
+
+        let mut offers = Vec::new();
+        for entry in result.as_array().into_iter().flatten() {
+            let pubkey = entry["pubkey"].as_str().unwrap_or_default();
+            let pubkey = Pubkey::from_str(pubkey)
+                .map_err(|_| ClientError::InvalidPubkey(pubkey.to_string()))?;
+            let data = STANDARD.decode(entry["account"]["data"][0].as_str().unwrap_or_default())?;
+            offers.push((pubkey, decode(&data)?));
+        }
+
+        Ok(offers)
+    }
+}
+
+impl AccountFetcher for RpcClient {
+    fn get_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": "confirmed" }]),
+        )?;
+
+        match result["value"]["data"][0].as_str() {
+            Some(data) => Ok(Some(STANDARD.decode(data)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use bestoffer::{
+    BuyingIntent, BuyingIntentState, Config, Dispute, EncryptedDeliveryInformation, Offer,
+    OfferState, TrackingDetails, Treasury,
+};
+
+// Every account type owned by the bestoffer program
+pub enum BestOfferAccount {
+    Config(Config),
+    Treasury(Treasury),
+    BuyingIntent(BuyingIntent),
+    Offer(Offer),
+    EncryptedDeliveryInformation(EncryptedDeliveryInformation),
+    TrackingDetails(TrackingDetails),
+    Dispute(Dispute),
+}
+
+fn try_decode<T: AccountDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    if !data.starts_with(T::DISCRIMINATOR) {
+        return None;
+    }
+
+    T::try_deserialize(&mut &data[..]).ok()
+}
+
+// Decode raw account data using the Anchor discriminator, None for unknown or corrupted data
+pub fn decode_account(data: &[u8]) -> Option<BestOfferAccount> {
+    if let Some(account) = try_decode::<BuyingIntent>(data) {
+        return Some(BestOfferAccount::BuyingIntent(account));
+    }
+    if let Some(account) = try_decode::<Offer>(data) {
+        return Some(BestOfferAccount::Offer(account));
+    }
+    if let Some(account) = try_decode::<TrackingDetails>(data) {
+        return Some(BestOfferAccount::TrackingDetails(account));
+    }
+    if let Some(account) = try_decode::<EncryptedDeliveryInformation>(data) {
+        return Some(BestOfferAccount::EncryptedDeliveryInformation(account));
+    }
+    if let Some(account) = try_decode::<Dispute>(data) {
+        return Some(BestOfferAccount::Dispute(account));
+    }
+    if let Some(account) = try_decode::<Config>(data) {
+        return Some(BestOfferAccount::Config(account));
+    }
+    if let Some(account) = try_decode::<Treasury>(data) {
+        return Some(BestOfferAccount::Treasury(account));
+    }
+
+    None
+}
+
+// Stored representation of the on-chain enums, older accounts may still hold the
+// deprecated intent-level SHIPPED and DISPUTED states
+#[allow(deprecated)]
+pub fn buying_intent_state_name(state: BuyingIntentState) -> &'static str {
+    match state {
+        BuyingIntentState::PUBLISHED => "PUBLISHED",
+        BuyingIntentState::CANCELLED => "CANCELLED",
+        BuyingIntentState::CONFIRMED => "CONFIRMED",
+        BuyingIntentState::SHIPPED => "SHIPPED",
+        BuyingIntentState::FULFILLED => "FULFILLED",
+        BuyingIntentState::DISPUTED => "DISPUTED",
+        BuyingIntentState::EXPIRED => "EXPIRED",
+    }
+}
+
+pub fn offer_state_name(state: OfferState) -> &'static str {
+    match state {
+        OfferState::PUBLISHED => "PUBLISHED",
+        OfferState::ACCEPTED => "ACCEPTED",
+        OfferState::DELIVERED => "DELIVERED",
+        OfferState::CANCELLED => "CANCELLED",
+        OfferState::SHIPPED => "SHIPPED",
+        OfferState::DISPUTED => "DISPUTED",
+        OfferState::EXPIRED => "EXPIRED",
+    }
+}
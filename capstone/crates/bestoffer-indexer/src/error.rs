@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Base64 error: {0}")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Invalid pubkey: {0}")]
+    InvalidPubkey(String),
+}
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
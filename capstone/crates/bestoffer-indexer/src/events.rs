@@ -0,0 +1,241 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bestoffer::EVENT_VERSION;
+
+// Declare the event enum and its decoder from the list of bestoffer events
+macro_rules! best_offer_events {
+    ($($event:ident),* $(,)?) => {
+        pub enum BestOfferEvent {
+            $($event(bestoffer::$event),)*
+        }
+
+        impl BestOfferEvent {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(BestOfferEvent::$event(_) => stringify!($event),)*
+                }
+            }
+        }
+
+        // Decode an event emitted with `emit!`, None for unknown discriminators
+        // and for events of another version than the program's
+        pub fn decode_event(data: &[u8]) -> Option<BestOfferEvent> {
+            $(
+                if data.starts_with(bestoffer::$event::DISCRIMINATOR) {
+                    let mut payload = &data[bestoffer::$event::DISCRIMINATOR.len()..];
+                    if payload.first() != Some(&EVENT_VERSION) {
+                        return None;
+                    }
+
+                    return bestoffer::$event::deserialize(&mut payload)
+                        .ok()
+                        .map(BestOfferEvent::$event);
+                }
+            )*
+
+            None
+        }
+    };
+}
+
+best_offer_events!(
+    ConfigCreated,
+    ConfigUpdated,
+    AdminProposed,
+    AdminAccepted,
+    TreasuryCreated,
+    TreasuryWithdrawn,
+    BuyingIntentCreated,
+    BuyingIntentCancelled,
+    BuyingIntentExpired,
+    BuyingIntentConfirmed,
+    OfferCreated,
+    OfferCommitted,
+    OfferRevealed,
+    BestOfferHighlighted,
+    OfferUpdated,
+    OfferCancelled,
+    OfferExpired,
+    OfferAccepted,
+    ShipmentRecorded,
+    ShipmentDelivered,
+    DeliveryAccepted,
+    DisputeOpened,
+    DisputeResolved,
+    FundsReleasedAfterTimeout,
+    BuyerRefundedAfterTimeout,
+    BuyingIntentClosed,
+    BondReturned,
+    BondSlashed,
+);
+
+impl BestOfferEvent {
+    // Buying intent the event refers to, if any
+    pub fn buying_intent(&self) -> Option<Pubkey> {
+        match self {
+            BestOfferEvent::BuyingIntentCreated(e) => Some(e.buying_intent),
+            BestOfferEvent::BuyingIntentCancelled(e) => Some(e.buying_intent),
+            BestOfferEvent::BuyingIntentExpired(e) => Some(e.buying_intent),
+            BestOfferEvent::BuyingIntentConfirmed(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferCreated(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferCommitted(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferRevealed(e) => Some(e.buying_intent),
+            BestOfferEvent::BestOfferHighlighted(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferUpdated(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferCancelled(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferExpired(e) => Some(e.buying_intent),
+            BestOfferEvent::OfferAccepted(e) => Some(e.buying_intent),
+            BestOfferEvent::ShipmentRecorded(e) => Some(e.buying_intent),
+            BestOfferEvent::ShipmentDelivered(e) => Some(e.buying_intent),
+            BestOfferEvent::DeliveryAccepted(e) => Some(e.buying_intent),
+            BestOfferEvent::DisputeOpened(e) => Some(e.buying_intent),
+            BestOfferEvent::DisputeResolved(e) => Some(e.buying_intent),
+            BestOfferEvent::FundsReleasedAfterTimeout(e) => Some(e.buying_intent),
+            BestOfferEvent::BuyerRefundedAfterTimeout(e) => Some(e.buying_intent),
+            BestOfferEvent::BuyingIntentClosed(e) => Some(e.buying_intent),
+            BestOfferEvent::BondReturned(e) => Some(e.buying_intent),
+            BestOfferEvent::BondSlashed(e) => Some(e.buying_intent),
+            _ => None,
+        }
+    }
+}
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+// Extract the events emitted by `program_id` from transaction logs.
+//
+// Only "Program data:" lines logged while `program_id` is the innermost running
+// program are kept, so events from CPI'd programs are ignored.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Vec<BestOfferEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(rest) = log.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let first = parts.next().unwrap_or_default();
+            let second = parts.next().unwrap_or_default();
+
+            if second == "invoke" {
+                stack.push(first);
+                continue;
+            }
+
+            if second == "success" || second == "failed:" || rest.ends_with(" failed") {
+                stack.pop();
+                continue;
+            }
+        }
+
+        if stack.last() != Some(&program_id.as_str()) {
+            continue;
+        }
+
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+            if let Some(event) = STANDARD
+                .decode(data)
+                .ok()
+                .and_then(|bytes| decode_event(&bytes))
+            {
+                events.push(event);
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AnchorSerialize, Discriminator};
+    use bestoffer::{BuyingIntentCreated, EVENT_VERSION};
+
+    fn encode<T: AnchorSerialize + Discriminator>(event: &T) -> String {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("{}{}", PROGRAM_DATA, STANDARD.encode(data))
+    }
+
+    fn created(buyer: Pubkey) -> BuyingIntentCreated {
+        BuyingIntentCreated {
+            version: EVENT_VERSION,
+            buying_intent: Pubkey::new_unique(),
+            buyer,
+            id: 7,
+            gtin: 3544056897834,
+            product_name: "Focal Bathys MG".to_string(),
+            shipping_country_code: "FR".to_string(),
+            shipping_state_code: None,
+            quantity: 1,
+            commit_deadline: None,
+            reveal_deadline: None,
+            expires_at: 0,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn parses_events_of_the_program_only() {
+        let program_id = bestoffer::ID;
+        let other_program = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: CreateBuyingIntent".to_string(),
+            format!("Program {} invoke [2]", other_program),
+            encode(&created(Pubkey::new_unique())),
+            format!("Program {} success", other_program),
+            encode(&created(buyer)),
+            format!(
+                "Program {} consumed 10000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+        ];
+
+        let events = parse_logs(&program_id, &logs);
+
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            BestOfferEvent::BuyingIntentCreated(event) => {
+                assert_eq!(event.buyer, buyer);
+                assert_eq!(event.id, 7);
+            }
+            _ => panic!("unexpected event"),
+        }
+    }
+
+    #[test]
+    fn ignores_other_versions() {
+        let mut data = BuyingIntentCreated::DISCRIMINATOR.to_vec();
+        created(Pubkey::new_unique()).serialize(&mut data).unwrap();
+        assert!(decode_event(&data).is_some());
+
+        // A truncated event is rejected
+        assert!(decode_event(&data[..data.len() - 8]).is_none());
+
+        for version in [EVENT_VERSION - 1, EVENT_VERSION + 1] {
+            let mut other = data.clone();
+            other[BuyingIntentCreated::DISCRIMINATOR.len()] = version;
+            assert!(decode_event(&other).is_none());
+        }
+    }
+
+    #[test]
+    fn ignores_unknown_data() {
+        let program_id = bestoffer::ID;
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("{}{}", PROGRAM_DATA, STANDARD.encode([0_u8; 16])),
+            format!("{}not base64", PROGRAM_DATA),
+            format!("Program {} success", program_id),
+        ];
+
+        assert!(parse_logs(&program_id, &logs).is_empty());
+    }
+}
@@ -0,0 +1,857 @@
+use anchor_lang::prelude::Pubkey;
+
+use crate::accounts::decode_account;
+use crate::error::Result;
+use crate::events::parse_logs;
+use crate::source::{AccountFixture, Fixtures, RpcClient};
+use crate::store::Store;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncReport {
+    pub transactions: usize,
+    pub events: usize,
+    pub accounts: usize,
+}
+
+pub struct Indexer {
+    store: Store,
+    program_id: Pubkey,
+}
+
+impl Indexer {
+    pub fn new(store: Store, program_id: Pubkey) -> Self {
+        Self { store, program_id }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    // Returns false when the data is not a bestoffer account
+    pub fn ingest_account(&self, address: &Pubkey, slot: u64, data: &[u8]) -> Result<bool> {
+        match decode_account(data) {
+            Some(account) => {
+                self.store.apply_account(address, slot, &account)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Returns the number of bestoffer events found in the logs
+    pub fn ingest_transaction(&self, signature: &str, slot: u64, logs: &[String]) -> Result<usize> {
+        let events = parse_logs(&self.program_id, logs);
+
+        for (index, event) in events.iter().enumerate() {
+            self.store.apply_event(signature, index, slot, event)?;
+        }
+
+        Ok(events.len())
+    }
+
+    // Replay accounts and transactions in slot order, a snapshot comes before
+    // the transactions of its slot
+    pub fn ingest_fixtures(&self, fixtures: &Fixtures) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+
+        let mut accounts: Vec<_> = fixtures.accounts.iter().collect();
+        accounts.sort_by_key(|account| account.slot);
+        let mut transactions: Vec<_> = fixtures.transactions.iter().collect();
+        transactions.sort_by_key(|transaction| transaction.slot);
+
+        let mut accounts = accounts.into_iter().peekable();
+        for transaction in transactions {
+            while let Some(account) = accounts.next_if(|account| account.slot <= transaction.slot) {
+                self.ingest_fixture_account(account, &mut report)?;
+            }
+
+            report.events += self.ingest_transaction(
+                &transaction.signature,
+                transaction.slot,
+                &transaction.logs,
+            )?;
+            report.transactions += 1;
+        }
+
+        for account in accounts {
+            self.ingest_fixture_account(account, &mut report)?;
+        }
+
+        Ok(report)
+    }
+
+    fn ingest_fixture_account(
+        &self,
+        account: &AccountFixture,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let (address, data) = account.decode()?;
+        if self.ingest_account(&address, account.slot, &data)? {
+            report.accounts += 1;
+        }
+
+        Ok(())
+    }
+
+    // Ingest the transactions since the last sync, then snapshot the program accounts
+    pub fn sync(&self, rpc: &RpcClient) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+
+        let last_signature = self.store.last_signature()?;
+        for info in rpc.get_signatures(&self.program_id, last_signature.as_deref())? {
+            if info.failed {
+                continue;
+            }
+
+            if let Some(logs) = rpc.get_transaction_logs(&info.signature)? {
+                report.events += self.ingest_transaction(&info.signature, info.slot, &logs)?;
+                report.transactions += 1;
+            }
+        }
+
+        let slot = rpc.get_slot()?;
+        for (address, data) in rpc.get_program_accounts(&self.program_id)? {
+            if self.ingest_account(&address, slot, &data)? {
+                report.accounts += 1;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::TransactionFixture;
+    use anchor_lang::{AccountSerialize, AnchorSerialize, Discriminator};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bestoffer::{
+        BestOfferHighlighted, BondReturned, BondSlashed, BuyingIntent, BuyingIntentCancelled,
+        BuyingIntentClosed, BuyingIntentExpired, BuyingIntentState, DeliveryAccepted,
+        OfferAccepted, OfferCommitted, OfferCreated, OfferExpired, OfferRevealed,
+        ShipmentDelivered, EVENT_VERSION,
+    };
+
+    fn account_fixture<T: AccountSerialize>(
+        pubkey: Pubkey,
+        slot: u64,
+        account: &T,
+    ) -> AccountFixture {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+
+        AccountFixture {
+            pubkey: pubkey.to_string(),
+            slot,
+            data: STANDARD.encode(data),
+        }
+    }
+
+    fn event_log<T: AnchorSerialize + Discriminator>(event: &T) -> String {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("Program data: {}", STANDARD.encode(data))
+    }
+
+    fn transaction(signature: &str, slot: u64, events: Vec<String>) -> TransactionFixture {
+        let mut logs = vec![format!("Program {} invoke [1]", bestoffer::ID)];
+        logs.extend(events);
+        logs.push(format!("Program {} success", bestoffer::ID));
+
+        TransactionFixture {
+            signature: signature.to_string(),
+            slot,
+            logs,
+        }
+    }
+
+    fn buying_intent(buyer: Pubkey, state: BuyingIntentState) -> BuyingIntent {
+        BuyingIntent {
+            id: 0,
+            buyer,
+            gtin: 3544056897834,
+            product_name: "Focal Bathys MG".to_string(),
+            shipping_country_code: "FR".to_string(),
+            shipping_state_code: None,
+            accepted_offers: Vec::new(),
+            state,
+            quantity: 1,
+            offer_count: 0,
+            allocated_quantity: 0,
+            settled_offers: 0,
+            delivered_offers: 0,
+            closed_offers: 0,
+            sealed_bid: None,
+            best_offer: None,
+            expires_at: 1_700_000_000,
+            bump: 255,
+        }
+    }
+
+    fn offer_accepted(
+        buying_intent: Pubkey,
+        offer: Pubkey,
+        quantity: u16,
+        escrow_amount: u64,
+        buying_intent_state: BuyingIntentState,
+    ) -> OfferAccepted {
+        OfferAccepted {
+            version: EVENT_VERSION,
+            buying_intent,
+            offer,
+            buyer: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            quantity,
+            escrow_amount,
+            buying_intent_state,
+            timestamp: 0,
+        }
+    }
+
+    fn offer_created(buying_intent: Pubkey, offer: Pubkey, price: u64) -> OfferCreated {
+        OfferCreated {
+            version: EVENT_VERSION,
+            buying_intent,
+            offer,
+            seller: Pubkey::new_unique(),
+            id: 0,
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            offer_price: price,
+            shipping_price: 0,
+            mint: Pubkey::new_unique(),
+            expires_at: 1_700_000_000,
+            timestamp: 0,
+            bond_amount: 0,
+        }
+    }
+
+    #[test]
+    fn replays_accounts_and_events() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let cheap_offer = Pubkey::new_unique();
+        let expensive_offer = Pubkey::new_unique();
+
+        let fixtures = Fixtures {
+            accounts: vec![account_fixture(
+                intent,
+                1,
+                &buying_intent(buyer, BuyingIntentState::PUBLISHED),
+            )],
+            transactions: vec![
+                transaction(
+                    "offers",
+                    2,
+                    vec![
+                        event_log(&offer_created(intent, expensive_offer, 450)),
+                        event_log(&offer_created(intent, cheap_offer, 400)),
+                    ],
+                ),
+                transaction(
+                    "accept",
+                    3,
+                    vec![event_log(&offer_accepted(
+                        intent,
+                        cheap_offer,
+                        1,
+                        400,
+                        BuyingIntentState::CONFIRMED,
+                    ))],
+                ),
+            ],
+        };
+
+        let report = indexer.ingest_fixtures(&fixtures).unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                transactions: 2,
+                events: 3,
+                accounts: 1
+            }
+        );
+
+        // The snapshot is older than the acceptance event
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "CONFIRMED");
+        assert_eq!(row.allocated_quantity, 1);
+
+        // Offers are sorted by total price
+        let offers = indexer.store().offers_for_buying_intent(&intent).unwrap();
+        assert_eq!(offers.len(), 2);
+        assert_eq!(offers[0].address, cheap_offer);
+        assert_eq!(offers[0].state, "ACCEPTED");
+        assert_eq!(offers[0].quantity, 1);
+        assert_eq!(offers[0].escrow_amount, 400);
+        assert_eq!(offers[1].address, expensive_offer);
+        assert_eq!(offers[1].state, "PUBLISHED");
+
+        assert!(indexer
+            .store()
+            .buying_intents_by_state("PUBLISHED")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            indexer
+                .store()
+                .buying_intents_by_state("CONFIRMED")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            indexer.store().last_signature().unwrap(),
+            Some("accept".to_string())
+        );
+    }
+
+    #[test]
+    fn sealed_offers_are_revealed_and_highlighted() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let intent = Pubkey::new_unique();
+        let revealed_offer = Pubkey::new_unique();
+        let void_offer = Pubkey::new_unique();
+
+        let committed = |offer: Pubkey| OfferCommitted {
+            version: EVENT_VERSION,
+            buying_intent: intent,
+            offer,
+            seller: Pubkey::new_unique(),
+            id: 0,
+            url: "https://www.focal.com".to_string(),
+            public_price: 599,
+            price_commitment: [1; 32],
+            mint: Pubkey::new_unique(),
+            expires_at: 1_700_000_000,
+            timestamp: 0,
+            bond_amount: 0,
+        };
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &buying_intent(Pubkey::new_unique(), BuyingIntentState::PUBLISHED),
+                )],
+                transactions: vec![
+                    transaction(
+                        "commit",
+                        2,
+                        vec![
+                            event_log(&committed(void_offer)),
+                            event_log(&committed(revealed_offer)),
+                        ],
+                    ),
+                    transaction(
+                        "reveal",
+                        3,
+                        vec![event_log(&OfferRevealed {
+                            version: EVENT_VERSION,
+                            buying_intent: intent,
+                            offer: revealed_offer,
+                            seller: Pubkey::new_unique(),
+                            offer_price: 450,
+                            shipping_price: 10,
+                            timestamp: 0,
+                        })],
+                    ),
+                    transaction(
+                        "highlight",
+                        4,
+                        vec![event_log(&BestOfferHighlighted {
+                            version: EVENT_VERSION,
+                            buying_intent: intent,
+                            offer: revealed_offer,
+                            seller: Pubkey::new_unique(),
+                            order_total: 460,
+                            timestamp: 0,
+                        })],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        // Unrevealed offers have no price and come last
+        let offers = indexer.store().offers_for_buying_intent(&intent).unwrap();
+        assert_eq!(offers[0].address, revealed_offer);
+        assert!(offers[0].revealed);
+        assert_eq!(offers[0].offer_price, 450);
+        assert_eq!(offers[1].address, void_offer);
+        assert!(!offers[1].revealed);
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.best_offer, Some(revealed_offer));
+
+        // Accepting the best offer clears it
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: Vec::new(),
+                transactions: vec![transaction(
+                    "accept",
+                    5,
+                    vec![event_log(&offer_accepted(
+                        intent,
+                        revealed_offer,
+                        1,
+                        460,
+                        BuyingIntentState::CONFIRMED,
+                    ))],
+                )],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.best_offer, None);
+    }
+
+    #[test]
+    fn replaying_twice_is_idempotent() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+        let fixtures = Fixtures {
+            accounts: vec![account_fixture(
+                intent,
+                1,
+                &buying_intent(Pubkey::new_unique(), BuyingIntentState::PUBLISHED),
+            )],
+            transactions: vec![transaction(
+                "offer",
+                2,
+                vec![event_log(&offer_created(intent, offer, 400))],
+            )],
+        };
+
+        indexer.ingest_fixtures(&fixtures).unwrap();
+        indexer.ingest_fixtures(&fixtures).unwrap();
+
+        assert_eq!(
+            indexer
+                .store()
+                .offers_for_buying_intent(&intent)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn cancelled_intent_is_closed() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &buying_intent(buyer, BuyingIntentState::PUBLISHED),
+                )],
+                transactions: vec![
+                    transaction(
+                        "offer",
+                        2,
+                        vec![event_log(&offer_created(intent, offer, 400))],
+                    ),
+                    transaction(
+                        "cancel",
+                        3,
+                        vec![event_log(&BuyingIntentCancelled {
+                            version: EVENT_VERSION,
+                            buying_intent: intent,
+                            buyer,
+                            cancelled_offers: vec![offer],
+                            timestamp: 0,
+                        })],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "CANCELLED");
+        assert!(row.closed);
+        assert_eq!(
+            indexer.store().offer(&offer).unwrap().unwrap().state,
+            "CANCELLED"
+        );
+    }
+
+    #[test]
+    fn expired_intent_and_offer_are_closed() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &buying_intent(buyer, BuyingIntentState::PUBLISHED),
+                )],
+                transactions: vec![
+                    transaction(
+                        "offer",
+                        2,
+                        vec![event_log(&offer_created(intent, offer, 400))],
+                    ),
+                    transaction(
+                        "expire",
+                        3,
+                        vec![
+                            event_log(&BuyingIntentExpired {
+                                version: EVENT_VERSION,
+                                buying_intent: intent,
+                                buyer,
+                                buying_intent_state: BuyingIntentState::EXPIRED,
+                                buying_intent_closed: true,
+                                timestamp: 0,
+                            }),
+                            event_log(&OfferExpired {
+                                version: EVENT_VERSION,
+                                buying_intent: intent,
+                                offer,
+                                seller: Pubkey::new_unique(),
+                                timestamp: 0,
+                            }),
+                        ],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "EXPIRED");
+        assert_eq!(row.expires_at, 1_700_000_000);
+        assert!(row.closed);
+
+        let offer = indexer.store().offer(&offer).unwrap().unwrap();
+        assert_eq!(offer.state, "EXPIRED");
+        assert_eq!(offer.expires_at, 1_700_000_000);
+        assert!(offer.closed);
+    }
+
+    #[test]
+    fn bonds_are_returned_or_slashed() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let intent = Pubkey::new_unique();
+        let delivered = Pubkey::new_unique();
+        let refunded = Pubkey::new_unique();
+
+        let bonded = |offer: Pubkey| OfferCreated {
+            bond_amount: 1_000,
+            ..offer_created(intent, offer, 400)
+        };
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![],
+                transactions: vec![
+                    transaction(
+                        "offers",
+                        1,
+                        vec![event_log(&bonded(delivered)), event_log(&bonded(refunded))],
+                    ),
+                    transaction(
+                        "bonds",
+                        2,
+                        vec![
+                            event_log(&BondReturned {
+                                version: EVENT_VERSION,
+                                buying_intent: intent,
+                                offer: delivered,
+                                seller: Pubkey::new_unique(),
+                                amount: 1_000,
+                                in_tokens: false,
+                                timestamp: 0,
+                            }),
+                            event_log(&BondSlashed {
+                                version: EVENT_VERSION,
+                                buying_intent: intent,
+                                offer: refunded,
+                                seller: Pubkey::new_unique(),
+                                buyer: Pubkey::new_unique(),
+                                buyer_amount: 500,
+                                treasury_amount: 500,
+                                in_tokens: false,
+                                timestamp: 0,
+                            }),
+                        ],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        let delivered = indexer.store().offer(&delivered).unwrap().unwrap();
+        assert_eq!(delivered.bond_amount, 0);
+        assert!(!delivered.bond_slashed);
+
+        let refunded = indexer.store().offer(&refunded).unwrap().unwrap();
+        assert_eq!(refunded.bond_amount, 0);
+        assert!(refunded.bond_slashed);
+    }
+
+    #[test]
+    fn cleaned_up_deal_keeps_final_state() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &buying_intent(buyer, BuyingIntentState::FULFILLED),
+                )],
+                transactions: vec![
+                    transaction(
+                        "offer",
+                        2,
+                        vec![event_log(&offer_created(intent, offer, 400))],
+                    ),
+                    transaction(
+                        "cleanup",
+                        3,
+                        vec![event_log(&BuyingIntentClosed {
+                            version: EVENT_VERSION,
+                            buying_intent: intent,
+                            offer,
+                            buyer,
+                            seller: Pubkey::new_unique(),
+                            buying_intent_closed: true,
+                            timestamp: 0,
+                        })],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "FULFILLED");
+        assert!(row.closed);
+        assert!(indexer.store().offer(&offer).unwrap().unwrap().closed);
+    }
+
+    #[test]
+    fn last_delivered_shipment_fulfills_the_deal() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+
+        let shipment_delivered = |shipment: u16, fulfilled: bool| ShipmentDelivered {
+            version: EVENT_VERSION,
+            buying_intent: intent,
+            offer,
+            tracking_details: Pubkey::new_unique(),
+            buyer,
+            seller,
+            mint: Pubkey::new_unique(),
+            shipment,
+            units: 1,
+            seller_amount: 198,
+            fee_amount: 2,
+            fulfilled,
+            buying_intent_state: if fulfilled {
+                BuyingIntentState::FULFILLED
+            } else {
+                BuyingIntentState::CONFIRMED
+            },
+            timestamp: 0,
+        };
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &buying_intent(buyer, BuyingIntentState::PUBLISHED),
+                )],
+                transactions: vec![
+                    transaction(
+                        "offer",
+                        2,
+                        vec![event_log(&offer_created(intent, offer, 200))],
+                    ),
+                    transaction(
+                        "accept",
+                        3,
+                        vec![event_log(&offer_accepted(
+                            intent,
+                            offer,
+                            2,
+                            400,
+                            BuyingIntentState::CONFIRMED,
+                        ))],
+                    ),
+                    transaction(
+                        "first parcel",
+                        4,
+                        vec![event_log(&shipment_delivered(0, false))],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        // A partial delivery does not settle the deal
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "CONFIRMED");
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![],
+                transactions: vec![transaction(
+                    "last parcel",
+                    5,
+                    vec![event_log(&shipment_delivered(1, true))],
+                )],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "FULFILLED");
+        assert_eq!(
+            indexer.store().offer(&offer).unwrap().unwrap().state,
+            "DELIVERED"
+        );
+    }
+
+    #[test]
+    fn accepted_offers_settle_independently() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        let buyer = Pubkey::new_unique();
+        let intent = Pubkey::new_unique();
+        let first_offer = Pubkey::new_unique();
+        let second_offer = Pubkey::new_unique();
+
+        let delivery_accepted = |offer: Pubkey, buying_intent_state| DeliveryAccepted {
+            version: EVENT_VERSION,
+            buying_intent: intent,
+            offer,
+            buyer,
+            seller: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            seller_amount: 396,
+            fee_amount: 4,
+            buying_intent_state,
+            timestamp: 0,
+        };
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![account_fixture(
+                    intent,
+                    1,
+                    &BuyingIntent {
+                        quantity: 3,
+                        ..buying_intent(buyer, BuyingIntentState::PUBLISHED)
+                    },
+                )],
+                transactions: vec![
+                    transaction(
+                        "offers",
+                        2,
+                        vec![
+                            event_log(&offer_created(intent, first_offer, 400)),
+                            event_log(&offer_created(intent, second_offer, 420)),
+                        ],
+                    ),
+                    transaction(
+                        "accept first",
+                        3,
+                        vec![event_log(&offer_accepted(
+                            intent,
+                            first_offer,
+                            1,
+                            400,
+                            BuyingIntentState::PUBLISHED,
+                        ))],
+                    ),
+                    transaction(
+                        "accept second",
+                        4,
+                        vec![event_log(&offer_accepted(
+                            intent,
+                            second_offer,
+                            2,
+                            840,
+                            BuyingIntentState::CONFIRMED,
+                        ))],
+                    ),
+                    transaction(
+                        "deliver first",
+                        5,
+                        vec![event_log(&delivery_accepted(
+                            first_offer,
+                            BuyingIntentState::CONFIRMED,
+                        ))],
+                    ),
+                ],
+            })
+            .unwrap();
+
+        // One delivered offer does not fulfill the buying intent
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "CONFIRMED");
+        assert_eq!(row.allocated_quantity, 3);
+        assert_eq!(
+            indexer.store().offer(&first_offer).unwrap().unwrap().state,
+            "DELIVERED"
+        );
+        assert_eq!(
+            indexer.store().offer(&second_offer).unwrap().unwrap().state,
+            "ACCEPTED"
+        );
+
+        indexer
+            .ingest_fixtures(&Fixtures {
+                accounts: vec![],
+                transactions: vec![transaction(
+                    "deliver second",
+                    6,
+                    vec![event_log(&delivery_accepted(
+                        second_offer,
+                        BuyingIntentState::FULFILLED,
+                    ))],
+                )],
+            })
+            .unwrap();
+
+        let row = indexer.store().buying_intent(&intent).unwrap().unwrap();
+        assert_eq!(row.state, "FULFILLED");
+        assert_eq!(
+            indexer.store().offer(&second_offer).unwrap().unwrap().state,
+            "DELIVERED"
+        );
+    }
+
+    #[test]
+    fn ignores_foreign_accounts() {
+        let indexer = Indexer::new(Store::open_in_memory().unwrap(), bestoffer::ID);
+
+        assert!(!indexer
+            .ingest_account(&Pubkey::new_unique(), 1, &[1, 2, 3])
+            .unwrap());
+    }
+}
@@ -0,0 +1,13 @@
+pub mod accounts;
+pub mod error;
+pub mod events;
+pub mod indexer;
+pub mod source;
+pub mod store;
+
+pub use accounts::*;
+pub use error::*;
+pub use events::*;
+pub use indexer::*;
+pub use source::*;
+pub use store::*;
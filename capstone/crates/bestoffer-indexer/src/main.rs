@@ -0,0 +1,89 @@
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use bestoffer_indexer::{parse_pubkey, Fixtures, Indexer, Result, RpcClient, Store};
+
+const USAGE: &str = "Usage:
+  bestoffer-indexer [--db <path>] fixtures <file.json>
+  bestoffer-indexer [--db <path>] sync [--rpc <url>] [--watch <seconds>]
+  bestoffer-indexer [--db <path>] intents [STATE]
+  bestoffer-indexer [--db <path>] offers <BUYING_INTENT>";
+
+const DEFAULT_DB: &str = "bestoffer.sqlite";
+const DEFAULT_RPC: &str = "http://127.0.0.1:8899";
+
+fn option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let position = args.iter().position(|arg| arg == name)?;
+    args.remove(position);
+
+    if position < args.len() {
+        Some(args.remove(position))
+    } else {
+        None
+    }
+}
+
+fn run(mut args: Vec<String>) -> Result<()> {
+    let db = option(&mut args, "--db").unwrap_or_else(|| DEFAULT_DB.to_string());
+    let rpc = option(&mut args, "--rpc").unwrap_or_else(|| DEFAULT_RPC.to_string());
+    let watch = option(&mut args, "--watch").and_then(|seconds| seconds.parse::<u64>().ok());
+
+    let indexer = Indexer::new(Store::open(db)?, bestoffer::ID);
+
+    match args.first().map(String::as_str) {
+        Some("fixtures") if args.len() == 2 => {
+            let report = indexer.ingest_fixtures(&Fixtures::load(&args[1])?)?;
+            println!("{:?}", report);
+        }
+        Some("sync") => {
+            let client = RpcClient::new(rpc);
+            loop {
+                let report = indexer.sync(&client)?;
+                println!("{:?}", report);
+
+                match watch {
+                    Some(seconds) => thread::sleep(Duration::from_secs(seconds)),
+                    None => break,
+                }
+            }
+        }
+        Some("intents") => {
+            let state = args.get(1).map(String::as_str).unwrap_or("PUBLISHED");
+            for intent in indexer.store().buying_intents_by_state(state)? {
+                println!(
+                    "{} #{} {} x{} {}",
+                    intent.address, intent.id, intent.product_name, intent.quantity, intent.state
+                );
+            }
+        }
+        Some("offers") if args.len() == 2 => {
+            let buying_intent = parse_pubkey(&args[1])?;
+            for offer in indexer.store().offers_for_buying_intent(&buying_intent)? {
+                println!(
+                    "{} seller={} price={} shipping={} mint={} {}",
+                    offer.address,
+                    offer.seller,
+                    offer.offer_price,
+                    offer.shipping_price,
+                    offer.mint,
+                    offer.state
+                );
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run(env::args().skip(1).collect()) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{IndexerError, Result};
+
+// Replayable snapshot of accounts and transaction logs.
+//
+// {
+//   "accounts": [{ "pubkey": "...", "slot": 10, "data": "<base64>" }],
+//   "transactions": [{ "signature": "...", "slot": 9, "logs": ["Program ... invoke [1]", ...] }]
+// }
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub accounts: Vec<AccountFixture>,
+
+    #[serde(default)]
+    pub transactions: Vec<TransactionFixture>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountFixture {
+    pub pubkey: String,
+    pub slot: u64,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFixture {
+    pub signature: String,
+    pub slot: u64,
+    pub logs: Vec<String>,
+}
+
+impl Fixtures {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+impl AccountFixture {
+    pub fn decode(&self) -> Result<(Pubkey, Vec<u8>)> {
+        Ok((parse_pubkey(&self.pubkey)?, STANDARD.decode(&self.data)?))
+    }
+}
+
+pub fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| IndexerError::InvalidPubkey(value.to_string()))
+}
+
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub failed: bool,
+}
+
+// Minimal JSON-RPC client for a validator (local or remote)
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    // Max signatures returned per getSignaturesForAddress page
+    const SIGNATURES_PAGE: usize = 1_000;
+
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|e| IndexerError::Rpc(e.to_string()))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(IndexerError::Rpc(error.to_string()));
+        }
+
+        Ok(response["result"].clone())
+    }
+
+    pub fn get_slot(&self) -> Result<u64> {
+        self.call("getSlot", json!([{ "commitment": "confirmed" }]))?
+            .as_u64()
+            .ok_or_else(|| IndexerError::Rpc("invalid getSlot result".to_string()))
+    }
+
+    pub fn get_program_accounts(&self, program_id: &Pubkey) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let result = self.call(
+            "getProgramAccounts",
+            json!([program_id.to_string(), { "encoding": "base64", "commitment": "confirmed" }]),
+        )?;
+
+        let mut accounts = Vec::new();
+        for entry in result.as_array().into_iter().flatten() {
+            let pubkey = parse_pubkey(entry["pubkey"].as_str().unwrap_or_default())?;
+            let data = STANDARD.decode(entry["account"]["data"][0].as_str().unwrap_or_default())?;
+            accounts.push((pubkey, data));
+        }
+
+        Ok(accounts)
+    }
+
+    // Signatures newer than `until`, oldest first
+    pub fn get_signatures(
+        &self,
+        program_id: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<SignatureInfo>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        loop {
+            let mut options = json!({ "limit": Self::SIGNATURES_PAGE, "commitment": "confirmed" });
+            if let Some(until) = until {
+                options["until"] = json!(until);
+            }
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+
+            let result = self.call(
+                "getSignaturesForAddress",
+                json!([program_id.to_string(), options]),
+            )?;
+            let page = result.as_array().cloned().unwrap_or_default();
+
+            for entry in &page {
+                signatures.push(SignatureInfo {
+                    signature: entry["signature"].as_str().unwrap_or_default().to_string(),
+                    slot: entry["slot"].as_u64().unwrap_or_default(),
+                    failed: !entry["err"].is_null(),
+                });
+            }
+
+            if page.len() < Self::SIGNATURES_PAGE {
+                break;
+            }
+            before = signatures.last().map(|info| info.signature.clone());
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    pub fn get_transaction_logs(&self, signature: &str) -> Result<Option<Vec<String>>> {
+        let result = self.call(
+            "getTransaction",
+            json!([signature, {
+                "encoding": "json",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )?;
+
+        Ok(result["meta"]["logMessages"].as_array().map(|logs| {
+            logs.iter()
+                .filter_map(|log| log.as_str().map(str::to_string))
+                .collect()
+        }))
+    }
+}
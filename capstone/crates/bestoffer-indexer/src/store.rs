@@ -0,0 +1,849 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use bestoffer::BuyingIntentState;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::accounts::{buying_intent_state_name, offer_state_name, BestOfferAccount};
+use crate::error::{IndexerError, Result};
+use crate::events::BestOfferEvent;
+
+// Bumped with every change to SCHEMA
+const SCHEMA_VERSION: i64 = 1;
+
+// Tables of SCHEMA, their indexes are dropped with them
+const TABLES: [&str; 6] = [
+    "configs",
+    "buying_intents",
+    "offers",
+    "tracking_details",
+    "encrypted_delivery_information",
+    "events",
+];
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS configs (
+    address TEXT PRIMARY KEY,
+    admin TEXT NOT NULL,
+    fee INTEGER NOT NULL,
+    paused INTEGER NOT NULL,
+    buying_intent_increment INTEGER NOT NULL,
+    offer_increment INTEGER NOT NULL,
+    slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS buying_intents (
+    address TEXT PRIMARY KEY,
+    id INTEGER NOT NULL,
+    buyer TEXT NOT NULL,
+    gtin INTEGER NOT NULL,
+    product_name TEXT NOT NULL,
+    shipping_country_code TEXT NOT NULL,
+    shipping_state_code TEXT,
+    quantity INTEGER NOT NULL,
+    state TEXT NOT NULL,
+    allocated_quantity INTEGER NOT NULL DEFAULT 0,
+    commit_deadline INTEGER,
+    reveal_deadline INTEGER,
+    best_offer TEXT,
+    expires_at INTEGER NOT NULL DEFAULT 0,
+    closed INTEGER NOT NULL DEFAULT 0,
+    slot INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS buying_intents_state ON buying_intents (state);
+CREATE INDEX IF NOT EXISTS buying_intents_buyer ON buying_intents (buyer);
+
+CREATE TABLE IF NOT EXISTS offers (
+    address TEXT PRIMARY KEY,
+    buying_intent TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    seller TEXT NOT NULL,
+    url TEXT NOT NULL,
+    public_price INTEGER NOT NULL,
+    offer_price INTEGER NOT NULL,
+    shipping_price INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    state TEXT NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 0,
+    escrow_amount INTEGER NOT NULL DEFAULT 0,
+    revealed INTEGER NOT NULL DEFAULT 1,
+    expires_at INTEGER NOT NULL DEFAULT 0,
+    bond_amount INTEGER NOT NULL DEFAULT 0,
+    bond_slashed INTEGER NOT NULL DEFAULT 0,
+    closed INTEGER NOT NULL DEFAULT 0,
+    slot INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS offers_buying_intent ON offers (buying_intent);
+
+CREATE TABLE IF NOT EXISTS tracking_details (
+    address TEXT PRIMARY KEY,
+    buying_intent TEXT NOT NULL,
+    offer TEXT NOT NULL,
+    shipment INTEGER NOT NULL,
+    units INTEGER NOT NULL,
+    carrier_name TEXT NOT NULL,
+    tracking_url TEXT NOT NULL,
+    tracking_code TEXT NOT NULL,
+    delivered_at INTEGER,
+    slot INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS tracking_details_buying_intent ON tracking_details (buying_intent);
+CREATE INDEX IF NOT EXISTS tracking_details_offer ON tracking_details (offer);
+
+CREATE TABLE IF NOT EXISTS encrypted_delivery_information (
+    address TEXT PRIMARY KEY,
+    version INTEGER NOT NULL,
+    arbiter TEXT,
+    slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    buying_intent TEXT,
+    PRIMARY KEY (signature, idx)
+);
+
+CREATE INDEX IF NOT EXISTS events_buying_intent ON events (buying_intent);
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuyingIntentRow {
+    pub address: Pubkey,
+    pub id: u64,
+    pub buyer: Pubkey,
+    pub gtin: u64,
+    pub product_name: String,
+    pub shipping_country_code: String,
+    pub shipping_state_code: Option<String>,
+    pub quantity: u16,
+    pub state: String,
+    pub allocated_quantity: u16,
+    pub commit_deadline: Option<i64>,
+    pub reveal_deadline: Option<i64>,
+    pub best_offer: Option<Pubkey>,
+    pub expires_at: i64,
+    pub closed: bool,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferRow {
+    pub address: Pubkey,
+    pub buying_intent: Pubkey,
+    pub id: u64,
+    pub seller: Pubkey,
+    pub url: String,
+    pub public_price: u64,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub mint: Pubkey,
+    pub state: String,
+    pub quantity: u16,
+    pub escrow_amount: u64,
+    pub revealed: bool,
+    pub expires_at: i64,
+    // Bond still locked, 0 once returned or slashed
+    pub bond_amount: u64,
+    pub bond_slashed: bool,
+    pub closed: bool,
+    pub slot: u64,
+}
+
+fn pubkey(value: String) -> rusqlite::Result<Pubkey> {
+    Pubkey::from_str(&value).map_err(|_| {
+        rusqlite::Error::FromSqlConversionFailure(
+            0,
+            rusqlite::types::Type::Text,
+            Box::new(IndexerError::InvalidPubkey(value)),
+        )
+    })
+}
+
+impl BuyingIntentRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            address: pubkey(row.get("address")?)?,
+            id: row.get::<_, i64>("id")? as u64,
+            buyer: pubkey(row.get("buyer")?)?,
+            gtin: row.get::<_, i64>("gtin")? as u64,
+            product_name: row.get("product_name")?,
+            shipping_country_code: row.get("shipping_country_code")?,
+            shipping_state_code: row.get("shipping_state_code")?,
+            quantity: row.get("quantity")?,
+            state: row.get("state")?,
+            allocated_quantity: row.get("allocated_quantity")?,
+            commit_deadline: row.get("commit_deadline")?,
+            reveal_deadline: row.get("reveal_deadline")?,
+            best_offer: row
+                .get::<_, Option<String>>("best_offer")?
+                .map(pubkey)
+                .transpose()?,
+            expires_at: row.get("expires_at")?,
+            closed: row.get("closed")?,
+            slot: row.get::<_, i64>("slot")? as u64,
+        })
+    }
+}
+
+impl OfferRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            address: pubkey(row.get("address")?)?,
+            buying_intent: pubkey(row.get("buying_intent")?)?,
+            id: row.get::<_, i64>("id")? as u64,
+            seller: pubkey(row.get("seller")?)?,
+            url: row.get("url")?,
+            public_price: row.get::<_, i64>("public_price")? as u64,
+            offer_price: row.get::<_, i64>("offer_price")? as u64,
+            shipping_price: row.get::<_, i64>("shipping_price")? as u64,
+            mint: pubkey(row.get("mint")?)?,
+            state: row.get("state")?,
+            quantity: row.get("quantity")?,
+            escrow_amount: row.get::<_, i64>("escrow_amount")? as u64,
+            revealed: row.get("revealed")?,
+            expires_at: row.get("expires_at")?,
+            bond_amount: row.get::<_, i64>("bond_amount")? as u64,
+            bond_slashed: row.get("bond_slashed")?,
+            closed: row.get("closed")?,
+            slot: row.get::<_, i64>("slot")? as u64,
+        })
+    }
+}
+
+// Local SQLite store of bestoffer accounts.
+//
+// u64 values are stored bit for bit in SQLite INTEGER (i64) columns. Every row
+// keeps the slot of its last update, older updates are ignored.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    // Everything in the store is replayed from the chain, so a database of another
+    // schema version is rebuilt and the next sync starts from the first transaction
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        if version != SCHEMA_VERSION {
+            let transaction = conn.transaction()?;
+            for table in TABLES {
+                transaction.execute_batch(&format!("DROP TABLE IF EXISTS {};", table))?;
+            }
+            transaction.execute_batch(SCHEMA)?;
+            transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            transaction.commit()?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    // Upsert a decoded account snapshot
+    pub fn apply_account(
+        &self,
+        address: &Pubkey,
+        slot: u64,
+        account: &BestOfferAccount,
+    ) -> Result<()> {
+        let address = address.to_string();
+        let slot = slot as i64;
+
+        match account {
+            BestOfferAccount::Config(config) => {
+                self.conn.execute(
+                    "INSERT INTO configs (address, admin, fee, paused, buying_intent_increment, offer_increment, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                     ON CONFLICT (address) DO UPDATE SET
+                        admin = excluded.admin, fee = excluded.fee, paused = excluded.paused,
+                        buying_intent_increment = excluded.buying_intent_increment,
+                        offer_increment = excluded.offer_increment, slot = excluded.slot
+                     WHERE excluded.slot >= configs.slot",
+                    params![
+                        address,
+                        config.admin.to_string(),
+                        config.fee,
+                        config.paused,
+                        config.buying_intent_increment as i64,
+                        config.offer_increment as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferAccount::BuyingIntent(buying_intent) => {
+                self.conn.execute(
+                    "INSERT INTO buying_intents (address, id, buyer, gtin, product_name, shipping_country_code,
+                        shipping_state_code, quantity, state, allocated_quantity, commit_deadline,
+                        reveal_deadline, best_offer, expires_at, closed, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, 0, ?15)
+                     ON CONFLICT (address) DO UPDATE SET
+                        state = excluded.state, allocated_quantity = excluded.allocated_quantity,
+                        quantity = excluded.quantity, best_offer = excluded.best_offer,
+                        closed = 0, slot = excluded.slot
+                     WHERE excluded.slot >= buying_intents.slot",
+                    params![
+                        address,
+                        buying_intent.id as i64,
+                        buying_intent.buyer.to_string(),
+                        buying_intent.gtin as i64,
+                        buying_intent.product_name,
+                        buying_intent.shipping_country_code,
+                        buying_intent.shipping_state_code,
+                        buying_intent.quantity,
+                        buying_intent_state_name(buying_intent.state),
+                        buying_intent.allocated_quantity,
+                        buying_intent
+                            .sealed_bid
+                            .as_ref()
+                            .map(|sealed_bid| sealed_bid.commit_deadline),
+                        buying_intent
+                            .sealed_bid
+                            .as_ref()
+                            .map(|sealed_bid| sealed_bid.reveal_deadline),
+                        buying_intent.best_offer.map(|offer| offer.to_string()),
+                        buying_intent.expires_at,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferAccount::Offer(offer) => {
+                self.conn.execute(
+                    "INSERT INTO offers (address, buying_intent, id, seller, url, public_price, offer_price,
+                        shipping_price, mint, state, quantity, escrow_amount, revealed, expires_at, bond_amount,
+                        closed, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, 0, ?16)
+                     ON CONFLICT (address) DO UPDATE SET
+                        url = excluded.url, offer_price = excluded.offer_price,
+                        shipping_price = excluded.shipping_price, state = excluded.state,
+                        quantity = excluded.quantity, escrow_amount = excluded.escrow_amount,
+                        revealed = excluded.revealed, bond_amount = excluded.bond_amount, closed = 0,
+                        slot = excluded.slot
+                     WHERE excluded.slot >= offers.slot",
+                    params![
+                        address,
+                        offer.buying_intent.to_string(),
+                        offer.id as i64,
+                        offer.seller.to_string(),
+                        offer.url,
+                        offer.public_price as i64,
+                        offer.offer_price as i64,
+                        offer.shipping_price as i64,
+                        offer.mint.to_string(),
+                        offer_state_name(offer.state),
+                        offer.quantity,
+                        offer.escrow_amount as i64,
+                        offer.is_revealed(),
+                        offer.expires_at,
+                        offer.bond_amount as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferAccount::TrackingDetails(tracking_details) => {
+                self.conn.execute(
+                    "INSERT INTO tracking_details (address, buying_intent, offer, shipment, units, carrier_name, tracking_url,
+                        tracking_code, delivered_at, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                     ON CONFLICT (address) DO UPDATE SET
+                        carrier_name = excluded.carrier_name, tracking_url = excluded.tracking_url,
+                        tracking_code = excluded.tracking_code, delivered_at = excluded.delivered_at,
+                        slot = excluded.slot
+                     WHERE excluded.slot >= tracking_details.slot",
+                    params![
+                        address,
+                        tracking_details.buying_intent.to_string(),
+                        tracking_details.offer.to_string(),
+                        tracking_details.shipment,
+                        tracking_details.units,
+                        tracking_details.carrier_name,
+                        tracking_details.tracking_url,
+                        tracking_details.tracking_code,
+                        tracking_details.delivered_at,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferAccount::EncryptedDeliveryInformation(delivery_information) => {
+                // Only metadata is indexed, ciphertexts stay on-chain
+                self.conn.execute(
+                    "INSERT INTO encrypted_delivery_information (address, version, arbiter, slot)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (address) DO UPDATE SET
+                        version = excluded.version, arbiter = excluded.arbiter, slot = excluded.slot
+                     WHERE excluded.slot >= encrypted_delivery_information.slot",
+                    params![
+                        address,
+                        delivery_information.version,
+                        delivery_information
+                            .arbiter_sealed_key
+                            .as_ref()
+                            .map(|arbiter_sealed_key| arbiter_sealed_key.arbiter.to_string()),
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferAccount::Treasury(_) | BestOfferAccount::Dispute(_) => {}
+        }
+
+        Ok(())
+    }
+
+    // Record an event and apply the state change it describes
+    pub fn apply_event(
+        &self,
+        signature: &str,
+        index: usize,
+        slot: u64,
+        event: &BestOfferEvent,
+    ) -> Result<()> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO events (signature, idx, slot, name, buying_intent)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                signature,
+                index as i64,
+                slot as i64,
+                event.name(),
+                event.buying_intent().map(|key| key.to_string()),
+            ],
+        )?;
+
+        // Already ingested
+        if inserted == 0 {
+            return Ok(());
+        }
+
+        let slot = slot as i64;
+
+        match event {
+            BestOfferEvent::BuyingIntentCreated(e) => {
+                self.conn.execute(
+                    "INSERT INTO buying_intents (address, id, buyer, gtin, product_name, shipping_country_code,
+                        shipping_state_code, quantity, state, commit_deadline, reveal_deadline, expires_at, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'PUBLISHED', ?9, ?10, ?11, ?12)
+                     ON CONFLICT (address) DO NOTHING",
+                    params![
+                        e.buying_intent.to_string(),
+                        e.id as i64,
+                        e.buyer.to_string(),
+                        e.gtin as i64,
+                        e.product_name,
+                        e.shipping_country_code,
+                        e.shipping_state_code,
+                        e.quantity,
+                        e.commit_deadline,
+                        e.reveal_deadline,
+                        e.expires_at,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferEvent::BuyingIntentCancelled(e) => {
+                self.set_buying_intent_state(&e.buying_intent, "CANCELLED", true, slot)?;
+                for offer in &e.cancelled_offers {
+                    self.set_offer_state(offer, "CANCELLED", false, slot)?;
+                }
+            }
+            BestOfferEvent::OfferCreated(e) => {
+                self.conn.execute(
+                    "INSERT INTO offers (address, buying_intent, id, seller, url, public_price, offer_price,
+                        shipping_price, mint, state, expires_at, bond_amount, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'PUBLISHED', ?10, ?11, ?12)
+                     ON CONFLICT (address) DO NOTHING",
+                    params![
+                        e.offer.to_string(),
+                        e.buying_intent.to_string(),
+                        e.id as i64,
+                        e.seller.to_string(),
+                        e.url,
+                        e.public_price as i64,
+                        e.offer_price as i64,
+                        e.shipping_price as i64,
+                        e.mint.to_string(),
+                        e.expires_at,
+                        e.bond_amount as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferEvent::OfferCommitted(e) => {
+                // Prices stay unknown until the reveal
+                self.conn.execute(
+                    "INSERT INTO offers (address, buying_intent, id, seller, url, public_price, offer_price,
+                        shipping_price, mint, state, revealed, expires_at, bond_amount, slot)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 0, ?7, 'PUBLISHED', 0, ?8, ?9, ?10)
+                     ON CONFLICT (address) DO NOTHING",
+                    params![
+                        e.offer.to_string(),
+                        e.buying_intent.to_string(),
+                        e.id as i64,
+                        e.seller.to_string(),
+                        e.url,
+                        e.public_price as i64,
+                        e.mint.to_string(),
+                        e.expires_at,
+                        e.bond_amount as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferEvent::OfferExpired(e) => {
+                self.set_offer_state(&e.offer, "EXPIRED", true, slot)?;
+            }
+            BestOfferEvent::OfferRevealed(e) => {
+                self.conn.execute(
+                    "UPDATE offers SET offer_price = ?2, shipping_price = ?3, revealed = 1, slot = ?4
+                     WHERE address = ?1 AND slot <= ?4",
+                    params![
+                        e.offer.to_string(),
+                        e.offer_price as i64,
+                        e.shipping_price as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferEvent::BestOfferHighlighted(e) => {
+                self.conn.execute(
+                    "UPDATE buying_intents SET best_offer = ?2, slot = ?3 WHERE address = ?1 AND slot <= ?3",
+                    params![e.buying_intent.to_string(), e.offer.to_string(), slot],
+                )?;
+            }
+            BestOfferEvent::OfferUpdated(e) => {
+                self.conn.execute(
+                    "UPDATE offers SET url = ?2, offer_price = ?3, shipping_price = ?4, slot = ?5
+                     WHERE address = ?1 AND slot <= ?5",
+                    params![
+                        e.offer.to_string(),
+                        e.url,
+                        e.offer_price as i64,
+                        e.shipping_price as i64,
+                        slot,
+                    ],
+                )?;
+            }
+            BestOfferEvent::OfferCancelled(e) => {
+                self.set_offer_state(&e.offer, "CANCELLED", true, slot)?;
+                self.clear_best_offer(&e.offer)?;
+            }
+            BestOfferEvent::OfferAccepted(e) => {
+                // Each accepted offer covers a slice of the buying intent quantity
+                self.conn.execute(
+                    "UPDATE buying_intents SET state = ?2, allocated_quantity = allocated_quantity + ?3, slot = ?4
+                     WHERE address = ?1 AND slot <= ?4",
+                    params![
+                        e.buying_intent.to_string(),
+                        buying_intent_state_name(e.buying_intent_state),
+                        e.quantity,
+                        slot,
+                    ],
+                )?;
+                self.conn.execute(
+                    "UPDATE offers SET state = 'ACCEPTED', quantity = ?2, escrow_amount = ?3, slot = ?4
+                     WHERE address = ?1 AND slot <= ?4",
+                    params![
+                        e.offer.to_string(),
+                        e.quantity,
+                        e.escrow_amount as i64,
+                        slot
+                    ],
+                )?;
+                self.clear_best_offer(&e.offer)?;
+            }
+            BestOfferEvent::BuyingIntentExpired(e) => {
+                // Kept open when offers were accepted before the expiry
+                self.set_buying_intent_state(
+                    &e.buying_intent,
+                    buying_intent_state_name(e.buying_intent_state),
+                    e.buying_intent_closed,
+                    slot,
+                )?;
+            }
+            BestOfferEvent::BuyingIntentConfirmed(e) => {
+                self.set_buying_intent_state(
+                    &e.buying_intent,
+                    buying_intent_state_name(e.buying_intent_state),
+                    false,
+                    slot,
+                )?;
+            }
+            BestOfferEvent::ShipmentRecorded(e) => {
+                self.set_offer_state(&e.offer, "SHIPPED", false, slot)?;
+            }
+            BestOfferEvent::ShipmentDelivered(e) => {
+                self.conn.execute(
+                    "UPDATE tracking_details SET delivered_at = ?2, slot = ?3 WHERE address = ?1 AND slot <= ?3",
+                    params![e.tracking_details.to_string(), e.timestamp, slot],
+                )?;
+                if e.fulfilled {
+                    self.settle_offer(
+                        &e.buying_intent,
+                        e.buying_intent_state,
+                        &e.offer,
+                        "DELIVERED",
+                        slot,
+                    )?;
+                }
+            }
+            BestOfferEvent::DisputeOpened(e) => {
+                self.set_offer_state(&e.offer, "DISPUTED", false, slot)?;
+            }
+            BestOfferEvent::DeliveryAccepted(e) => {
+                self.settle_offer(
+                    &e.buying_intent,
+                    e.buying_intent_state,
+                    &e.offer,
+                    "DELIVERED",
+                    slot,
+                )?;
+            }
+            BestOfferEvent::FundsReleasedAfterTimeout(e) => {
+                self.settle_offer(
+                    &e.buying_intent,
+                    e.buying_intent_state,
+                    &e.offer,
+                    "DELIVERED",
+                    slot,
+                )?;
+            }
+            BestOfferEvent::BuyerRefundedAfterTimeout(e) => {
+                self.settle_offer(
+                    &e.buying_intent,
+                    e.buying_intent_state,
+                    &e.offer,
+                    "CANCELLED",
+                    slot,
+                )?;
+            }
+            BestOfferEvent::DisputeResolved(e) => {
+                // A full refund cancels the offer
+                let offer_state = if e.buyer_share_bps == 10_000 {
+                    "CANCELLED"
+                } else {
+                    "DELIVERED"
+                };
+
+                self.settle_offer(
+                    &e.buying_intent,
+                    e.buying_intent_state,
+                    &e.offer,
+                    offer_state,
+                    slot,
+                )?;
+            }
+            BestOfferEvent::BondReturned(e) => {
+                self.conn.execute(
+                    "UPDATE offers SET bond_amount = 0, slot = ?2 WHERE address = ?1 AND slot <= ?2",
+                    params![e.offer.to_string(), slot],
+                )?;
+            }
+            BestOfferEvent::BondSlashed(e) => {
+                self.conn.execute(
+                    "UPDATE offers SET bond_amount = 0, bond_slashed = 1, slot = ?2
+                     WHERE address = ?1 AND slot <= ?2",
+                    params![e.offer.to_string(), slot],
+                )?;
+            }
+            BestOfferEvent::BuyingIntentClosed(e) => {
+                // Accounts are gone, the final states are kept
+                if e.buying_intent_closed {
+                    self.conn.execute(
+                        "UPDATE buying_intents SET closed = 1, slot = ?2 WHERE address = ?1 AND slot <= ?2",
+                        params![e.buying_intent.to_string(), slot],
+                    )?;
+                }
+                self.conn.execute(
+                    "UPDATE offers SET closed = 1, slot = ?2 WHERE address = ?1 AND slot <= ?2",
+                    params![e.offer.to_string(), slot],
+                )?;
+            }
+            BestOfferEvent::ConfigUpdated(e) => {
+                self.conn.execute(
+                    "UPDATE configs SET admin = ?1, fee = ?2, paused = ?3, slot = ?4 WHERE slot <= ?4",
+                    params![e.admin.to_string(), e.fee, e.paused, slot],
+                )?;
+            }
+            BestOfferEvent::AdminAccepted(e) => {
+                self.conn.execute(
+                    "UPDATE configs SET admin = ?1, slot = ?2 WHERE slot <= ?2",
+                    params![e.admin.to_string(), slot],
+                )?;
+            }
+            BestOfferEvent::ConfigCreated(_)
+            | BestOfferEvent::AdminProposed(_)
+            | BestOfferEvent::TreasuryCreated(_)
+            | BestOfferEvent::TreasuryWithdrawn(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn set_buying_intent_state(
+        &self,
+        address: &Pubkey,
+        state: &str,
+        closed: bool,
+        slot: i64,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE buying_intents SET state = ?2, closed = ?3, slot = ?4 WHERE address = ?1 AND slot <= ?4",
+            params![address.to_string(), state, closed, slot],
+        )?;
+        Ok(())
+    }
+
+    // An accepted offer is settled, the buying intent state comes with the event
+    fn settle_offer(
+        &self,
+        buying_intent: &Pubkey,
+        buying_intent_state: BuyingIntentState,
+        offer: &Pubkey,
+        offer_state: &str,
+        slot: i64,
+    ) -> Result<()> {
+        self.set_buying_intent_state(
+            buying_intent,
+            buying_intent_state_name(buying_intent_state),
+            false,
+            slot,
+        )?;
+        self.set_offer_state(offer, offer_state, false, slot)
+    }
+
+    fn set_offer_state(
+        &self,
+        address: &Pubkey,
+        state: &str,
+        closed: bool,
+        slot: i64,
+    ) -> Result<()> {
+        self.conn.execute(
+            "UPDATE offers SET state = ?2, closed = ?3, slot = ?4 WHERE address = ?1 AND slot <= ?4",
+            params![address.to_string(), state, closed, slot],
+        )?;
+        Ok(())
+    }
+
+    // The program forgets the best offer once it is accepted or cancelled
+    fn clear_best_offer(&self, offer: &Pubkey) -> Result<()> {
+        self.conn.execute(
+            "UPDATE buying_intents SET best_offer = NULL WHERE best_offer = ?1",
+            params![offer.to_string()],
+        )?;
+        Ok(())
+    }
+
+    pub fn buying_intent(&self, address: &Pubkey) -> Result<Option<BuyingIntentRow>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT * FROM buying_intents WHERE address = ?1",
+                params![address.to_string()],
+                BuyingIntentRow::from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn buying_intents_by_state(&self, state: &str) -> Result<Vec<BuyingIntentRow>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT * FROM buying_intents WHERE state = ?1 AND closed = 0 ORDER BY id")?;
+
+        let rows = statement
+            .query_map(params![state], BuyingIntentRow::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(rows)
+    }
+
+    // Revealed offers first, cheapest first
+    pub fn offers_for_buying_intent(&self, buying_intent: &Pubkey) -> Result<Vec<OfferRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT * FROM offers WHERE buying_intent = ?1
+             ORDER BY revealed DESC, offer_price + shipping_price, id",
+        )?;
+
+        let rows = statement
+            .query_map(params![buying_intent.to_string()], OfferRow::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn offer(&self, address: &Pubkey) -> Result<Option<OfferRow>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT * FROM offers WHERE address = ?1",
+                params![address.to_string()],
+                OfferRow::from_row,
+            )
+            .optional()?)
+    }
+
+    // Most recent ingested transaction, used to resume an RPC sync
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT signature FROM events ORDER BY slot DESC, idx DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuilds_a_database_of_another_schema_version() {
+        // Offers table and event log as created before the schema was versioned
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE offers (address TEXT PRIMARY KEY, state TEXT NOT NULL, slot INTEGER NOT NULL);
+             CREATE TABLE events (signature TEXT NOT NULL, idx INTEGER NOT NULL, slot INTEGER NOT NULL,
+                name TEXT NOT NULL, buying_intent TEXT, PRIMARY KEY (signature, idx));
+             INSERT INTO events VALUES ('old', 0, 1, 'OfferCreated', NULL);",
+        )
+        .unwrap();
+
+        let store = Store::with_connection(conn).unwrap();
+
+        let version: i64 = store
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(store.last_signature().unwrap(), None);
+        assert!(store
+            .offers_for_buying_intent(&Pubkey::new_unique())
+            .unwrap()
+            .is_empty());
+
+        // The current schema is kept as is
+        store
+            .conn
+            .execute(
+                "INSERT INTO events VALUES ('new', 0, 2, 'OfferCreated', NULL)",
+                [],
+            )
+            .unwrap();
+        let store = Store::with_connection(store.conn).unwrap();
+        assert_eq!(store.last_signature().unwrap(), Some("new".to_string()));
+    }
+}
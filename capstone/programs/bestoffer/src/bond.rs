@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::{close_vault, split_fee, transfer_from_vault};
+use crate::{BondReturned, BondSlashed, Config, Offer, EVENT_VERSION};
+
+// Seller bond locked with every offer while the config requires one. Lamport
+// bonds sit on top of the offer rent and leave with the offer account, payment
+// mint bonds sit in a bond vault owned by the offer. The bond goes back to the
+// seller unless the shipping deadline is missed or the seller loses a dispute.
+
+// Token accounts of a bond held in the payment mint
+pub struct BondVault<'a, 'info> {
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'a, 'info> BondVault<'a, 'info> {
+    // Optional accounts, only passed for a token bond
+    pub fn from_accounts(
+        token_program: Option<&'a Interface<'info, TokenInterface>>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    ) -> Option<Self> {
+        Some(Self {
+            token_program: token_program?,
+            mint: mint?,
+            vault: vault?,
+        })
+    }
+}
+
+// Where a bond goes, the wallet for lamports or its token account for tokens
+pub struct BondRecipient<'a, 'info> {
+    pub wallet: AccountInfo<'info>,
+    pub token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+}
+
+// The seller locks the bond the config requires when posting the offer
+pub fn lock<'info>(
+    config: &Config,
+    offer: &mut Account<'info, Offer>,
+    seller: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    bond_vault: Option<BondVault<'_, 'info>>,
+    seller_ata: Option<&InterfaceAccount<'info, TokenAccount>>,
+) -> Result<()> {
+    let (amount, in_tokens) = config.offer_bond_for(offer.mint);
+    offer.bond_amount = amount;
+    offer.bond_in_tokens = in_tokens;
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    if !in_tokens {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: seller.to_account_info(),
+                to: offer.to_account_info(),
+            },
+        );
+
+        return transfer(cpi_ctx, amount);
+    }
+
+    let (bond_vault, seller_ata) = bond_vault
+        .zip(seller_ata)
+        .ok_or(BestOfferErrorCode::BondAccountsRequired)?;
+
+    let transfer_accounts = TransferChecked {
+        from: seller_ata.to_account_info(),
+        mint: bond_vault.mint.to_account_info(),
+        to: bond_vault.vault.to_account_info(),
+        authority: seller.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(
+        bond_vault.token_program.to_account_info(),
+        transfer_accounts,
+    );
+
+    transfer_checked(cpi_ctx, amount, bond_vault.mint.decimals)
+}
+
+// The offer was delivered, the whole bond goes back to the seller
+pub fn release<'info>(
+    offer: &mut Account<'info, Offer>,
+    bond_vault: Option<&BondVault<'_, 'info>>,
+    seller: &BondRecipient<'_, 'info>,
+) -> Result<()> {
+    let amount = offer.take_bond();
+    pay(offer, bond_vault, seller, amount)?;
+
+    emit_returned(offer, amount)
+}
+
+// The offer account is closed, what is left of the bond goes back to the seller
+// with the bond vault. Tokens sent to the vault after the fact follow.
+pub fn release_and_close<'info>(
+    offer: &mut Account<'info, Offer>,
+    bond_vault: Option<&BondVault<'_, 'info>>,
+    seller: &BondRecipient<'_, 'info>,
+) -> Result<()> {
+    if !offer.bond_in_tokens {
+        return release(offer, bond_vault, seller);
+    }
+
+    let amount = offer.take_bond();
+    let bond_vault = bond_vault.ok_or(BestOfferErrorCode::BondAccountsRequired)?;
+    let seller_ata = seller
+        .token_account
+        .ok_or(BestOfferErrorCode::BondAccountsRequired)?;
+
+    transfer_from_vault(
+        bond_vault.token_program,
+        offer,
+        bond_vault.mint,
+        bond_vault.vault,
+        seller_ata,
+        bond_vault.vault.amount,
+    )?;
+
+    // The seller paid the bond vault rent
+    close_vault(
+        bond_vault.token_program,
+        offer,
+        bond_vault.vault,
+        &seller.wallet,
+    )?;
+
+    emit_returned(offer, amount)
+}
+
+// The seller missed the shipping deadline or lost a dispute, the treasury keeps its share and the
+// buyer gets the rest
+pub fn slash<'info>(
+    offer: &mut Account<'info, Offer>,
+    bond_vault: Option<&BondVault<'_, 'info>>,
+    treasury_share_bps: u16,
+    buyer: &BondRecipient<'_, 'info>,
+    treasury: &BondRecipient<'_, 'info>,
+) -> Result<()> {
+    let amount = offer.take_bond();
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let (treasury_amount, buyer_amount) = split_fee(amount, treasury_share_bps)?;
+    pay(offer, bond_vault, buyer, buyer_amount)?;
+    pay(offer, bond_vault, treasury, treasury_amount)?;
+
+    emit!(BondSlashed {
+        version: EVENT_VERSION,
+        buying_intent: offer.buying_intent,
+        offer: offer.key(),
+        seller: offer.seller,
+        buyer: buyer.wallet.key(),
+        buyer_amount,
+        treasury_amount,
+        in_tokens: offer.bond_in_tokens,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+fn emit_returned(offer: &Account<'_, Offer>, amount: u64) -> Result<()> {
+    if amount > 0 {
+        emit!(BondReturned {
+            version: EVENT_VERSION,
+            buying_intent: offer.buying_intent,
+            offer: offer.key(),
+            seller: offer.seller,
+            amount,
+            in_tokens: offer.bond_in_tokens,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+
+fn pay<'info>(
+    offer: &Account<'info, Offer>,
+    bond_vault: Option<&BondVault<'_, 'info>>,
+    recipient: &BondRecipient<'_, 'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    if !offer.bond_in_tokens {
+        // The offer is owned by this program, lamports above its rent are moved directly
+        let offer_info = offer.to_account_info();
+        **offer_info.try_borrow_mut_lamports()? = offer_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        **recipient.wallet.try_borrow_mut_lamports()? = recipient
+            .wallet
+            .lamports()
+            .checked_add(amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        return Ok(());
+    }
+
+    let bond_vault = bond_vault.ok_or(BestOfferErrorCode::BondAccountsRequired)?;
+    let token_account = recipient
+        .token_account
+        .ok_or(BestOfferErrorCode::BondAccountsRequired)?;
+
+    transfer_from_vault(
+        bond_vault.token_program,
+        offer,
+        bond_vault.mint,
+        bond_vault.vault,
+        token_account,
+        amount,
+    )
+}
@@ -2,3 +2,15 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Version of the event schema, bumped when an event layout changes
+#[constant]
+pub const EVENT_VERSION: u8 = 8;
+
+// Version of the EncryptedDeliveryInformation envelope accepted by accept_offer
+#[constant]
+pub const DELIVERY_INFORMATION_VERSION: u8 = 1;
+
+// Offer mint paid in native lamports through the SOL vault instead of a token account
+#[constant]
+pub const NATIVE_SOL_MINT: Pubkey = pubkey!("11111111111111111111111111111111");
@@ -1,11 +1,17 @@
+// The derived impls still name the deprecated variants
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
-#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum BuyingIntentState {
-    PUBLISHED, // Initial state
-    CANCELLED, // The buyer
-    CONFIRMED, // When the buyers choose an offer
-    SHIPPED,   // When the seller sent shipping information
-    FULFILLED, // Transaction completed
-    DISPUTED,  // Buyer open a disputed
+    PUBLISHED, // Initial state, offers can be accepted until the quantity is covered
+    CANCELLED, // The buyer, or every accepted offer was refunded
+    CONFIRMED, // When the accepted offers cover the quantity
+    #[deprecated(note = "shipping is tracked per accepted offer, see OfferState::SHIPPED")]
+    SHIPPED, // Kept for the Borsh discriminants of existing accounts
+    FULFILLED, // Every accepted offer is settled, at least one delivered
+    #[deprecated(note = "disputes are opened per accepted offer, see OfferState::DISPUTED")]
+    DISPUTED, // Kept for the Borsh discriminants of existing accounts
+    EXPIRED,   // Nobody was accepted before expires_at, closed by the expire crank
 }
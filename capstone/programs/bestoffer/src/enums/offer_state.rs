@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 
-#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(InitSpace, AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum OfferState {
     PUBLISHED, // Initial state
-    ACCEPTED,
+    ACCEPTED,  // When the buyer locks the slice in escrow
     DELIVERED,
     CANCELLED, // The seller can cancel an offer
+    SHIPPED,   // When the seller sent shipping information
+    DISPUTED,  // Buyer open a disputed
+    EXPIRED,   // Not accepted before expires_at, closed by the expire crank
 }
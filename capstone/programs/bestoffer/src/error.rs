@@ -4,4 +4,150 @@ use anchor_lang::prelude::*;
 pub enum BestOfferErrorCode {
     #[msg("Numerical overflow occurred during calculation")]
     NumericalOverflow,
+    #[msg("Invalid buying intent state transition")]
+    InvalidBuyingIntentStateTransition,
+    #[msg("Invalid offer state transition")]
+    InvalidOfferStateTransition,
+    #[msg("Only the buyer can perform this action")]
+    UnauthorizedBuyer,
+    #[msg("Offer account does not belong to this buying intent")]
+    InvalidOfferAccount,
+    #[msg("Only the seller can perform this action")]
+    UnauthorizedSeller,
+    #[msg("Buying intent is not published")]
+    BuyingIntentNotPublished,
+    #[msg("Offer is not published")]
+    OfferNotPublished,
+    #[msg("Only the admin can perform this action")]
+    UnauthorizedAdmin,
+    #[msg("Only a configured arbiter can perform this action")]
+    UnauthorizedArbiter,
+    #[msg("Too many arbiters")]
+    TooManyArbiters,
+    #[msg("Basis points must be between 0 and 10000")]
+    InvalidBasisPoints,
+    #[msg("Buying intent is not confirmed")]
+    BuyingIntentNotConfirmed,
+    #[msg("Buying intent is not shipped")]
+    BuyingIntentNotShipped,
+    #[msg("Offer is not the accepted offer of this buying intent")]
+    OfferNotAccepted,
+    #[msg("Offer argument does not match the offer account")]
+    OfferMismatch,
+    #[msg("Mint does not match the offer mint")]
+    InvalidMint,
+    #[msg("Seller does not match the offer seller")]
+    InvalidSeller,
+    #[msg("Fee is above the maximum allowed")]
+    FeeTooHigh,
+    #[msg("Marketplace is paused")]
+    MarketplacePaused,
+    #[msg("Only the pending admin can accept the admin role")]
+    UnauthorizedPendingAdmin,
+    #[msg("Not enough funds in the treasury")]
+    InsufficientTreasuryBalance,
+    #[msg("Split shares must match the recipients and sum to at most 10000")]
+    InvalidSplit,
+    #[msg("Timeout must be strictly positive")]
+    InvalidTimeout,
+    #[msg("Delivery timeout has not elapsed yet")]
+    DeliveryTimeoutNotReached,
+    #[msg("Shipping timeout has not elapsed yet")]
+    ShippingTimeoutNotReached,
+    #[msg("Unsupported delivery information version")]
+    UnsupportedDeliveryInformationVersion,
+    #[msg("Encrypted delivery address is empty or too long")]
+    InvalidEncryptedAddressLength,
+    #[msg("Buying intent is not fulfilled or cancelled")]
+    BuyingIntentNotFinished,
+    #[msg("Shipping country code is not an ISO 3166-1 alpha-2 code")]
+    InvalidCountryCode,
+    #[msg("Shipping state code is required for this country")]
+    MissingStateCode,
+    #[msg("Shipping state code is invalid for this country")]
+    InvalidStateCode,
+    #[msg("GTIN is not a valid GTIN-8, GTIN-12, GTIN-13 or GTIN-14")]
+    InvalidGtin,
+    #[msg("Quantity must be greater than zero")]
+    InvalidQuantity,
+    #[msg("Offer price must be greater than zero")]
+    InvalidOfferPrice,
+    #[msg("Offer price is above the public price")]
+    OfferPriceAbovePublicPrice,
+    #[msg("Maximum offers per buying intent must be greater than zero")]
+    InvalidMaxOffers,
+    #[msg("Buying intent already has the maximum number of offers")]
+    TooManyOffers,
+    #[msg("Seller reputation is too low for this offer price")]
+    InsufficientReputation,
+    #[msg("Mint is not accepted for payments")]
+    MintNotAccepted,
+    #[msg("Order total is below the minimum amount of the mint")]
+    AmountBelowMinimum,
+    #[msg("Shipment units must be positive and within the ordered quantity")]
+    InvalidShipmentUnits,
+    #[msg("Shipment was already confirmed by the buyer")]
+    ShipmentAlreadyDelivered,
+    #[msg("Not every ordered unit was shipped")]
+    ShipmentIncomplete,
+    #[msg("Tracking details accounts do not match the shipments of the buying intent")]
+    InvalidTrackingDetails,
+    #[msg("Offer is not shipped")]
+    OfferNotShipped,
+    #[msg("Offer was already shipped or settled")]
+    OfferNotAwaitingShipment,
+    #[msg("Offer is not delivered or cancelled")]
+    OfferNotSettled,
+    #[msg("Buying intent already has the maximum number of accepted offers")]
+    TooManyAcceptedOffers,
+    #[msg("Quantity is above what is left to cover on the buying intent")]
+    QuantityExceedsRemaining,
+    #[msg("Buying intent already has accepted offers")]
+    BuyingIntentHasAcceptedOffers,
+    #[msg("Buying intent has no accepted offer")]
+    NoAcceptedOffer,
+    #[msg("Sealed-bid periods must be positive and at most 30 days")]
+    InvalidSealedBidPeriods,
+    #[msg("Buying intent only takes sealed offers")]
+    SealedBidRequired,
+    #[msg("Buying intent does not take sealed offers")]
+    NotSealedBid,
+    #[msg("Commit phase is closed")]
+    CommitPhaseClosed,
+    #[msg("Reveal window is not open")]
+    RevealPhaseNotOpen,
+    #[msg("Offers cannot be accepted before the reveal window closes")]
+    RevealPhaseNotOver,
+    #[msg("Revealed price does not match the commitment")]
+    InvalidPriceReveal,
+    #[msg("Offer price was already revealed")]
+    OfferAlreadyRevealed,
+    #[msg("Offer price was never revealed")]
+    OfferNotRevealed,
+    #[msg("Every revealed offer of the buying intent must be passed")]
+    IncompleteRevealedOffers,
+    #[msg("Expiry must be in the future and within the allowed lifetime")]
+    InvalidExpiry,
+    #[msg("Buying intent has expired")]
+    BuyingIntentExpired,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Account has not expired yet")]
+    NotExpired,
+    #[msg("Offer bond must lock a positive amount")]
+    InvalidOfferBond,
+    #[msg(
+        "Bond vault, mint, seller token account and token program are required for a token bond"
+    )]
+    BondAccountsRequired,
+    #[msg("Every live offer of the buying intent must be passed")]
+    IncompleteOffers,
+    #[msg("Dispute reason is too long")]
+    DisputeReasonTooLong,
+    #[msg("Disputes can only be opened before the delivery timeout")]
+    DisputeWindowClosed,
+    #[msg("Seller already has an accepted offer on this buying intent")]
+    OfferAlreadyAccepted,
+    #[msg("No revealed offer in this mint competes")]
+    NoOfferInMint,
 }
@@ -0,0 +1,368 @@
+use anchor_lang::prelude::*;
+
+use crate::{BuyingIntentState, Config, OfferBond, ReputationRequirement};
+
+// Every event starts with the schema version (EVENT_VERSION) so indexers can
+// decode old and new layouts side by side. Fields are only ever appended.
+
+#[event]
+pub struct ConfigCreated {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub fee: u16,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub fee: u16,
+    pub paused: bool,
+    pub shipping_timeout: i64,
+    pub delivery_timeout: i64,
+    pub arbiters: Vec<Pubkey>,
+    pub offer_bond: Option<OfferBond>,
+    pub max_offers_per_intent: u16,
+    pub reputation_requirement: Option<ReputationRequirement>,
+}
+
+impl ConfigUpdated {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            version: crate::EVENT_VERSION,
+            admin: config.admin,
+            fee: config.fee,
+            paused: config.paused,
+            shipping_timeout: config.shipping_timeout,
+            delivery_timeout: config.delivery_timeout,
+            arbiters: config.arbiters.clone(),
+            offer_bond: config.offer_bond.clone(),
+            max_offers_per_intent: config.max_offers_per_intent,
+            reputation_requirement: config.reputation_requirement.clone(),
+        }
+    }
+}
+
+#[event]
+pub struct AdminProposed {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub version: u8,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct TreasuryCreated {
+    pub version: u8,
+    pub treasury: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub version: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub destination_amount: u64,
+    pub recipients: Vec<Pubkey>,
+    pub recipient_amounts: Vec<u64>,
+}
+
+#[event]
+pub struct BuyingIntentCreated {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub buyer: Pubkey,
+    pub id: u64,
+    pub gtin: u64,
+    pub product_name: String,
+    pub shipping_country_code: String,
+    pub shipping_state_code: Option<String>,
+    pub quantity: u16,
+    pub timestamp: i64,
+    pub commit_deadline: Option<i64>,
+    pub reveal_deadline: Option<i64>,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct BuyingIntentCancelled {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub buyer: Pubkey,
+    pub cancelled_offers: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuyingIntentExpired {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub buyer: Pubkey,
+    // EXPIRED and closed, or CONFIRMED to keep the slices accepted before expiry
+    pub buying_intent_state: BuyingIntentState,
+    pub buying_intent_closed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferCreated {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub id: u64,
+    pub url: String,
+    pub public_price: u64,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+    pub expires_at: i64,
+    pub bond_amount: u64,
+}
+
+#[event]
+pub struct OfferCommitted {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub id: u64,
+    pub url: String,
+    pub public_price: u64,
+    pub price_commitment: [u8; 32],
+    pub mint: Pubkey,
+    pub timestamp: i64,
+    pub expires_at: i64,
+    pub bond_amount: u64,
+}
+
+#[event]
+pub struct OfferRevealed {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BestOfferHighlighted {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub order_total: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferUpdated {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub url: String,
+    pub offer_price: u64,
+    pub shipping_price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferExpired {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_amount: u64,
+    pub timestamp: i64,
+    // Units of the buying intent covered by the offer
+    pub quantity: u16,
+    // CONFIRMED once the accepted offers cover the quantity
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct BuyingIntentConfirmed {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub buyer: Pubkey,
+    // Units covered by the accepted offers, the rest is given up
+    pub allocated_quantity: u16,
+    pub buying_intent_state: BuyingIntentState,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShipmentRecorded {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub tracking_details: Pubkey,
+    pub seller: Pubkey,
+    pub carrier_name: String,
+    pub timestamp: i64,
+    pub shipment: u16,
+    pub units: u16,
+    pub offer: Pubkey,
+}
+
+#[event]
+pub struct ShipmentDelivered {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub tracking_details: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub shipment: u16,
+    pub units: u16,
+    pub seller_amount: u64,
+    pub fee_amount: u64,
+    // The last unit of the offer was confirmed, the offer is delivered
+    pub fulfilled: bool,
+    pub timestamp: i64,
+    pub offer: Pubkey,
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct DeliveryAccepted {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub seller_amount: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub dispute: Pubkey,
+    pub buyer: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+    pub offer: Pubkey,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub dispute: Pubkey,
+    pub arbiter: Pubkey,
+    pub buyer_share_bps: u16,
+    pub buyer_amount: u64,
+    pub seller_amount: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
+    pub offer: Pubkey,
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct FundsReleasedAfterTimeout {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub seller_amount: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct BuyerRefundedAfterTimeout {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+    pub buying_intent_state: BuyingIntentState,
+}
+
+#[event]
+pub struct BuyingIntentClosed {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+    // The last accepted offer also closes the buying intent
+    pub buying_intent_closed: bool,
+}
+
+#[event]
+pub struct AcceptedMintUpdated {
+    pub version: u8,
+    pub accepted_mint: Pubkey,
+    pub mint: Pubkey,
+    pub fee: u16,
+    pub min_amount: u64,
+    pub enabled: bool,
+    pub reputation_price_threshold: u64,
+}
+
+#[event]
+pub struct BondReturned {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    // Held in the offer mint, in lamports otherwise
+    pub in_tokens: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondSlashed {
+    pub version: u8,
+    pub buying_intent: Pubkey,
+    pub offer: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub buyer_amount: u64,
+    pub treasury_amount: u64,
+    pub in_tokens: bool,
+    pub timestamp: i64,
+}
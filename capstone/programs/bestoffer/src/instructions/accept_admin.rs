@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{AdminAccepted, Config, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ BestOfferErrorCode::UnauthorizedPendingAdmin,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    // Second step of the admin rotation
+    pub fn accept(&mut self) -> Result<()> {
+        self.config.admin = self.new_admin.key();
+        self.config.pending_admin = None;
+
+        emit!(AdminAccepted {
+            version: EVENT_VERSION,
+            admin: self.config.admin,
+        });
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
+use crate::bond::{self, BondRecipient, BondVault};
 use crate::error::*;
-use crate::{BuyingIntent, BuyingIntentState, Config, Offer, OfferState, Treasury};
+use crate::settlement;
+use crate::utils::{split_fee, transfer_from_vault};
+use crate::{BuyingIntent, DeliveryAccepted, Offer, Reputation, Treasury, EVENT_VERSION};
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount};
 use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
 
 #[derive(Accounts)]
@@ -9,18 +12,15 @@ pub struct AcceptDelivery<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
-    #[account(mut)]
-    pub seller: SystemAccount<'info>,
-
     #[account(
         mut,
-        seeds = [b"config"],
-        bump = config.bump,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
     )]
-    pub config: Account<'info, Config>,
+    pub seller: SystemAccount<'info>,
 
     #[account(
         mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
         seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
         bump = buying_intent.bump,
     )]
@@ -28,11 +28,26 @@ pub struct AcceptDelivery<'info> {
 
     #[account(
         mut,
-        seeds = [b"offer", buying_intent.key().as_ref(), seller.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
         bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
     )]
     pub offer: Account<'info, Offer>,
 
+    #[account(
+        mut,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
     #[account(
         mut,
         seeds = [b"treasury"],
@@ -40,13 +55,16 @@ pub struct AcceptDelivery<'info> {
     )]
     pub treasury: Account<'info, Treasury>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        address = offer.mint @ BestOfferErrorCode::InvalidMint,
+    )]
     pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = mint,
-        associated_token::authority = buying_intent,
+        associated_token::authority = offer,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
@@ -64,77 +82,81 @@ pub struct AcceptDelivery<'info> {
     )]
     pub seller_ata: InterfaceAccount<'info, TokenAccount>,
 
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> AcceptDelivery<'info> {
-    pub fn accept_delivery(&mut self) -> Result<()> {
-        // Change status
-        self.buying_intent.state = BuyingIntentState::FULFILLED;
-        self.offer.state = OfferState::DELIVERED;
-
-        Ok(())
+    pub fn accept_delivery(&mut self) -> Result<u64> {
+        settlement::accept_delivery(
+            &mut self.buying_intent,
+            &mut self.offer,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )
     }
 
-    pub fn transfer_funds(&mut self) -> Result<()> {
-        // Get vault amount
-        let vault_amount = self.vault.amount;
-
-        // Calculate fees
-        let fee_amount = vault_amount
-            .checked_mul(self.config.fee as u64)
-            .ok_or(BestOfferErrorCode::NumericalOverflow)?
-            .checked_div(10_000_u64)
-            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
-
-        // Seller funds
-        let seller_amount = vault_amount
-            .checked_sub(fee_amount)
-            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    pub fn transfer_funds(&mut self, amount: u64) -> Result<()> {
+        // Calculate fees with the fee tier of the acceptance, what is left in the vault goes to the seller
+        let (fee_amount, seller_amount) = split_fee(amount, self.offer.fee)?;
 
         // Move fees to treasury
-        let treasury_transfer = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint.to_account_info(),
-            to: self.treasury_ata.to_account_info(),
-            authority: self.buying_intent.to_account_info(),
-        };
-
-        let bytes = self.buying_intent.id.to_le_bytes();
-
-        let seeds = &[
-            b"buy_intent",
-            self.buyer.key.as_ref(),
-            bytes.as_ref(),
-            &[self.buying_intent.bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-
-        let cpi_ctx_treasury = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            treasury_transfer,
-            signer_seeds,
-        );
-
-        transfer_checked(cpi_ctx_treasury, fee_amount, self.mint.decimals)?;
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.treasury_ata,
+            fee_amount,
+        )?;
 
         // Move funds to a seller
-        let seller_transfer = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint.to_account_info(),
-            to: self.seller_ata.to_account_info(),
-            authority: self.buying_intent.to_account_info(),
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.seller_ata,
+            seller_amount,
+        )?;
+
+        // The deal went through, the seller gets the bond back
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: Some(&self.seller_ata),
         };
-
-        let cpi_ctx_seller = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            seller_transfer,
-            signer_seeds,
-        );
-
-        transfer_checked(cpi_ctx_seller, seller_amount, self.mint.decimals)?;
+        bond::release(
+            &mut self.offer,
+            BondVault::from_accounts(
+                Some(&self.token_program),
+                Some(&self.mint),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            &seller,
+        )?;
+
+        emit!(DeliveryAccepted {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            mint: self.mint.key(),
+            seller_amount,
+            fee_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         Ok(())
     }
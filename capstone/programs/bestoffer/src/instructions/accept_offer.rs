@@ -4,22 +4,36 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::{BuyingIntent, BuyingIntentState, EncryptedDeliveryInformation, Offer, OfferState};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::{
+    AcceptedMint, ArbiterSealedKey, BuyingIntent, Config, EncryptedDeliveryInformation, Offer,
+    OfferAccepted, EVENT_VERSION,
+};
 
 #[derive(Accounts)]
 pub struct AcceptOffer<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ BestOfferErrorCode::MarketplacePaused,
+    )]
+    pub config: Account<'info, Config>,
+
     #[account(
         mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
         seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
         bump = buying_intent.bump,
     )]
     pub buying_intent: Account<'info, BuyingIntent>,
 
     #[account(
-        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
         bump = offer.bump,
     )]
     pub offer: Account<'info, Offer>,
@@ -28,14 +42,24 @@ pub struct AcceptOffer<'info> {
         init,
         payer = buyer,
         space = 8 + EncryptedDeliveryInformation::INIT_SPACE,
-        seeds = [b"encrypted_delivery_information", buying_intent.key().as_ref()],
+        seeds = [b"encrypted_delivery_information", offer.key().as_ref()],
         bump,
     )]
     pub encrypted_delivery_information: Account<'info, EncryptedDeliveryInformation>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        address = offer.mint @ BestOfferErrorCode::InvalidMint,
+    )]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    #[account(
+        seeds = [b"accepted_mint", mint.key().as_ref()],
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ BestOfferErrorCode::MintNotAccepted,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
     #[account(
         mut,
         associated_token::mint = mint,
@@ -47,7 +71,7 @@ pub struct AcceptOffer<'info> {
         init,
         payer = buyer,
         associated_token::mint = mint,
-        associated_token::authority = buying_intent,
+        associated_token::authority = offer,
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
@@ -57,47 +81,38 @@ pub struct AcceptOffer<'info> {
 }
 
 impl<'info> AcceptOffer<'info> {
-    // Accept one seller offer
-    pub fn accept_offer(&mut self, offer: Pubkey) -> Result<()> {
-        // Change the state of the buying intent to confirm
-        // TODO, Previous state should be PUBLISHED, if no, throws custom error
-        self.buying_intent.state = BuyingIntentState::CONFIRMED;
-        self.buying_intent.accepted_offer = Some(offer);
-
-        // Update the offer state to reflect the accepted offer
-        // TODO, Previous state should be PUBLISHED, if no, throws custom error
-        self.offer.state = OfferState::ACCEPTED;
-
-        Ok(())
+    // Accept one seller offer for a slice of the quantity
+    pub fn accept_offer(&mut self, offer: Pubkey, quantity: u16) -> Result<()> {
+        // The offer argument must match the offer account
+        require_keys_eq!(offer, self.offer.key(), BestOfferErrorCode::OfferMismatch);
+
+        settlement::accept(
+            &mut self.buying_intent,
+            &mut self.offer,
+            offer,
+            quantity,
+            &self.accepted_mint,
+        )
     }
 
-    // create the encrypted delivery address
+    // Store the encrypted delivery address envelope
     pub fn set_encrypted_delivery_address(
         &mut self,
+        version: u8,
         nonce: [u8; 24],
-        buyer_ephemeral_pubkey: [u8; 32],
-        encrypted_delivery_lastname: Vec<u8>,
-        encrypted_delivery_firstname: Vec<u8>,
-        encrypted_delivery_address_line_1: Vec<u8>,
-        encrypted_delivery_address_line_2: Option<Vec<u8>>,
-        encrypted_delivery_city: Vec<u8>,
-        encrypted_delivery_postal_code: Vec<u8>,
-        encrypted_delivery_country_code: Vec<u8>,
-        encrypted_delivery_state_code: Option<Vec<u8>>,
+        encrypted_address: Vec<u8>,
+        seller_sealed_key: [u8; 80],
+        arbiter_sealed_key: Option<ArbiterSealedKey>,
     ) -> Result<()> {
-        self.encrypted_delivery_information
-            .set_inner(EncryptedDeliveryInformation {
-                nonce,
-                buyer_ephemeral_pubkey,
-                encrypted_delivery_firstname,
-                encrypted_delivery_lastname,
-                encrypted_delivery_address_line_1,
-                encrypted_delivery_address_line_2,
-                encrypted_delivery_city,
-                encrypted_delivery_postal_code,
-                encrypted_delivery_country_code,
-                encrypted_delivery_state_code,
-            });
+        let information = EncryptedDeliveryInformation::new(
+            version,
+            nonce,
+            encrypted_address,
+            seller_sealed_key,
+            arbiter_sealed_key,
+            &self.config.arbiters,
+        )?;
+        self.encrypted_delivery_information.set_inner(information);
 
         Ok(())
     }
@@ -113,7 +128,20 @@ impl<'info> AcceptOffer<'info> {
 
         let cpi_context = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
 
-        transfer_checked(cpi_context, self.offer.offer_price, self.mint.decimals)?;
+        transfer_checked(cpi_context, self.offer.escrow_amount, self.mint.decimals)?;
+
+        emit!(OfferAccepted {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            seller: self.offer.seller,
+            mint: self.mint.key(),
+            quantity: self.offer.quantity,
+            escrow_amount: self.offer.escrow_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
         Ok(())
     }
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::{
+    AcceptedMint, ArbiterSealedKey, BuyingIntent, Config, EncryptedDeliveryInformation, Offer,
+    OfferAccepted, EVENT_VERSION, NATIVE_SOL_MINT,
+};
+
+#[derive(Accounts)]
+pub struct AcceptOfferSol<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ BestOfferErrorCode::MarketplacePaused,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + EncryptedDeliveryInformation::INIT_SPACE,
+        seeds = [b"encrypted_delivery_information", offer.key().as_ref()],
+        bump,
+    )]
+    pub encrypted_delivery_information: Account<'info, EncryptedDeliveryInformation>,
+
+    #[account(
+        seeds = [b"accepted_mint", NATIVE_SOL_MINT.as_ref()],
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ BestOfferErrorCode::MintNotAccepted,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    // System owned vault holding the escrowed lamports
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AcceptOfferSol<'info> {
+    // Accept one seller offer paid in native SOL for a slice of the quantity
+    pub fn accept_offer(&mut self, offer: Pubkey, quantity: u16) -> Result<()> {
+        // The offer argument must match the offer account
+        require_keys_eq!(offer, self.offer.key(), BestOfferErrorCode::OfferMismatch);
+
+        settlement::accept(
+            &mut self.buying_intent,
+            &mut self.offer,
+            offer,
+            quantity,
+            &self.accepted_mint,
+        )
+    }
+
+    // Store the encrypted delivery address envelope
+    pub fn set_encrypted_delivery_address(
+        &mut self,
+        version: u8,
+        nonce: [u8; 24],
+        encrypted_address: Vec<u8>,
+        seller_sealed_key: [u8; 80],
+        arbiter_sealed_key: Option<ArbiterSealedKey>,
+    ) -> Result<()> {
+        let information = EncryptedDeliveryInformation::new(
+            version,
+            nonce,
+            encrypted_address,
+            seller_sealed_key,
+            arbiter_sealed_key,
+            &self.config.arbiters,
+        )?;
+        self.encrypted_delivery_information.set_inner(information);
+
+        Ok(())
+    }
+
+    // Move lamports from buyer to vault
+    pub fn transfer_funds(&mut self) -> Result<()> {
+        // The buyer also funds the vault rent, so it stays rent exempt until cleanup
+        let amount = self
+            .offer
+            .escrow_amount
+            .checked_add(Rent::get()?.minimum_balance(0))
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        let cpi_context = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.sol_vault.to_account_info(),
+            },
+        );
+
+        transfer(cpi_context, amount)?;
+
+        emit!(OfferAccepted {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            seller: self.offer.seller,
+            mint: NATIVE_SOL_MINT,
+            quantity: self.offer.quantity,
+            escrow_amount: self.offer.escrow_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
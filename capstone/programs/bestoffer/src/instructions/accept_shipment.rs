@@ -0,0 +1,189 @@
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::*;
+use crate::settlement;
+use crate::utils::{split_fee, transfer_from_vault};
+use crate::{
+    BuyingIntent, Offer, OfferState, Reputation, ShipmentDelivered, TrackingDetails, Treasury,
+    EVENT_VERSION,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(shipment: u16)]
+pub struct AcceptShipment<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"tracking_details", offer.key().as_ref(), shipment.to_le_bytes().as_ref()],
+        bump = tracking_details.bump,
+    )]
+    pub tracking_details: Account<'info, TrackingDetails>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        address = offer.mint @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AcceptShipment<'info> {
+    pub fn accept_shipment(&mut self, shipment: u16) -> Result<u64> {
+        // The shipment argument selects the tracking details account
+        require_eq!(
+            shipment,
+            self.tracking_details.shipment,
+            BestOfferErrorCode::InvalidTrackingDetails
+        );
+
+        settlement::accept_shipment(
+            &mut self.buying_intent,
+            &mut self.offer,
+            &mut self.tracking_details,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self, amount: u64) -> Result<()> {
+        // Calculate fees on the parcel part with the fee tier of the acceptance
+        let (fee_amount, seller_amount) = split_fee(amount, self.offer.fee)?;
+
+        // Move fees to treasury
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.treasury_ata,
+            fee_amount,
+        )?;
+
+        // Move funds to a seller
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.seller_ata,
+            seller_amount,
+        )?;
+
+        // The last parcel delivers the offer, the seller gets the bond back
+        let fulfilled = self.offer.state == OfferState::DELIVERED;
+        if fulfilled {
+            let seller = BondRecipient {
+                wallet: self.seller.to_account_info(),
+                token_account: Some(&self.seller_ata),
+            };
+            bond::release(
+                &mut self.offer,
+                BondVault::from_accounts(
+                    Some(&self.token_program),
+                    Some(&self.mint),
+                    self.bond_vault.as_ref(),
+                )
+                .as_ref(),
+                &seller,
+            )?;
+        }
+
+        emit!(ShipmentDelivered {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            tracking_details: self.tracking_details.key(),
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            mint: self.mint.key(),
+            shipment: self.tracking_details.shipment,
+            units: self.tracking_details.units,
+            seller_amount,
+            fee_amount,
+            fulfilled,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
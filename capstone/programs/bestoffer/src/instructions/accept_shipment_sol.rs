@@ -0,0 +1,150 @@
+use crate::bond::{self, BondRecipient};
+use crate::error::*;
+use crate::settlement;
+use crate::utils::{split_fee, transfer_from_sol_vault};
+use crate::{
+    BuyingIntent, Offer, OfferState, Reputation, ShipmentDelivered, TrackingDetails, Treasury,
+    EVENT_VERSION, NATIVE_SOL_MINT,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(shipment: u16)]
+pub struct AcceptShipmentSol<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"tracking_details", offer.key().as_ref(), shipment.to_le_bytes().as_ref()],
+        bump = tracking_details.bump,
+    )]
+    pub tracking_details: Account<'info, TrackingDetails>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    // Native SOL fees are kept as lamports on the treasury PDA
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AcceptShipmentSol<'info> {
+    pub fn accept_shipment(&mut self, shipment: u16) -> Result<u64> {
+        // The shipment argument selects the tracking details account
+        require_eq!(
+            shipment,
+            self.tracking_details.shipment,
+            BestOfferErrorCode::InvalidTrackingDetails
+        );
+
+        settlement::accept_shipment(
+            &mut self.buying_intent,
+            &mut self.offer,
+            &mut self.tracking_details,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self, amount: u64, sol_vault_bump: u8) -> Result<()> {
+        // Calculate fees on the parcel part with the native SOL fee tier of the acceptance
+        let (fee_amount, seller_amount) = split_fee(amount, self.offer.fee)?;
+
+        // Move fees to treasury
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.treasury.to_account_info(),
+            fee_amount,
+        )?;
+
+        // Move funds to a seller
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.seller.to_account_info(),
+            seller_amount,
+        )?;
+
+        // The last parcel delivers the offer, the seller gets the lamport bond back
+        let fulfilled = self.offer.state == OfferState::DELIVERED;
+        if fulfilled {
+            let seller = BondRecipient {
+                wallet: self.seller.to_account_info(),
+                token_account: None,
+            };
+            bond::release(&mut self.offer, None, &seller)?;
+        }
+
+        emit!(ShipmentDelivered {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            tracking_details: self.tracking_details.key(),
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            mint: NATIVE_SOL_MINT,
+            shipment: self.tracking_details.shipment,
+            units: self.tracking_details.units,
+            seller_amount,
+            fee_amount,
+            fulfilled,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::{token, token_2022};
+
+use crate::error::BestOfferErrorCode;
+use crate::{AcceptedMint, AcceptedMintUpdated, Config, EVENT_VERSION, NATIVE_SOL_MINT};
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: A token mint or the native SOL sentinel, checked in add
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AcceptedMint::INIT_SPACE,
+        seeds = [b"accepted_mint", mint.key().as_ref()],
+        bump,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddAcceptedMint<'info> {
+    // The fee defaults to the marketplace fee, no offer needs reputation without a threshold
+    pub fn add(
+        &mut self,
+        fee: Option<u16>,
+        min_amount: u64,
+        reputation_price_threshold: Option<u64>,
+        bumps: &AddAcceptedMintBumps,
+    ) -> Result<()> {
+        require!(
+            self.mint.key() == NATIVE_SOL_MINT || self.is_token_mint(),
+            BestOfferErrorCode::InvalidMint
+        );
+
+        let fee = fee.unwrap_or(self.config.fee);
+        require!(fee <= Config::MAX_FEE, BestOfferErrorCode::FeeTooHigh);
+        let reputation_price_threshold = reputation_price_threshold.unwrap_or(u64::MAX);
+
+        self.accepted_mint.set_inner(AcceptedMint {
+            mint: self.mint.key(),
+            fee,
+            min_amount,
+            enabled: true,
+            reputation_price_threshold,
+            bump: bumps.accepted_mint,
+        });
+
+        emit!(AcceptedMintUpdated {
+            version: EVENT_VERSION,
+            accepted_mint: self.accepted_mint.key(),
+            mint: self.accepted_mint.mint,
+            fee,
+            min_amount,
+            enabled: true,
+            reputation_price_threshold,
+        });
+
+        Ok(())
+    }
+
+    fn is_token_mint(&self) -> bool {
+        let owner = *self.mint.owner;
+
+        (owner == token::ID || owner == token_2022::ID)
+            && self
+                .mint
+                .try_borrow_data()
+                .is_ok_and(|data| Mint::try_deserialize(&mut &data[..]).is_ok())
+    }
+}
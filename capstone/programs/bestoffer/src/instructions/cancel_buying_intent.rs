@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::{transition_buying_intent, transition_offer};
+use crate::{
+    BuyingIntent, BuyingIntentCancelled, BuyingIntentState, Offer, OfferState, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct CancelBuyingIntent<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelBuyingIntent<'info> {
+    pub fn cancel(&mut self) -> Result<()> {
+        // Only a PUBLISHED buying intent without accepted offers can be cancelled,
+        // funds are not locked yet
+        require!(
+            self.buying_intent.accepted_offers.is_empty(),
+            BestOfferErrorCode::BuyingIntentHasAcceptedOffers
+        );
+
+        self.buying_intent.state =
+            transition_buying_intent(self.buying_intent.state, BuyingIntentState::CANCELLED)?;
+
+        Ok(())
+    }
+
+    // Mark every outstanding offer passed as remaining accounts as CANCELLED
+    pub fn cancel_offers(&self, offers: &'info [AccountInfo<'info>]) -> Result<()> {
+        // A missing offer would stay PUBLISHED on a closed buying intent
+        require!(
+            offers.len() == self.buying_intent.offer_count as usize,
+            BestOfferErrorCode::IncompleteOffers
+        );
+
+        let buying_intent_key = self.buying_intent.key();
+        let mut cancelled_offers = Vec::with_capacity(offers.len());
+
+        for offer_info in offers.iter() {
+            require!(
+                offer_info.is_writable,
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+
+            let mut offer = Account::<Offer>::try_from(offer_info)?;
+
+            // The offer must belong to this buying intent
+            let (expected_offer, _) = Pubkey::find_program_address(
+                &[b"offer", buying_intent_key.as_ref(), offer.seller.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                expected_offer,
+                offer_info.key(),
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+            require!(
+                !cancelled_offers.contains(&offer_info.key()),
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+
+            offer.state = transition_offer(offer.state, OfferState::CANCELLED)?;
+            offer.exit(&crate::ID)?;
+
+            cancelled_offers.push(offer_info.key());
+        }
+
+        emit!(BuyingIntentCancelled {
+            version: EVENT_VERSION,
+            buying_intent: buying_intent_key,
+            buyer: self.buyer.key(),
+            cancelled_offers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::transition_offer;
+use crate::utils::release_offer_slot;
+use crate::{Offer, OfferCancelled, OfferState, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: Used to derive the offer PDA, the buying intent may already be closed
+    #[account(mut)]
+    pub buying_intent: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller @ BestOfferErrorCode::UnauthorizedSeller,
+        seeds = [b"offer", buying_intent.key().as_ref(), seller.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    // Bond accounts, only passed when the bond is held in the payment mint
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub bond_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bond_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelOffer<'info> {
+    pub fn cancel(&mut self) -> Result<()> {
+        // An accepted offer holds escrow, it is closed by the cleanup once settled
+        require!(
+            self.offer.accepted_at.is_none(),
+            BestOfferErrorCode::OfferNotPublished
+        );
+
+        // Only a live revealed offer takes part in a sealed-bid auction
+        let was_revealed = self.offer.state == OfferState::PUBLISHED && self.offer.is_revealed();
+
+        // Offers already cancelled by the buyer can still be closed to recover the rent
+        if self.offer.state != OfferState::CANCELLED {
+            self.offer.state = transition_offer(self.offer.state, OfferState::CANCELLED)?;
+        }
+
+        release_offer_slot(&self.buying_intent, self.offer.key(), was_revealed)?;
+
+        // Nothing was sold, the bond goes back with the offer
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: self.seller_ata.as_ref(),
+        };
+        bond::release_and_close(
+            &mut self.offer,
+            BondVault::from_accounts(
+                self.token_program.as_ref(),
+                self.bond_mint.as_ref(),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            &seller,
+        )?;
+
+        emit!(OfferCancelled {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
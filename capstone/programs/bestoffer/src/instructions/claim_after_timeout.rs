@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::{split_fee, transfer_from_vault};
+use crate::{
+    BuyingIntent, Config, FundsReleasedAfterTimeout, Offer, Reputation, Treasury, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct ClaimAfterTimeout<'info> {
+    // Anyone can crank the release once the delivery timeout elapsed
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buying_intent.buyer.as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimAfterTimeout<'info> {
+    pub fn claim(&mut self) -> Result<u64> {
+        settlement::claim_after_timeout(
+            &mut self.buying_intent,
+            &mut self.offer,
+            self.config.delivery_timeout,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self, amount: u64) -> Result<()> {
+        let (fee_amount, seller_amount) = split_fee(amount, self.offer.fee)?;
+
+        // Move fees to treasury
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.treasury_ata,
+            fee_amount,
+        )?;
+
+        // Move funds to the seller
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.seller_ata,
+            seller_amount,
+        )?;
+
+        // The deal went through, the seller gets the bond back
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: Some(&self.seller_ata),
+        };
+        bond::release(
+            &mut self.offer,
+            BondVault::from_accounts(
+                Some(&self.token_program),
+                Some(&self.mint),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            &seller,
+        )?;
+
+        emit!(FundsReleasedAfterTimeout {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            mint: self.mint.key(),
+            seller_amount,
+            fee_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::bond::{self, BondRecipient};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::{split_fee, transfer_from_sol_vault};
+use crate::{
+    BuyingIntent, Config, FundsReleasedAfterTimeout, Offer, Reputation, Treasury, EVENT_VERSION,
+    NATIVE_SOL_MINT,
+};
+
+#[derive(Accounts)]
+pub struct ClaimAfterTimeoutSol<'info> {
+    // Anyone can crank the release once the delivery timeout elapsed
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buying_intent.buyer.as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimAfterTimeoutSol<'info> {
+    pub fn claim(&mut self) -> Result<u64> {
+        settlement::claim_after_timeout(
+            &mut self.buying_intent,
+            &mut self.offer,
+            self.config.delivery_timeout,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self, amount: u64, sol_vault_bump: u8) -> Result<()> {
+        let (fee_amount, seller_amount) = split_fee(amount, self.offer.fee)?;
+
+        // Move fees to treasury
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.treasury.to_account_info(),
+            fee_amount,
+        )?;
+
+        // Move funds to the seller
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.seller.to_account_info(),
+            seller_amount,
+        )?;
+
+        // The deal went through, the seller gets the lamport bond back
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: None,
+        };
+        bond::release(&mut self.offer, None, &seller)?;
+
+        emit!(FundsReleasedAfterTimeout {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            mint: NATIVE_SOL_MINT,
+            seller_amount,
+            fee_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::{
+    close_if_initialized, close_tracking_details, close_vault, transfer_from_vault,
+};
+use crate::{BuyingIntent, BuyingIntentClosed, EncryptedDeliveryInformation, Offer, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct CleanupBuyingIntent<'info> {
+    // Anyone can crank the cleanup, the rent always goes back to the original payers
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    // Closed with the last accepted offer once the buying intent is finished
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"encrypted_delivery_information", offer.key().as_ref()],
+        bump,
+    )]
+    pub encrypted_delivery_information: Account<'info, EncryptedDeliveryInformation>,
+
+    /// CHECK: PDA only created when the buyer opened a dispute, closed when initialized
+    #[account(
+        mut,
+        seeds = [b"dispute", offer.key().as_ref()],
+        bump,
+    )]
+    pub dispute: UncheckedAccount<'info>,
+
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CleanupBuyingIntent<'info> {
+    // Clean up one settled offer, its tracking details are passed as remaining accounts
+    pub fn cleanup(&mut self, tracking_details: &[AccountInfo<'info>]) -> Result<()> {
+        settlement::require_settled(&self.buying_intent, &self.offer)?;
+
+        // Tokens sent to the vault after settlement go back to the buyer
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.buyer_ata,
+            self.vault.amount,
+        )?;
+
+        // The buyer paid the vault rent
+        close_vault(
+            &self.token_program,
+            &self.offer,
+            &self.vault,
+            &self.buyer.to_account_info(),
+        )?;
+
+        // A bond not returned on delivery nor slashed goes back to the seller
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: self.seller_ata.as_ref(),
+        };
+        bond::release_and_close(
+            &mut self.offer,
+            BondVault::from_accounts(
+                Some(&self.token_program),
+                Some(&self.mint),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            &seller,
+        )?;
+
+        // The seller paid the tracking details, the buyer paid the dispute
+        close_tracking_details(
+            &self.offer,
+            tracking_details,
+            &self.seller.to_account_info(),
+        )?;
+        close_if_initialized(
+            &self.dispute.to_account_info(),
+            &self.buyer.to_account_info(),
+        )?;
+
+        // The buyer paid the buying intent
+        let buying_intent_closed = settlement::close_slice(&mut self.buying_intent)?;
+        if buying_intent_closed {
+            self.buying_intent.close(self.buyer.to_account_info())?;
+        }
+
+        emit!(BuyingIntentClosed {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            buying_intent_closed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+
+use crate::bond::{self, BondRecipient};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::{close_if_initialized, close_tracking_details, transfer_from_sol_vault};
+use crate::{
+    BuyingIntent, BuyingIntentClosed, EncryptedDeliveryInformation, Offer, EVENT_VERSION,
+    NATIVE_SOL_MINT,
+};
+
+#[derive(Accounts)]
+pub struct CleanupBuyingIntentSol<'info> {
+    // Anyone can crank the cleanup, the rent always goes back to the original payers
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    // Closed with the last accepted offer once the buying intent is finished
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"encrypted_delivery_information", offer.key().as_ref()],
+        bump,
+    )]
+    pub encrypted_delivery_information: Account<'info, EncryptedDeliveryInformation>,
+
+    /// CHECK: PDA only created when the buyer opened a dispute, closed when initialized
+    #[account(
+        mut,
+        seeds = [b"dispute", offer.key().as_ref()],
+        bump,
+    )]
+    pub dispute: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CleanupBuyingIntentSol<'info> {
+    // Clean up one settled offer, its tracking details are passed as remaining accounts
+    pub fn cleanup(
+        &mut self,
+        tracking_details: &[AccountInfo<'info>],
+        sol_vault_bump: u8,
+    ) -> Result<()> {
+        settlement::require_settled(&self.buying_intent, &self.offer)?;
+
+        // The vault rent and any lamports sent after settlement go back to the buyer
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.buyer.to_account_info(),
+            self.sol_vault.lamports(),
+        )?;
+
+        // A bond not returned on delivery nor slashed goes back to the seller
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: None,
+        };
+        bond::release_and_close(&mut self.offer, None, &seller)?;
+
+        // The seller paid the tracking details, the buyer paid the dispute
+        close_tracking_details(
+            &self.offer,
+            tracking_details,
+            &self.seller.to_account_info(),
+        )?;
+        close_if_initialized(
+            &self.dispute.to_account_info(),
+            &self.buyer.to_account_info(),
+        )?;
+
+        // The buyer paid the buying intent
+        let buying_intent_closed = settlement::close_slice(&mut self.buying_intent)?;
+        if buying_intent_closed {
+            self.buying_intent.close(self.buyer.to_account_info())?;
+        }
+
+        emit!(BuyingIntentClosed {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            buying_intent_closed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
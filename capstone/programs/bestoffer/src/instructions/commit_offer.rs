@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::bond::{self, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::validation::validate_expires_at;
+use crate::OfferState::PUBLISHED;
+use crate::{AcceptedMint, BuyingIntent, Config, Offer, OfferCommitted, Reputation, EVENT_VERSION};
+
+#[derive(Accounts)]
+#[instruction(url: String, public_price: u64, price_commitment: [u8; 32], mint: Pubkey)]
+pub struct CommitOffer<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ BestOfferErrorCode::MarketplacePaused,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    // Same PDA as an open offer, one sealed offer per seller
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Offer::INIT_SPACE,
+        seeds = [b"offer", buying_intent.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        seeds = [b"accepted_mint", mint.as_ref()],
+        bump = accepted_mint.bump,
+        constraint = accepted_mint.enabled @ BestOfferErrorCode::MintNotAccepted,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    // Bond accounts, only passed when the config asks for a bond in the payment mint
+    #[account(address = mint @ BestOfferErrorCode::InvalidMint)]
+    pub bond_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bond_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+        token::mint = bond_mint,
+        token::authority = offer,
+        token::token_program = token_program,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitOffer<'info> {
+    pub fn commit(
+        &mut self,
+        url: String,
+        public_price: u64,
+        price_commitment: [u8; 32],
+        mint: Pubkey,
+        expires_at: Option<i64>,
+        bumps: &CommitOfferBumps,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.buying_intent.check_commit_open(now)?;
+
+        // An offer key is accepted at most once per buying intent
+        require!(
+            !self
+                .buying_intent
+                .accepted_offers
+                .contains(&self.offer.key()),
+            BestOfferErrorCode::OfferAlreadyAccepted
+        );
+
+        // Offers live at most as long as their buying intent
+        require!(
+            !self.buying_intent.is_expired(now),
+            BestOfferErrorCode::BuyingIntentExpired
+        );
+        let expires_at = expires_at.unwrap_or(self.buying_intent.expires_at);
+        validate_expires_at(
+            expires_at,
+            self.buying_intent.accepting_from(now),
+            self.buying_intent.expires_at,
+        )?;
+
+        // Price, minimum amount and reputation are checked on reveal
+        require!(public_price > 0, BestOfferErrorCode::InvalidOfferPrice);
+
+        self.seller_reputation
+            .initialize_if_needed(self.seller.key(), bumps.seller_reputation);
+
+        require!(
+            self.buying_intent.offer_count < self.config.max_offers_per_intent,
+            BestOfferErrorCode::TooManyOffers
+        );
+        self.buying_intent.offer_count = self
+            .buying_intent
+            .offer_count
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        self.offer.set_inner(Offer {
+            id: self.config.offer_increment,
+            buying_intent: self.buying_intent.key(),
+            seller: self.seller.key(),
+            url,
+            public_price,
+            offer_price: 0,
+            shipping_price: 0,
+            price_commitment: Some(price_commitment),
+            state: PUBLISHED,
+            mint,
+            quantity: 0,
+            escrow_amount: 0,
+            fee: 0,
+            released_amount: 0,
+            accepted_at: None,
+            shipped_at: None,
+            last_shipped_at: None,
+            shipment_count: 0,
+            shipped_units: 0,
+            delivered_units: 0,
+            expires_at,
+            bond_amount: 0,
+            bond_in_tokens: false,
+            bump: bumps.offer,
+        });
+
+        // Sellers stand behind the offer until it is cancelled or delivered
+        bond::lock(
+            &self.config,
+            &mut self.offer,
+            &self.seller,
+            &self.system_program,
+            BondVault::from_accounts(
+                self.token_program.as_ref(),
+                self.bond_mint.as_ref(),
+                self.bond_vault.as_ref(),
+            ),
+            self.seller_ata.as_ref(),
+        )?;
+
+        self.config.offer_increment += 1;
+
+        emit!(OfferCommitted {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            id: self.offer.id,
+            url: self.offer.url.clone(),
+            public_price,
+            price_commitment,
+            mint,
+            expires_at,
+            timestamp: now,
+            bond_amount: self.offer.bond_amount,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::{BuyingIntent, BuyingIntentConfirmed, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct ConfirmBuyingIntent<'info> {
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+}
+
+impl<'info> ConfirmBuyingIntent<'info> {
+    // The buyer settles for the units already covered by the accepted offers
+    pub fn confirm(&mut self) -> Result<()> {
+        settlement::confirm(&mut self.buying_intent)?;
+
+        emit!(BuyingIntentConfirmed {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            buyer: self.buyer.key(),
+            allocated_quantity: self.buying_intent.allocated_quantity,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
 
+use crate::error::BestOfferErrorCode;
+use crate::validation::{
+    validate_expires_at, validate_gtin, validate_quantity, validate_sealed_bid_periods,
+    validate_shipping_destination,
+};
 use crate::BuyingIntentState::PUBLISHED;
-use crate::{BuyingIntent, Config};
+use crate::{
+    BuyingIntent, BuyingIntentCreated, Config, Reputation, SealedBid, SealedBidPeriods,
+    EVENT_VERSION,
+};
 
 #[derive(Accounts)]
 pub struct CreateBuyingIntent<'info> {
@@ -21,13 +29,24 @@ pub struct CreateBuyingIntent<'info> {
         mut,
         seeds = [b"config"],
         bump = config.bump,
+        constraint = !config.paused @ BestOfferErrorCode::MarketplacePaused,
     )]
     pub config: Account<'info, Config>,
 
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> CreateBuyingIntent<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &mut self,
         gtin: u64,
@@ -35,8 +54,52 @@ impl<'info> CreateBuyingIntent<'info> {
         shipping_country_code: String,
         shipping_state_code: Option<String>,
         quantity: u16,
+        sealed_bid: Option<SealedBidPeriods>,
+        expires_at: Option<i64>,
         bumps: &CreateBuyingIntentBumps,
     ) -> Result<()> {
+        validate_shipping_destination(&shipping_country_code, shipping_state_code.as_deref())?;
+        validate_gtin(gtin)?;
+        validate_quantity(quantity)?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // Both phases start now, the reveal window follows the commit phase
+        let sealed_bid = match sealed_bid {
+            Some(periods) => {
+                validate_sealed_bid_periods(periods.commit_period, periods.reveal_period)?;
+                let commit_deadline = now
+                    .checked_add(periods.commit_period)
+                    .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+                Some(SealedBid {
+                    commit_deadline,
+                    reveal_deadline: commit_deadline
+                        .checked_add(periods.reveal_period)
+                        .ok_or(BestOfferErrorCode::NumericalOverflow)?,
+                    revealed_offers: 0,
+                })
+            }
+            None => None,
+        };
+
+        // The lifetime counts from when offers become acceptable
+        let accepting_from = sealed_bid
+            .as_ref()
+            .map_or(now, |sealed_bid| sealed_bid.reveal_deadline);
+        let expires_at = match expires_at {
+            Some(expires_at) => expires_at,
+            None => accepting_from
+                .checked_add(BuyingIntent::DEFAULT_LIFETIME)
+                .ok_or(BestOfferErrorCode::NumericalOverflow)?,
+        };
+        validate_expires_at(
+            expires_at,
+            accepting_from,
+            accepting_from
+                .checked_add(BuyingIntent::MAX_LIFETIME)
+                .ok_or(BestOfferErrorCode::NumericalOverflow)?,
+        )?;
+
         self.buying_intent.set_inner(BuyingIntent {
             id: self.config.buying_intent_increment,
             buyer: self.buyer.key(),
@@ -45,13 +108,40 @@ impl<'info> CreateBuyingIntent<'info> {
             shipping_country_code,
             shipping_state_code,
             state: PUBLISHED,
-            accepted_offer: None,
+            accepted_offers: Vec::new(),
             quantity,
+            offer_count: 0,
+            allocated_quantity: 0,
+            settled_offers: 0,
+            delivered_offers: 0,
+            closed_offers: 0,
+            sealed_bid: sealed_bid.clone(),
+            best_offer: None,
+            expires_at,
             bump: bumps.buying_intent,
         });
 
         self.config.buying_intent_increment += 1;
 
+        self.buyer_reputation
+            .initialize_if_needed(self.buyer.key(), bumps.buyer_reputation);
+
+        emit!(BuyingIntentCreated {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            buyer: self.buyer.key(),
+            id: self.buying_intent.id,
+            gtin: self.buying_intent.gtin,
+            product_name: self.buying_intent.product_name.clone(),
+            shipping_country_code: self.buying_intent.shipping_country_code.clone(),
+            shipping_state_code: self.buying_intent.shipping_state_code.clone(),
+            quantity: self.buying_intent.quantity,
+            commit_deadline: sealed_bid.as_ref().map(|s| s.commit_deadline),
+            reveal_deadline: sealed_bid.as_ref().map(|s| s.reveal_deadline),
+            expires_at,
+            timestamp: now,
+        });
+
         Ok(())
     }
 }
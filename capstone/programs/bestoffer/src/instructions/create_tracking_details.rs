@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::{BuyingIntent, BuyingIntentState, TrackingDetails};
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::transition_offer;
+use crate::{BuyingIntent, Offer, OfferState, ShipmentRecorded, TrackingDetails, EVENT_VERSION};
 
 #[derive(Accounts)]
 pub struct CreateTrackingDetails<'info> {
@@ -8,17 +10,27 @@ pub struct CreateTrackingDetails<'info> {
     pub seller: Signer<'info>,
 
     #[account(
-        mut,
         seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
         bump = buying_intent.bump,
     )]
     pub buying_intent: Account<'info, BuyingIntent>,
 
+    // Only the seller of an accepted offer can ship its slice
+    #[account(
+        mut,
+        has_one = seller @ BestOfferErrorCode::UnauthorizedSeller,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    // One tracking details account per parcel, numbered by the offer
     #[account(
         init,
         payer = seller,
         space = 8 + TrackingDetails::INIT_SPACE,
-        seeds = [b"tracking_details", buying_intent.key().as_ref()],
+        seeds = [b"tracking_details", offer.key().as_ref(), offer.shipment_count.to_le_bytes().as_ref()],
         bump
     )]
     pub tracking_details: Account<'info, TrackingDetails>,
@@ -32,20 +44,67 @@ impl<'info> CreateTrackingDetails<'info> {
         carrier_name: String,
         tracking_url: String,
         tracking_code: String,
+        units: u16,
         bumps: &CreateTrackingDetailsBumps,
     ) -> Result<()> {
-        // Update Buying Intent state
-        // TODO, Previous state should be CONFIRMED, if no, throws custom error
-        self.buying_intent.state = BuyingIntentState::SHIPPED;
+        let now = Clock::get()?.unix_timestamp;
+        let shipment = self.offer.shipment_count;
+
+        // The first parcel moves the offer from ACCEPTED to SHIPPED,
+        // the next ones are only accepted while it is still SHIPPED
+        if shipment == 0 {
+            self.offer.state = transition_offer(self.offer.state, OfferState::SHIPPED)?;
+            self.offer.shipped_at = Some(now);
+        } else {
+            require!(
+                self.offer.state == OfferState::SHIPPED,
+                BestOfferErrorCode::OfferNotShipped
+            );
+        }
+
+        // Parcels cannot carry more than the accepted quantity
+        let shipped_units = self
+            .offer
+            .shipped_units
+            .checked_add(units)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        require!(
+            units > 0 && shipped_units <= self.offer.quantity,
+            BestOfferErrorCode::InvalidShipmentUnits
+        );
+
+        self.offer.shipped_units = shipped_units;
+        self.offer.shipment_count = shipment
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        self.offer.last_shipped_at = Some(now);
 
         // Save shipping details
         self.tracking_details.set_inner(TrackingDetails {
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            shipment,
+            units,
             carrier_name,
             tracking_url,
             tracking_code,
+            shipped_at: now,
+            delivered_at: None,
             bump: bumps.tracking_details,
         });
 
+        emit!(ShipmentRecorded {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            tracking_details: self.tracking_details.key(),
+            seller: self.seller.key(),
+            carrier_name: self.tracking_details.carrier_name.clone(),
+            shipment,
+            units,
+            timestamp: now,
+        });
+
         Ok(())
     }
 }
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::state_machine::transition_buying_intent;
+use crate::{BuyingIntent, BuyingIntentExpired, BuyingIntentState, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct ExpireBuyingIntent<'info> {
+    // Anyone can crank the expiry, the rent goes back to the buyer
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+}
+
+impl<'info> ExpireBuyingIntent<'info> {
+    pub fn expire(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.buying_intent.state == BuyingIntentState::PUBLISHED,
+            BestOfferErrorCode::BuyingIntentNotPublished
+        );
+        require!(
+            self.buying_intent.is_expired(now),
+            BestOfferErrorCode::NotExpired
+        );
+
+        // Slices accepted before the expiry hold escrow, the buying intent is confirmed with them
+        // and closed by their cleanup. Without any, nothing is locked and it closes right away.
+        let buying_intent_closed = self.buying_intent.accepted_offers.is_empty();
+        if buying_intent_closed {
+            self.buying_intent.state =
+                transition_buying_intent(self.buying_intent.state, BuyingIntentState::EXPIRED)?;
+        } else {
+            settlement::confirm(&mut self.buying_intent)?;
+        }
+
+        emit!(BuyingIntentExpired {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            buyer: self.buyer.key(),
+            buying_intent_state: self.buying_intent.state,
+            buying_intent_closed,
+            timestamp: now,
+        });
+
+        if buying_intent_closed {
+            self.buying_intent.close(self.buyer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::transition_offer;
+use crate::utils::release_offer_slot;
+use crate::{Offer, OfferExpired, OfferState, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct ExpireOffer<'info> {
+    // Anyone can crank the expiry, the rent goes back to the seller
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    /// CHECK: Used to derive the offer PDA, the buying intent may already be closed
+    #[account(mut)]
+    pub buying_intent: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    // Bond accounts, only passed when the bond is held in the payment mint
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub bond_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bond_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ExpireOffer<'info> {
+    pub fn expire(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.offer.is_expired(now), BestOfferErrorCode::NotExpired);
+
+        // Accepted offers hold escrow and follow the deal, cancelled ones are closed by the seller
+        require!(
+            self.offer.state == OfferState::PUBLISHED,
+            BestOfferErrorCode::OfferNotPublished
+        );
+        let was_revealed = self.offer.is_revealed();
+        self.offer.state = transition_offer(self.offer.state, OfferState::EXPIRED)?;
+
+        release_offer_slot(&self.buying_intent, self.offer.key(), was_revealed)?;
+
+        // Nothing was sold, the bond goes back with the offer
+        let seller = BondRecipient {
+            wallet: self.seller.to_account_info(),
+            token_account: self.seller_ata.as_ref(),
+        };
+        bond::release_and_close(
+            &mut self.offer,
+            BondVault::from_accounts(
+                self.token_program.as_ref(),
+                self.bond_mint.as_ref(),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            &seller,
+        )?;
+
+        emit!(OfferExpired {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
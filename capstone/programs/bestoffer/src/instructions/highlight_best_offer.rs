@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::order_total;
+use crate::{
+    BestOfferHighlighted, BuyingIntent, BuyingIntentState, Offer, OfferState, EVENT_VERSION,
+};
+
+// Anyone can point the buyer to the lowest revealed offer once the reveal window is over
+#[derive(Accounts)]
+pub struct HighlightBestOffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+}
+
+impl<'info> HighlightBestOffer<'info> {
+    // Every revealed offer is passed as remaining accounts, only the live ones paid in `mint` compete
+    pub fn highlight(&mut self, mint: Pubkey, offers: &'info [AccountInfo<'info>]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.buying_intent.state == BuyingIntentState::PUBLISHED,
+            BestOfferErrorCode::BuyingIntentNotPublished
+        );
+        let revealed_offers = self
+            .buying_intent
+            .sealed_bid
+            .as_ref()
+            .ok_or(BestOfferErrorCode::NotSealedBid)?
+            .revealed_offers;
+        self.buying_intent.check_reveal_over(now)?;
+
+        // A missing offer could hide a lower price
+        require!(
+            offers.len() == revealed_offers as usize,
+            BestOfferErrorCode::IncompleteRevealedOffers
+        );
+
+        let buying_intent_key = self.buying_intent.key();
+        let quantity = self.buying_intent.remaining_quantity();
+        let mut seen = Vec::with_capacity(offers.len());
+        let mut best: Option<(Pubkey, Pubkey, u64)> = None;
+
+        for offer_info in offers.iter() {
+            let offer = Account::<Offer>::try_from(offer_info)?;
+
+            // The offer must belong to this buying intent and be passed once
+            let (expected_offer, _) = Pubkey::find_program_address(
+                &[b"offer", buying_intent_key.as_ref(), offer.seller.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(
+                expected_offer,
+                offer_info.key(),
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+            require!(
+                !seen.contains(&offer_info.key()),
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+            seen.push(offer_info.key());
+
+            require!(
+                offer.state == OfferState::PUBLISHED && offer.is_revealed(),
+                BestOfferErrorCode::InvalidOfferAccount
+            );
+
+            // Expired offers can no longer be accepted, they don't compete
+            if offer.mint != mint || offer.is_expired(now) {
+                continue;
+            }
+
+            // Ties go to the first offer passed
+            let total = order_total(offer.offer_price, quantity, offer.shipping_price)?;
+            let better = match best {
+                Some((_, _, best_total)) => total < best_total,
+                None => true,
+            };
+            if better {
+                best = Some((offer_info.key(), offer.seller, total));
+            }
+        }
+
+        let (offer, seller, order_total) = best.ok_or(BestOfferErrorCode::NoOfferInMint)?;
+        self.buying_intent.best_offer = Some(offer);
+
+        emit!(BestOfferHighlighted {
+            version: EVENT_VERSION,
+            buying_intent: buying_intent_key,
+            offer,
+            seller,
+            order_total,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::Config;
+use crate::{Config, ConfigCreated, EVENT_VERSION};
 
 #[derive(Accounts)]
 pub struct InitializeConfig<'info> {
@@ -22,13 +22,27 @@ pub struct InitializeConfig<'info> {
 impl<'info> InitializeConfig<'info> {
     pub fn initialize(&mut self, bumps: &InitializeConfigBumps) -> Result<()> {
         self.config.set_inner(Config {
-            admin: self.admin.key.clone(),
+            admin: self.admin.key(),
+            pending_admin: None,
+            paused: false,
             fee: 100,
             buying_intent_increment: 0,
             offer_increment: 0,
+            shipping_timeout: Config::DEFAULT_SHIPPING_TIMEOUT,
+            delivery_timeout: Config::DEFAULT_DELIVERY_TIMEOUT,
+            max_offers_per_intent: Config::DEFAULT_MAX_OFFERS_PER_INTENT,
+            reputation_requirement: None,
+            offer_bond: None,
+            arbiters: vec![self.admin.key()],
             bump: bumps.config,
         });
 
+        emit!(ConfigCreated {
+            version: EVENT_VERSION,
+            admin: self.config.admin,
+            fee: self.config.fee,
+        });
+
         Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use crate::Treasury;
+use crate::{Treasury, TreasuryCreated, EVENT_VERSION};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -24,6 +24,13 @@ impl<'info> InitializeTreasury<'info> {
             admin: self.admin.key(),
             bump: bumps.treasury,
         });
+
+        emit!(TreasuryCreated {
+            version: EVENT_VERSION,
+            treasury: self.treasury.key(),
+            admin: self.treasury.admin,
+        });
+
         Ok(())
     }
 }
@@ -1,15 +1,81 @@
+pub mod accept_admin;
 pub mod accept_delivery;
+pub mod accept_delivery_sol;
 pub mod accept_offer;
+pub mod accept_offer_sol;
+pub mod accept_shipment;
+pub mod accept_shipment_sol;
+pub mod add_accepted_mint;
+pub mod cancel_buying_intent;
+pub mod cancel_offer;
+pub mod claim_after_timeout;
+pub mod claim_after_timeout_sol;
+pub mod cleanup_buying_intent;
+pub mod cleanup_buying_intent_sol;
+pub mod commit_offer;
+pub mod confirm_buying_intent;
 pub mod create_buying_intent;
 pub mod create_offer;
 pub mod create_tracking_details;
+pub mod expire_buying_intent;
+pub mod expire_offer;
+pub mod highlight_best_offer;
 pub mod initialize_config;
 pub mod initialize_treasury;
+pub mod open_dispute;
+pub mod refund_after_timeout;
+pub mod refund_after_timeout_sol;
+pub mod resolve_dispute;
+pub mod resolve_dispute_sol;
+pub mod reveal_offer;
+pub mod set_arbiters;
+pub mod set_max_offers_per_intent;
+pub mod set_offer_bond;
+pub mod set_reputation_requirement;
+pub mod set_timeouts;
+pub mod update_accepted_mint;
+pub mod update_config;
+pub mod update_offer;
+pub mod withdraw_treasury;
+pub mod withdraw_treasury_sol;
 
+pub use accept_admin::*;
 pub use accept_delivery::*;
+pub use accept_delivery_sol::*;
 pub use accept_offer::*;
+pub use accept_offer_sol::*;
+pub use accept_shipment::*;
+pub use accept_shipment_sol::*;
+pub use add_accepted_mint::*;
+pub use cancel_buying_intent::*;
+pub use cancel_offer::*;
+pub use claim_after_timeout::*;
+pub use claim_after_timeout_sol::*;
+pub use cleanup_buying_intent::*;
+pub use cleanup_buying_intent_sol::*;
+pub use commit_offer::*;
+pub use confirm_buying_intent::*;
 pub use create_buying_intent::*;
 pub use create_offer::*;
 pub use create_tracking_details::*;
+pub use expire_buying_intent::*;
+pub use expire_offer::*;
+pub use highlight_best_offer::*;
 pub use initialize_config::*;
 pub use initialize_treasury::*;
+pub use open_dispute::*;
+pub use refund_after_timeout::*;
+pub use refund_after_timeout_sol::*;
+pub use resolve_dispute::*;
+pub use resolve_dispute_sol::*;
+pub use reveal_offer::*;
+pub use set_arbiters::*;
+pub use set_max_offers_per_intent::*;
+pub use set_offer_bond::*;
+pub use set_reputation_requirement::*;
+pub use set_timeouts::*;
+pub use update_accepted_mint::*;
+pub use update_config::*;
+pub use update_offer::*;
+pub use withdraw_treasury::*;
+pub use withdraw_treasury_sol::*;
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::transition_offer;
+use crate::{BuyingIntent, Config, Dispute, DisputeOpened, Offer, OfferState, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        has_one = buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    // Each accepted offer is disputed on its own
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", offer.key().as_ref()],
+        bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenDispute<'info> {
+    pub fn open(&mut self, reason: String, bumps: &OpenDisputeBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            reason.len() <= Dispute::MAX_REASON_LEN,
+            BestOfferErrorCode::DisputeReasonTooLong
+        );
+
+        // Only a SHIPPED offer can be disputed, its vault stays frozen until resolution
+        self.offer.state = transition_offer(self.offer.state, OfferState::DISPUTED)?;
+
+        // Once the delivery timeout elapsed the seller can claim the escrow
+        require!(
+            now < self.offer.delivery_deadline(self.config.delivery_timeout)?,
+            BestOfferErrorCode::DisputeWindowClosed
+        );
+
+        self.dispute.set_inner(Dispute {
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            reason,
+            opened_at: now,
+            resolved_by: None,
+            buyer_share_bps: None,
+            resolved_at: None,
+            bump: bumps.dispute,
+        });
+
+        emit!(DisputeOpened {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            dispute: self.dispute.key(),
+            buyer: self.buyer.key(),
+            reason: self.dispute.reason.clone(),
+            timestamp: self.dispute.opened_at,
+        });
+
+        Ok(())
+    }
+}
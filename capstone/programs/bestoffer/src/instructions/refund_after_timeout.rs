@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::transfer_from_vault;
+use crate::{
+    BuyerRefundedAfterTimeout, BuyingIntent, Config, Offer, Reputation, Treasury, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct RefundAfterTimeout<'info> {
+    // Anyone can crank the refund once the shipping timeout elapsed
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", offer.seller.as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    // Keeps its share of the slashed bond
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundAfterTimeout<'info> {
+    pub fn refund(&mut self) -> Result<()> {
+        settlement::refund_after_timeout(
+            &mut self.buying_intent,
+            &mut self.offer,
+            self.config.shipping_timeout,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self) -> Result<()> {
+        // No fee is charged on a refund
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.buyer_ata,
+            self.offer.escrow_amount,
+        )?;
+
+        // The seller missed the shipping deadline and loses the bond
+        let buyer = BondRecipient {
+            wallet: self.buyer.to_account_info(),
+            token_account: Some(&self.buyer_ata),
+        };
+        let treasury = BondRecipient {
+            wallet: self.treasury.to_account_info(),
+            token_account: self.treasury_ata.as_ref(),
+        };
+        bond::slash(
+            &mut self.offer,
+            BondVault::from_accounts(
+                Some(&self.token_program),
+                Some(&self.mint),
+                self.bond_vault.as_ref(),
+            )
+            .as_ref(),
+            self.config.bond_treasury_share_bps(),
+            &buyer,
+            &treasury,
+        )?;
+
+        emit!(BuyerRefundedAfterTimeout {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            mint: self.mint.key(),
+            refund_amount: self.offer.escrow_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::bond::{self, BondRecipient};
+use crate::error::BestOfferErrorCode;
+use crate::settlement;
+use crate::utils::transfer_from_sol_vault;
+use crate::{
+    BuyerRefundedAfterTimeout, BuyingIntent, Config, Offer, Reputation, Treasury, EVENT_VERSION,
+    NATIVE_SOL_MINT,
+};
+
+#[derive(Accounts)]
+pub struct RefundAfterTimeoutSol<'info> {
+    // Anyone can crank the refund once the shipping timeout elapsed
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", offer.seller.as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    // Keeps its share of the slashed bond
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundAfterTimeoutSol<'info> {
+    pub fn refund(&mut self) -> Result<()> {
+        settlement::refund_after_timeout(
+            &mut self.buying_intent,
+            &mut self.offer,
+            self.config.shipping_timeout,
+            &mut self.seller_reputation,
+        )
+    }
+
+    pub fn transfer_funds(&mut self, sol_vault_bump: u8) -> Result<()> {
+        // No fee is charged on a refund
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.buyer.to_account_info(),
+            self.offer.escrow_amount,
+        )?;
+
+        // The seller missed the shipping deadline and loses the lamport bond
+        let buyer = BondRecipient {
+            wallet: self.buyer.to_account_info(),
+            token_account: None,
+        };
+        let treasury = BondRecipient {
+            wallet: self.treasury.to_account_info(),
+            token_account: None,
+        };
+        bond::slash(
+            &mut self.offer,
+            None,
+            self.config.bond_treasury_share_bps(),
+            &buyer,
+            &treasury,
+        )?;
+
+        emit!(BuyerRefundedAfterTimeout {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            buyer: self.buyer.key(),
+            mint: NATIVE_SOL_MINT,
+            refund_amount: self.offer.escrow_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
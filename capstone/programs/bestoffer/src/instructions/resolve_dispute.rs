@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::bond::{self, BondRecipient, BondVault};
+use crate::error::BestOfferErrorCode;
+use crate::settlement::{self, DisputeSplit};
+use crate::utils::transfer_from_vault;
+use crate::{
+    BuyingIntent, Config, Dispute, DisputeResolved, Offer, Reputation, Treasury, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.arbiters.contains(&arbiter.key()) @ BestOfferErrorCode::UnauthorizedArbiter,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute", offer.key().as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    // Keeps its share of a slashed bond
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(address = offer.mint @ BestOfferErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Only passed when the bond is held in the payment mint
+    #[account(
+        mut,
+        seeds = [b"bond_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub bond_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveDispute<'info> {
+    pub fn resolve(&mut self, buyer_share_bps: u16) -> Result<DisputeSplit> {
+        let split = settlement::resolve_dispute(
+            &mut self.buying_intent,
+            &mut self.offer,
+            buyer_share_bps,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )?;
+
+        self.dispute.resolved_by = Some(self.arbiter.key());
+        self.dispute.buyer_share_bps = Some(buyer_share_bps);
+        self.dispute.resolved_at = Some(Clock::get()?.unix_timestamp);
+
+        Ok(split)
+    }
+
+    pub fn transfer_funds(&mut self, buyer_share_bps: u16, split: DisputeSplit) -> Result<()> {
+        let DisputeSplit {
+            buyer_amount,
+            fee_amount,
+            seller_amount,
+            buyer_won,
+        } = split;
+
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.buyer_ata,
+            buyer_amount,
+        )?;
+
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.treasury_ata,
+            fee_amount,
+        )?;
+
+        transfer_from_vault(
+            &self.token_program,
+            &self.offer,
+            &self.mint,
+            &self.vault,
+            &self.seller_ata,
+            seller_amount,
+        )?;
+
+        // The seller forfeits the bond when losing the dispute, gets it back otherwise
+        let bond_vault = BondVault::from_accounts(
+            Some(&self.token_program),
+            Some(&self.mint),
+            self.bond_vault.as_ref(),
+        );
+        if buyer_won {
+            let buyer = BondRecipient {
+                wallet: self.buyer.to_account_info(),
+                token_account: Some(&self.buyer_ata),
+            };
+            let treasury = BondRecipient {
+                wallet: self.treasury.to_account_info(),
+                token_account: Some(&self.treasury_ata),
+            };
+            bond::slash(
+                &mut self.offer,
+                bond_vault.as_ref(),
+                self.config.bond_treasury_share_bps(),
+                &buyer,
+                &treasury,
+            )?;
+        } else {
+            let seller = BondRecipient {
+                wallet: self.seller.to_account_info(),
+                token_account: Some(&self.seller_ata),
+            };
+            bond::release(&mut self.offer, bond_vault.as_ref(), &seller)?;
+        }
+
+        emit!(DisputeResolved {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            dispute: self.dispute.key(),
+            arbiter: self.arbiter.key(),
+            buyer_share_bps,
+            buyer_amount,
+            seller_amount,
+            fee_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+
+use crate::bond::{self, BondRecipient};
+use crate::error::BestOfferErrorCode;
+use crate::settlement::{self, DisputeSplit};
+use crate::utils::transfer_from_sol_vault;
+use crate::{
+    BuyingIntent, Config, Dispute, DisputeResolved, Offer, Reputation, Treasury, EVENT_VERSION,
+    NATIVE_SOL_MINT,
+};
+
+#[derive(Accounts)]
+pub struct ResolveDisputeSol<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        mut,
+        address = buying_intent.buyer @ BestOfferErrorCode::UnauthorizedBuyer,
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = offer.seller @ BestOfferErrorCode::InvalidSeller,
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.arbiters.contains(&arbiter.key()) @ BestOfferErrorCode::UnauthorizedArbiter,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", buying_intent.key().as_ref(), offer.seller.key().as_ref()],
+        bump = offer.bump,
+        constraint = buying_intent.accepted_offers.contains(&offer.key()) @ BestOfferErrorCode::OfferNotAccepted,
+        constraint = offer.mint == NATIVE_SOL_MINT @ BestOfferErrorCode::InvalidMint,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", buyer.key().as_ref()],
+        bump = buyer_reputation.bump,
+    )]
+    pub buyer_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute", offer.key().as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", offer.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveDisputeSol<'info> {
+    pub fn resolve(&mut self, buyer_share_bps: u16) -> Result<DisputeSplit> {
+        let split = settlement::resolve_dispute(
+            &mut self.buying_intent,
+            &mut self.offer,
+            buyer_share_bps,
+            &mut self.buyer_reputation,
+            &mut self.seller_reputation,
+        )?;
+
+        self.dispute.resolved_by = Some(self.arbiter.key());
+        self.dispute.buyer_share_bps = Some(buyer_share_bps);
+        self.dispute.resolved_at = Some(Clock::get()?.unix_timestamp);
+
+        Ok(split)
+    }
+
+    pub fn transfer_funds(
+        &mut self,
+        buyer_share_bps: u16,
+        split: DisputeSplit,
+        sol_vault_bump: u8,
+    ) -> Result<()> {
+        let DisputeSplit {
+            buyer_amount,
+            fee_amount,
+            seller_amount,
+            buyer_won,
+        } = split;
+
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.buyer.to_account_info(),
+            buyer_amount,
+        )?;
+
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.treasury.to_account_info(),
+            fee_amount,
+        )?;
+
+        transfer_from_sol_vault(
+            &self.system_program,
+            &self.offer,
+            &self.sol_vault,
+            sol_vault_bump,
+            self.seller.to_account_info(),
+            seller_amount,
+        )?;
+
+        // The seller forfeits the lamport bond when losing the dispute, gets it back otherwise
+        if buyer_won {
+            let buyer = BondRecipient {
+                wallet: self.buyer.to_account_info(),
+                token_account: None,
+            };
+            let treasury = BondRecipient {
+                wallet: self.treasury.to_account_info(),
+                token_account: None,
+            };
+            bond::slash(
+                &mut self.offer,
+                None,
+                self.config.bond_treasury_share_bps(),
+                &buyer,
+                &treasury,
+            )?;
+        } else {
+            let seller = BondRecipient {
+                wallet: self.seller.to_account_info(),
+                token_account: None,
+            };
+            bond::release(&mut self.offer, None, &seller)?;
+        }
+
+        emit!(DisputeResolved {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            dispute: self.dispute.key(),
+            arbiter: self.arbiter.key(),
+            buyer_share_bps,
+            buyer_amount,
+            seller_amount,
+            fee_amount,
+            buying_intent_state: self.buying_intent.state,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
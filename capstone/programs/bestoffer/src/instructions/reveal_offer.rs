@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::order_total;
+use crate::validation::validate_offer_price;
+use crate::{
+    AcceptedMint, BuyingIntent, Config, Offer, OfferRevealed, OfferState, Reputation, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct RevealOffer<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        has_one = seller @ BestOfferErrorCode::UnauthorizedSeller,
+        seeds = [b"offer", buying_intent.key().as_ref(), seller.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        seeds = [b"accepted_mint", offer.mint.as_ref()],
+        bump = accepted_mint.bump,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+}
+
+impl<'info> RevealOffer<'info> {
+    pub fn reveal(&mut self, offer_price: u64, shipping_price: u64, salt: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.buying_intent.check_reveal_open(now)?;
+        require!(
+            self.offer.state == OfferState::PUBLISHED,
+            BestOfferErrorCode::OfferNotPublished
+        );
+
+        let offer = self.offer.key();
+        self.offer
+            .reveal(&offer, offer_price, shipping_price, &salt)?;
+
+        // The checks create_offer runs on open offers
+        validate_offer_price(self.offer.public_price, offer_price)?;
+        require!(
+            order_total(offer_price, self.buying_intent.quantity, shipping_price)?
+                >= self.accepted_mint.min_amount,
+            BestOfferErrorCode::AmountBelowMinimum
+        );
+        self.config
+            .check_reputation(&self.seller_reputation, &self.accepted_mint, offer_price)?;
+
+        if let Some(sealed_bid) = self.buying_intent.sealed_bid.as_mut() {
+            sealed_bid.revealed_offers = sealed_bid
+                .revealed_offers
+                .checked_add(1)
+                .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        }
+
+        emit!(OfferRevealed {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            offer_price,
+            shipping_price,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{Config, ConfigUpdated};
+
+#[derive(Accounts)]
+pub struct SetArbiters<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetArbiters<'info> {
+    pub fn set_arbiters(&mut self, arbiters: Vec<Pubkey>) -> Result<()> {
+        require!(
+            arbiters.len() <= Config::MAX_ARBITERS,
+            BestOfferErrorCode::TooManyArbiters
+        );
+
+        self.config.arbiters = arbiters;
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{Config, ConfigUpdated};
+
+#[derive(Accounts)]
+pub struct SetMaxOffersPerIntent<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetMaxOffersPerIntent<'info> {
+    pub fn set_max_offers_per_intent(&mut self, max_offers_per_intent: u16) -> Result<()> {
+        // Buying intents already above a lowered limit keep their offers but get no new one
+        require!(
+            max_offers_per_intent > 0,
+            BestOfferErrorCode::InvalidMaxOffers
+        );
+
+        self.config.max_offers_per_intent = max_offers_per_intent;
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::validation::validate_offer_bond;
+use crate::{Config, ConfigUpdated, OfferBond};
+
+#[derive(Accounts)]
+pub struct SetOfferBond<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetOfferBond<'info> {
+    // None lets sellers post offers for free, offers already posted keep their bond
+    pub fn set_offer_bond(&mut self, offer_bond: Option<OfferBond>) -> Result<()> {
+        if let Some(offer_bond) = &offer_bond {
+            validate_offer_bond(offer_bond)?;
+        }
+
+        self.config.offer_bond = offer_bond;
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{Config, ConfigUpdated, ReputationRequirement};
+
+#[derive(Accounts)]
+pub struct SetReputationRequirement<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetReputationRequirement<'info> {
+    // None lets any seller post offers at any price
+    pub fn set_reputation_requirement(
+        &mut self,
+        reputation_requirement: Option<ReputationRequirement>,
+    ) -> Result<()> {
+        self.config.reputation_requirement = reputation_requirement;
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{Config, ConfigUpdated};
+
+#[derive(Accounts)]
+pub struct SetTimeouts<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> SetTimeouts<'info> {
+    pub fn set_timeouts(&mut self, shipping_timeout: i64, delivery_timeout: i64) -> Result<()> {
+        require!(
+            shipping_timeout > 0 && delivery_timeout > 0,
+            BestOfferErrorCode::InvalidTimeout
+        );
+
+        self.config.shipping_timeout = shipping_timeout;
+        self.config.delivery_timeout = delivery_timeout;
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+}
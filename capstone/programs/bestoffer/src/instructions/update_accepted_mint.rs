@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{AcceptedMint, AcceptedMintUpdated, Config, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct UpdateAcceptedMint<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"accepted_mint", accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+}
+
+impl<'info> UpdateAcceptedMint<'info> {
+    // Only provided values are updated, mints are disabled rather than removed
+    // so that running deals can still settle
+    pub fn update(
+        &mut self,
+        fee: Option<u16>,
+        min_amount: Option<u64>,
+        enabled: Option<bool>,
+        reputation_price_threshold: Option<u64>,
+    ) -> Result<()> {
+        if let Some(fee) = fee {
+            require!(fee <= Config::MAX_FEE, BestOfferErrorCode::FeeTooHigh);
+            self.accepted_mint.fee = fee;
+        }
+
+        if let Some(min_amount) = min_amount {
+            self.accepted_mint.min_amount = min_amount;
+        }
+
+        if let Some(enabled) = enabled {
+            self.accepted_mint.enabled = enabled;
+        }
+
+        if let Some(reputation_price_threshold) = reputation_price_threshold {
+            self.accepted_mint.reputation_price_threshold = reputation_price_threshold;
+        }
+
+        emit!(AcceptedMintUpdated {
+            version: EVENT_VERSION,
+            accepted_mint: self.accepted_mint.key(),
+            mint: self.accepted_mint.mint,
+            fee: self.accepted_mint.fee,
+            min_amount: self.accepted_mint.min_amount,
+            enabled: self.accepted_mint.enabled,
+            reputation_price_threshold: self.accepted_mint.reputation_price_threshold,
+        });
+
+        Ok(())
+    }
+}
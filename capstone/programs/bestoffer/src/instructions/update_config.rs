@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{AdminProposed, Config, ConfigUpdated, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    // Only provided values are updated
+    pub fn update(&mut self, fee: Option<u16>, paused: Option<bool>) -> Result<()> {
+        if let Some(fee) = fee {
+            require!(fee <= Config::MAX_FEE, BestOfferErrorCode::FeeTooHigh);
+            self.config.fee = fee;
+        }
+
+        if let Some(paused) = paused {
+            self.config.paused = paused;
+        }
+
+        emit!(ConfigUpdated::from_config(&self.config));
+
+        Ok(())
+    }
+
+    // First step of the admin rotation, the new admin has to accept
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.config.pending_admin = Some(new_admin);
+
+        emit!(AdminProposed {
+            version: EVENT_VERSION,
+            admin: self.config.admin,
+            pending_admin: new_admin,
+        });
+
+        Ok(())
+    }
+}
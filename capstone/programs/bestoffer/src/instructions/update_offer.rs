@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::order_total;
+use crate::validation::validate_offer_price;
+use crate::{
+    AcceptedMint, BuyingIntent, BuyingIntentState, Config, Offer, OfferState, OfferUpdated,
+    Reputation, EVENT_VERSION,
+};
+
+#[derive(Accounts)]
+pub struct UpdateOffer<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"buy_intent", buying_intent.buyer.key().as_ref(), buying_intent.id.to_le_bytes().as_ref()],
+        bump = buying_intent.bump,
+    )]
+    pub buying_intent: Account<'info, BuyingIntent>,
+
+    #[account(
+        mut,
+        has_one = seller @ BestOfferErrorCode::UnauthorizedSeller,
+        seeds = [b"offer", buying_intent.key().as_ref(), seller.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        seeds = [b"reputation", seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Account<'info, Reputation>,
+
+    #[account(
+        seeds = [b"accepted_mint", offer.mint.as_ref()],
+        bump = accepted_mint.bump,
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+}
+
+impl<'info> UpdateOffer<'info> {
+    pub fn update(&mut self, url: String, offer_price: u64, shipping_price: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Only offers the buyer can still choose are updatable
+        require!(
+            self.buying_intent.state == BuyingIntentState::PUBLISHED,
+            BestOfferErrorCode::BuyingIntentNotPublished
+        );
+        require!(
+            self.offer.state == OfferState::PUBLISHED,
+            BestOfferErrorCode::OfferNotPublished
+        );
+
+        // Sealed prices are bound to their commitment
+        require!(
+            self.buying_intent.sealed_bid.is_none(),
+            BestOfferErrorCode::SealedBidRequired
+        );
+
+        // Stale intents and offers cannot be revived by an update
+        require!(
+            !self.buying_intent.is_expired(now),
+            BestOfferErrorCode::BuyingIntentExpired
+        );
+        require!(
+            !self.offer.is_expired(now),
+            BestOfferErrorCode::OfferExpired
+        );
+
+        // The checks create_offer runs, the public price is fixed at creation
+        validate_offer_price(self.offer.public_price, offer_price)?;
+        require!(
+            order_total(offer_price, self.buying_intent.quantity, shipping_price)?
+                >= self.accepted_mint.min_amount,
+            BestOfferErrorCode::AmountBelowMinimum
+        );
+        self.config
+            .check_reputation(&self.seller_reputation, &self.accepted_mint, offer_price)?;
+
+        self.offer.url = url;
+        self.offer.offer_price = offer_price;
+        self.offer.shipping_price = shipping_price;
+
+        emit!(OfferUpdated {
+            version: EVENT_VERSION,
+            buying_intent: self.buying_intent.key(),
+            offer: self.offer.key(),
+            seller: self.seller.key(),
+            url: self.offer.url.clone(),
+            offer_price,
+            shipping_price,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::split_withdrawal;
+use crate::{Config, Treasury, TreasuryWithdrawn, EVENT_VERSION};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    // Receives whatever is not sent to the split recipients
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    // Split recipients are passed as remaining accounts, with their share in basis points
+    pub fn withdraw(
+        &mut self,
+        amount: Option<u64>,
+        shares_bps: Vec<u16>,
+        recipients: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        // Withdraw the whole balance by default
+        let amount = amount.unwrap_or(self.treasury_ata.amount);
+        require!(
+            amount <= self.treasury_ata.amount,
+            BestOfferErrorCode::InsufficientTreasuryBalance
+        );
+
+        let (recipient_amounts, remaining_amount) =
+            split_withdrawal(amount, &shares_bps, recipients.len())?;
+        let mut recipient_keys = Vec::with_capacity(recipients.len());
+
+        for (recipient, recipient_amount) in recipients.iter().zip(&recipient_amounts) {
+            // Recipients must be token accounts of the withdrawn mint
+            let recipient_account = InterfaceAccount::<TokenAccount>::try_from(recipient)?;
+            require_keys_eq!(
+                recipient_account.mint,
+                self.mint.key(),
+                BestOfferErrorCode::InvalidMint
+            );
+
+            self.transfer_from_treasury(recipient.clone(), *recipient_amount)?;
+
+            recipient_keys.push(recipient.key());
+        }
+
+        self.transfer_from_treasury(self.destination.to_account_info(), remaining_amount)?;
+
+        emit!(TreasuryWithdrawn {
+            version: EVENT_VERSION,
+            mint: self.mint.key(),
+            amount,
+            destination: self.destination.key(),
+            destination_amount: remaining_amount,
+            recipients: recipient_keys,
+            recipient_amounts,
+        });
+
+        Ok(())
+    }
+
+    fn transfer_from_treasury(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let seeds = &[b"treasury".as_ref(), &[self.treasury.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.treasury_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            to,
+            authority: self.treasury.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::split_withdrawal;
+use crate::{Config, Treasury, TreasuryWithdrawn, EVENT_VERSION, NATIVE_SOL_MINT};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasurySol<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ BestOfferErrorCode::UnauthorizedAdmin,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Native SOL fees are the lamports above the treasury rent exemption
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // Receives whatever is not sent to the split recipients
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+}
+
+impl<'info> WithdrawTreasurySol<'info> {
+    // Split recipients are passed as remaining accounts, with their share in basis points
+    pub fn withdraw(
+        &mut self,
+        amount: Option<u64>,
+        shares_bps: Vec<u16>,
+        recipients: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let treasury = self.treasury.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury.lamports().saturating_sub(rent_exempt);
+
+        // Withdraw the whole balance by default
+        let amount = amount.unwrap_or(available);
+        require!(
+            amount <= available,
+            BestOfferErrorCode::InsufficientTreasuryBalance
+        );
+
+        let (recipient_amounts, remaining_amount) =
+            split_withdrawal(amount, &shares_bps, recipients.len())?;
+        let mut recipient_keys = Vec::with_capacity(recipients.len());
+
+        for (recipient, recipient_amount) in recipients.iter().zip(&recipient_amounts) {
+            // Recipients must be wallets
+            SystemAccount::try_from(recipient)?;
+
+            self.transfer_from_treasury(recipient, *recipient_amount)?;
+
+            recipient_keys.push(recipient.key());
+        }
+
+        self.transfer_from_treasury(&self.destination.to_account_info(), remaining_amount)?;
+
+        emit!(TreasuryWithdrawn {
+            version: EVENT_VERSION,
+            mint: NATIVE_SOL_MINT,
+            amount,
+            destination: self.destination.key(),
+            destination_amount: remaining_amount,
+            recipients: recipient_keys,
+            recipient_amounts,
+        });
+
+        Ok(())
+    }
+
+    // The treasury is owned by this program, lamports are moved directly
+    fn transfer_from_treasury(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let treasury = self.treasury.to_account_info();
+        **treasury.try_borrow_mut_lamports()? = treasury
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        **to.try_borrow_mut_lamports()? = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+}
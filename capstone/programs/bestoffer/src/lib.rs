@@ -1,14 +1,21 @@
 #![allow(unexpected_cfgs)]
+pub mod bond;
 pub mod constants;
 pub mod enums;
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod settlement;
 pub mod state;
+pub mod state_machine;
+pub mod utils;
+pub mod validation;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
 pub use enums::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -24,13 +31,83 @@ pub mod bestoffer {
         Ok(())
     }
 
+    // Admin updates the fee and pauses or resumes the marketplace
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee: Option<u16>,
+        paused: Option<bool>,
+    ) -> Result<()> {
+        ctx.accounts.update(fee, paused)?;
+        Ok(())
+    }
+
+    // Admin proposes a new admin
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.propose_admin(new_admin)?;
+        Ok(())
+    }
+
+    // Proposed admin accepts the admin role
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.accept()?;
+        Ok(())
+    }
+
     // Create the global treasury account as PDA
     pub fn create_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
         ctx.accounts.initialize(&ctx.bumps)?;
         Ok(())
     }
 
-    // Buyers creates buying intent as PDA
+    // Admin withdraws fees from a treasury ATA, optionally split between recipients
+    pub fn withdraw_treasury<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawTreasury<'info>>,
+        amount: Option<u64>,
+        shares_bps: Vec<u16>,
+    ) -> Result<()> {
+        ctx.accounts
+            .withdraw(amount, shares_bps, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    // Admin accepts a payment mint, with its own fee, minimum order total and reputation threshold
+    pub fn add_accepted_mint(
+        ctx: Context<AddAcceptedMint>,
+        fee: Option<u16>,
+        min_amount: u64,
+        reputation_price_threshold: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .add(fee, min_amount, reputation_price_threshold, &ctx.bumps)?;
+        Ok(())
+    }
+
+    // Admin updates the fee tier, minimum, status or reputation threshold of an accepted mint
+    pub fn update_accepted_mint(
+        ctx: Context<UpdateAcceptedMint>,
+        fee: Option<u16>,
+        min_amount: Option<u64>,
+        enabled: Option<bool>,
+        reputation_price_threshold: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .update(fee, min_amount, enabled, reputation_price_threshold)?;
+        Ok(())
+    }
+
+    // Admin withdraws the native SOL fees held by the treasury, optionally split between recipients
+    pub fn withdraw_treasury_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawTreasurySol<'info>>,
+        amount: Option<u64>,
+        shares_bps: Vec<u16>,
+    ) -> Result<()> {
+        ctx.accounts
+            .withdraw(amount, shares_bps, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    // Buyers creates buying intent as PDA, optionally as a sealed-bid reverse auction
+    #[allow(clippy::too_many_arguments)]
     pub fn create_buying_intent(
         ctx: Context<CreateBuyingIntent>,
         gtin: u64,
@@ -38,6 +115,8 @@ pub mod bestoffer {
         shipping_country_code: String,
         shipping_state_code: Option<String>,
         quantity: u16,
+        sealed_bid: Option<SealedBidPeriods>,
+        expires_at: Option<i64>,
     ) -> Result<()> {
         ctx.accounts.initialize(
             gtin,
@@ -45,11 +124,30 @@ pub mod bestoffer {
             shipping_country_code,
             shipping_state_code,
             quantity,
+            sealed_bid,
+            expires_at,
             &ctx.bumps,
         )?;
         Ok(())
     }
 
+    // Buyers cancel a published buying intent, the rent goes back to the buyer
+    pub fn cancel_buying_intent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelBuyingIntent<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.cancel()?;
+
+        // Every live offer is passed as remaining accounts
+        ctx.accounts.cancel_offers(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    // Buyers stop looking for sellers and keep the offers accepted so far
+    pub fn confirm_buying_intent(ctx: Context<ConfirmBuyingIntent>) -> Result<()> {
+        ctx.accounts.confirm()?;
+        Ok(())
+    }
+
     // Seller creates an offer as PDA
     pub fn create_offer(
         ctx: Context<CreateOffer>,
@@ -58,6 +156,7 @@ pub mod bestoffer {
         offer_price: u64,
         shipping_price: u64,
         mint: Pubkey,
+        expires_at: Option<i64>,
     ) -> Result<()> {
         ctx.accounts.initialize(
             url,
@@ -65,42 +164,104 @@ pub mod bestoffer {
             offer_price,
             shipping_price,
             mint,
+            expires_at,
+            &ctx.bumps,
+        )?;
+
+        Ok(())
+    }
+
+    // Seller commits to a hidden price on a sealed-bid buying intent
+    pub fn commit_offer(
+        ctx: Context<CommitOffer>,
+        url: String,
+        public_price: u64,
+        price_commitment: [u8; 32],
+        mint: Pubkey,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.commit(
+            url,
+            public_price,
+            price_commitment,
+            mint,
+            expires_at,
             &ctx.bumps,
         )?;
+        Ok(())
+    }
 
+    // Seller reveals the committed price during the reveal window
+    pub fn reveal_offer(
+        ctx: Context<RevealOffer>,
+        offer_price: u64,
+        shipping_price: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.reveal(offer_price, shipping_price, salt)?;
         Ok(())
     }
 
-    // Buyers accept the offer
+    // Anyone records the lowest revealed offer in a mint, every revealed offer as remaining accounts
+    pub fn highlight_best_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HighlightBestOffer<'info>>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.highlight(mint, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    // Anyone closes an expired buying intent, or confirms it with the offers accepted so far
+    pub fn expire_buying_intent(ctx: Context<ExpireBuyingIntent>) -> Result<()> {
+        ctx.accounts.expire()?;
+        Ok(())
+    }
+
+    // Anyone closes an expired offer nobody accepted, the rent goes back to the seller
+    pub fn expire_offer(ctx: Context<ExpireOffer>) -> Result<()> {
+        ctx.accounts.expire()?;
+        Ok(())
+    }
+
+    // Seller withdraws a published offer, the rent goes back to the seller
+    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+        ctx.accounts.cancel()?;
+        Ok(())
+    }
+
+    // Seller re-prices a published offer
+    pub fn update_offer(
+        ctx: Context<UpdateOffer>,
+        url: String,
+        offer_price: u64,
+        shipping_price: u64,
+    ) -> Result<()> {
+        ctx.accounts.update(url, offer_price, shipping_price)?;
+        Ok(())
+    }
+
+    // Buyers accept an offer for a slice of the quantity, each accepted offer gets its own vault
+    #[allow(clippy::too_many_arguments)]
     pub fn accept_offer(
         ctx: Context<AcceptOffer>,
         offer: Pubkey,
+        quantity: u16,
+        version: u8,
         nonce: [u8; 24],
-        buyer_ephemeral_pubkey: [u8; 32],
-        encrypted_delivery_lastname: Vec<u8>,
-        encrypted_delivery_firstname: Vec<u8>,
-        encrypted_delivery_address_line_1: Vec<u8>,
-        encrypted_delivery_address_line_2: Option<Vec<u8>>,
-        encrypted_delivery_city: Vec<u8>,
-        encrypted_delivery_postal_code: Vec<u8>,
-        encrypted_delivery_country_code: Vec<u8>,
-        encrypted_delivery_state_code: Option<Vec<u8>>,
+        encrypted_address: Vec<u8>,
+        seller_sealed_key: [u8; 80],
+        arbiter_sealed_key: Option<ArbiterSealedKey>,
     ) -> Result<()> {
         // Update states on Buying intent and offer
-        ctx.accounts.accept_offer(offer)?;
+        ctx.accounts.accept_offer(offer, quantity)?;
 
-        // Create the delivery address PDA with E2E encrypted data
+        // Create the delivery address PDA with the E2E encrypted envelope
         ctx.accounts.set_encrypted_delivery_address(
+            version,
             nonce,
-            buyer_ephemeral_pubkey,
-            encrypted_delivery_lastname,
-            encrypted_delivery_firstname,
-            encrypted_delivery_address_line_1,
-            encrypted_delivery_address_line_2,
-            encrypted_delivery_city,
-            encrypted_delivery_postal_code,
-            encrypted_delivery_country_code,
-            encrypted_delivery_state_code,
+            encrypted_address,
+            seller_sealed_key,
+            arbiter_sealed_key,
         )?;
 
         // Transfer funds from buyer to vault
@@ -109,22 +270,186 @@ pub mod bestoffer {
         Ok(())
     }
 
-    // Seller create tracking details PDA
+    // Seller create tracking details PDA for one parcel and the units it carries
     pub fn create_tracking_details(
         ctx: Context<CreateTrackingDetails>,
         carrier_name: String,
         tracking_url: String,
         tracking_code: String,
+        units: u16,
     ) -> Result<()> {
         ctx.accounts
-            .initialize(carrier_name, tracking_url, tracking_code, &ctx.bumps)?;
+            .initialize(carrier_name, tracking_url, tracking_code, units, &ctx.bumps)?;
         Ok(())
     }
 
     // Buyers accept delivery
     pub fn accept_delivery(ctx: Context<AcceptDelivery>) -> Result<()> {
-        ctx.accounts.accept_delivery()?;
+        let amount = ctx.accounts.accept_delivery()?;
+        ctx.accounts.transfer_funds(amount)?;
+        Ok(())
+    }
+
+    // Buyer confirms one parcel, its pro-rata part of the escrow goes to the seller
+    pub fn accept_shipment(ctx: Context<AcceptShipment>, shipment: u16) -> Result<()> {
+        let amount = ctx.accounts.accept_shipment(shipment)?;
+        ctx.accounts.transfer_funds(amount)?;
+        Ok(())
+    }
+
+    // Admin sets the arbiters allowed to resolve disputes
+    pub fn set_arbiters(ctx: Context<SetArbiters>, arbiters: Vec<Pubkey>) -> Result<()> {
+        ctx.accounts.set_arbiters(arbiters)?;
+        Ok(())
+    }
+
+    // Buyers open a dispute on a shipped buying intent
+    pub fn open_dispute(ctx: Context<OpenDispute>, reason: String) -> Result<()> {
+        ctx.accounts.open(reason, &ctx.bumps)?;
+        Ok(())
+    }
+
+    // Arbiter splits the escrow between buyer refund and seller payout
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, buyer_share_bps: u16) -> Result<()> {
+        let split = ctx.accounts.resolve(buyer_share_bps)?;
+        ctx.accounts.transfer_funds(buyer_share_bps, split)?;
+        Ok(())
+    }
+
+    // Admin sets the shipping and delivery timeouts
+    pub fn set_timeouts(
+        ctx: Context<SetTimeouts>,
+        shipping_timeout: i64,
+        delivery_timeout: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_timeouts(shipping_timeout, delivery_timeout)?;
+        Ok(())
+    }
+
+    // Admin sets how many live offers a buying intent accepts
+    pub fn set_max_offers_per_intent(
+        ctx: Context<SetMaxOffersPerIntent>,
+        max_offers_per_intent: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_max_offers_per_intent(max_offers_per_intent)?;
+        Ok(())
+    }
+
+    // Admin requires a minimum seller reputation above an offer price
+    pub fn set_reputation_requirement(
+        ctx: Context<SetReputationRequirement>,
+        reputation_requirement: Option<ReputationRequirement>,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_reputation_requirement(reputation_requirement)?;
+        Ok(())
+    }
+
+    // Admin requires sellers to lock a bond with every offer
+    pub fn set_offer_bond(ctx: Context<SetOfferBond>, offer_bond: Option<OfferBond>) -> Result<()> {
+        ctx.accounts.set_offer_bond(offer_bond)?;
+        Ok(())
+    }
+
+    // Anyone releases the funds to the seller once the delivery timeout elapsed
+    pub fn claim_after_timeout(ctx: Context<ClaimAfterTimeout>) -> Result<()> {
+        let amount = ctx.accounts.claim()?;
+        ctx.accounts.transfer_funds(amount)?;
+        Ok(())
+    }
+
+    // Anyone refunds the buyer once the shipping timeout elapsed
+    pub fn refund_after_timeout(ctx: Context<RefundAfterTimeout>) -> Result<()> {
+        ctx.accounts.refund()?;
         ctx.accounts.transfer_funds()?;
         Ok(())
     }
+
+    // Close the accounts of a settled offer, the buying intent goes with the last one
+    pub fn cleanup_buying_intent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CleanupBuyingIntent<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.cleanup(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    // Buyers accept an offer paid in native SOL for a slice of the quantity
+    #[allow(clippy::too_many_arguments)]
+    pub fn accept_offer_sol(
+        ctx: Context<AcceptOfferSol>,
+        offer: Pubkey,
+        quantity: u16,
+        version: u8,
+        nonce: [u8; 24],
+        encrypted_address: Vec<u8>,
+        seller_sealed_key: [u8; 80],
+        arbiter_sealed_key: Option<ArbiterSealedKey>,
+    ) -> Result<()> {
+        // Update states on Buying intent and offer
+        ctx.accounts.accept_offer(offer, quantity)?;
+
+        // Create the delivery address PDA with the E2E encrypted envelope
+        ctx.accounts.set_encrypted_delivery_address(
+            version,
+            nonce,
+            encrypted_address,
+            seller_sealed_key,
+            arbiter_sealed_key,
+        )?;
+
+        // Transfer lamports from buyer to the SOL vault
+        ctx.accounts.transfer_funds()?;
+
+        Ok(())
+    }
+
+    // Buyer accepts the delivery, the SOL vault pays the seller and the treasury
+    pub fn accept_delivery_sol(ctx: Context<AcceptDeliverySol>) -> Result<()> {
+        let amount = ctx.accounts.accept_delivery()?;
+        ctx.accounts.transfer_funds(amount, ctx.bumps.sol_vault)?;
+        Ok(())
+    }
+
+    // Anyone releases the SOL vault to the seller once the delivery timeout elapsed
+    pub fn claim_after_timeout_sol(ctx: Context<ClaimAfterTimeoutSol>) -> Result<()> {
+        let amount = ctx.accounts.claim()?;
+        ctx.accounts.transfer_funds(amount, ctx.bumps.sol_vault)?;
+        Ok(())
+    }
+
+    // Anyone refunds the buyer from the SOL vault once the shipping timeout elapsed
+    pub fn refund_after_timeout_sol(ctx: Context<RefundAfterTimeoutSol>) -> Result<()> {
+        ctx.accounts.refund()?;
+        ctx.accounts.transfer_funds(ctx.bumps.sol_vault)?;
+        Ok(())
+    }
+
+    // Arbiter splits the SOL vault between buyer and seller
+    pub fn resolve_dispute_sol(
+        ctx: Context<ResolveDisputeSol>,
+        buyer_share_bps: u16,
+    ) -> Result<()> {
+        let split = ctx.accounts.resolve(buyer_share_bps)?;
+        ctx.accounts
+            .transfer_funds(buyer_share_bps, split, ctx.bumps.sol_vault)?;
+        Ok(())
+    }
+
+    // Close the accounts of a settled native SOL offer, the vault is emptied to the buyer
+    pub fn cleanup_buying_intent_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CleanupBuyingIntentSol<'info>>,
+    ) -> Result<()> {
+        ctx.accounts
+            .cleanup(ctx.remaining_accounts, ctx.bumps.sol_vault)?;
+        Ok(())
+    }
+
+    // Buyer confirms one parcel paid in native SOL, its pro-rata part goes to the seller
+    pub fn accept_shipment_sol(ctx: Context<AcceptShipmentSol>, shipment: u16) -> Result<()> {
+        let amount = ctx.accounts.accept_shipment(shipment)?;
+        ctx.accounts.transfer_funds(amount, ctx.bumps.sol_vault)?;
+        Ok(())
+    }
 }
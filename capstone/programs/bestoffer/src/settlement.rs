@@ -0,0 +1,557 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::state_machine::{transition_buying_intent, transition_offer};
+use crate::utils::{bps_of, order_total, split_fee, BPS_DENOMINATOR};
+use crate::{
+    AcceptedMint, BuyingIntent, BuyingIntentState, Offer, OfferState, Reputation, TrackingDetails,
+};
+
+// Deal lifecycle shared by the token and the native SOL escrows, the
+// instructions only differ in how they move the funds. Every accepted offer
+// is a slice of the buying intent quantity with its own vault.
+
+// The buyer picks an offer for `quantity` units, the slice total is locked in its vault
+pub fn accept(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    offer_key: Pubkey,
+    quantity: u16,
+    accepted_mint: &AcceptedMint,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    // Offers can be accepted until the quantity is covered
+    require!(
+        buying_intent.state == BuyingIntentState::PUBLISHED,
+        BestOfferErrorCode::BuyingIntentNotPublished
+    );
+
+    // Sealed prices are compared once the reveal window is over, unrevealed offers are void
+    buying_intent.check_reveal_over(now)?;
+    require!(offer.is_revealed(), BestOfferErrorCode::OfferNotRevealed);
+
+    // Stale intents and offers cannot lock funds
+    require!(
+        !buying_intent.is_expired(now),
+        BestOfferErrorCode::BuyingIntentExpired
+    );
+    require!(!offer.is_expired(now), BestOfferErrorCode::OfferExpired);
+
+    require!(
+        buying_intent.accepted_offers.len() < BuyingIntent::MAX_ACCEPTED_OFFERS,
+        BestOfferErrorCode::TooManyAcceptedOffers
+    );
+    require!(quantity > 0, BestOfferErrorCode::InvalidQuantity);
+    require!(
+        quantity <= buying_intent.remaining_quantity(),
+        BestOfferErrorCode::QuantityExceedsRemaining
+    );
+
+    // Update the offer state to reflect the accepted offer, previous state must be PUBLISHED
+    offer.state = transition_offer(offer.state, OfferState::ACCEPTED)?;
+    offer.quantity = quantity;
+    offer.accepted_at = Some(now);
+
+    // Unit price x quantity + shipping is locked in the vault
+    offer.escrow_amount = order_total(offer.offer_price, quantity, offer.shipping_price)?;
+    require!(
+        offer.escrow_amount >= accepted_mint.min_amount,
+        BestOfferErrorCode::AmountBelowMinimum
+    );
+
+    // Later fee tier changes only apply to new deals
+    offer.fee = accepted_mint.fee;
+
+    buying_intent.remove_revealed_offer(offer_key)?;
+    buying_intent.accepted_offers.push(offer_key);
+    buying_intent.allocated_quantity = buying_intent
+        .allocated_quantity
+        .checked_add(quantity)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    // The buying intent is confirmed once every unit has a seller
+    if buying_intent.remaining_quantity() == 0 {
+        buying_intent.state =
+            transition_buying_intent(buying_intent.state, BuyingIntentState::CONFIRMED)?;
+    }
+
+    Ok(())
+}
+
+// The buyer stops looking for sellers and keeps the slices accepted so far
+pub fn confirm(buying_intent: &mut BuyingIntent) -> Result<()> {
+    require!(
+        !buying_intent.accepted_offers.is_empty(),
+        BestOfferErrorCode::NoAcceptedOffer
+    );
+
+    buying_intent.state =
+        transition_buying_intent(buying_intent.state, BuyingIntentState::CONFIRMED)?;
+
+    // Every slice may already be settled
+    finalize(buying_intent)
+}
+
+// The buyer confirms the delivery, the seller gets what is left in the vault
+pub fn accept_delivery(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<u64> {
+    // A disputed offer can only be settled by an arbiter
+    require!(
+        offer.state == OfferState::SHIPPED,
+        BestOfferErrorCode::OfferNotShipped
+    );
+
+    release_remaining(buying_intent, offer, buyer_reputation, seller_reputation)
+}
+
+// The buyer confirms one parcel, the seller gets its pro-rata part of the escrow
+pub fn accept_shipment(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    tracking_details: &mut TrackingDetails,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<u64> {
+    // A disputed offer can only be settled by an arbiter
+    require!(
+        offer.state == OfferState::SHIPPED,
+        BestOfferErrorCode::OfferNotShipped
+    );
+    require!(
+        tracking_details.delivered_at.is_none(),
+        BestOfferErrorCode::ShipmentAlreadyDelivered
+    );
+
+    let amount = offer.release_for_units(tracking_details.units)?;
+
+    tracking_details.delivered_at = Some(Clock::get()?.unix_timestamp);
+    offer.delivered_units = offer
+        .delivered_units
+        .checked_add(tracking_details.units)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    offer.released_amount = offer
+        .released_amount
+        .checked_add(amount)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    // The last unit of the slice fulfills it
+    if offer.delivered_units == offer.quantity {
+        fulfill(buying_intent, offer, buyer_reputation, seller_reputation)?;
+    }
+
+    Ok(amount)
+}
+
+// Nobody complained during the delivery timeout, the seller gets what is left in the vault
+pub fn claim_after_timeout(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    delivery_timeout: i64,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<u64> {
+    check_claimable(offer, delivery_timeout, Clock::get()?.unix_timestamp)?;
+
+    release_remaining(buying_intent, offer, buyer_reputation, seller_reputation)
+}
+
+// The seller can claim a fully shipped, undisputed offer once the delivery timeout elapsed
+fn check_claimable(offer: &Offer, delivery_timeout: i64, now: i64) -> Result<()> {
+    // An open dispute freezes the vault
+    require!(
+        offer.state == OfferState::SHIPPED,
+        BestOfferErrorCode::OfferNotShipped
+    );
+
+    // Missing units are settled by the buyer or an arbiter, never by the timeout
+    require!(
+        offer.shipped_units == offer.quantity,
+        BestOfferErrorCode::ShipmentIncomplete
+    );
+
+    require!(
+        now >= offer.delivery_deadline(delivery_timeout)?,
+        BestOfferErrorCode::DeliveryTimeoutNotReached
+    );
+
+    Ok(())
+}
+
+// Nothing was shipped during the shipping timeout, the buyer gets the slice refunded
+pub fn refund_after_timeout(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    shipping_timeout: i64,
+    seller_reputation: &mut Reputation,
+) -> Result<()> {
+    // Only an ACCEPTED offer (never shipped) can be refunded
+    require!(
+        offer.state == OfferState::ACCEPTED,
+        BestOfferErrorCode::OfferNotAwaitingShipment
+    );
+
+    let accepted_at = offer
+        .accepted_at
+        .ok_or(BestOfferErrorCode::OfferNotAwaitingShipment)?;
+
+    let deadline = accepted_at
+        .checked_add(shipping_timeout)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    require!(
+        Clock::get()?.unix_timestamp >= deadline,
+        BestOfferErrorCode::ShippingTimeoutNotReached
+    );
+
+    offer.state = transition_offer(offer.state, OfferState::CANCELLED)?;
+    settle(buying_intent, false)?;
+
+    seller_reputation.record_missed_shipping_deadline()
+}
+
+// How an arbiter decision splits the escrow
+pub struct DisputeSplit {
+    pub buyer_amount: u64,
+    pub fee_amount: u64,
+    pub seller_amount: u64,
+    // The seller lost the dispute and forfeits the bond
+    pub buyer_won: bool,
+}
+
+// The buyer wins a dispute when getting at least half of the escrow back
+pub fn buyer_wins_dispute(buyer_share_bps: u16) -> bool {
+    buyer_share_bps as u64 * 2 >= BPS_DENOMINATOR
+}
+
+// An arbiter splits what is left in the vault between buyer and seller
+pub fn resolve_dispute(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    buyer_share_bps: u16,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<DisputeSplit> {
+    require!(
+        buyer_share_bps as u64 <= BPS_DENOMINATOR,
+        BestOfferErrorCode::InvalidBasisPoints
+    );
+
+    // A full refund cancels the slice, any seller payout delivers it
+    let offer_state = if buyer_share_bps as u64 == BPS_DENOMINATOR {
+        OfferState::CANCELLED
+    } else {
+        OfferState::DELIVERED
+    };
+
+    offer.state = transition_offer(offer.state, offer_state)?;
+
+    // Buyer refund, fees are only charged on the seller portion
+    let remaining_escrow = offer.remaining_escrow()?;
+    let buyer_amount = bps_of(remaining_escrow, buyer_share_bps)?;
+    let seller_portion = remaining_escrow
+        .checked_sub(buyer_amount)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    let (fee_amount, seller_amount) = split_fee(seller_portion, offer.fee)?;
+
+    // Confirmed parcels were already paid out, the seller keeps them
+    let seller_total = offer
+        .released_amount
+        .checked_add(seller_portion)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    offer.released_amount = offer.escrow_amount;
+
+    let buyer_won = buyer_wins_dispute(buyer_share_bps);
+    buyer_reputation.record_dispute(buyer_won)?;
+    seller_reputation.record_dispute(!buyer_won)?;
+
+    // Disputes are opened on shipped offers only
+    record_shipment(offer, seller_reputation)?;
+
+    // A partial refund still completes the deal for what the seller kept
+    let delivered = offer.state == OfferState::DELIVERED;
+    if delivered {
+        buyer_reputation.record_completed_deal(offer.mint, seller_total)?;
+        seller_reputation.record_completed_deal(offer.mint, seller_total)?;
+    }
+
+    settle(buying_intent, delivered)?;
+
+    Ok(DisputeSplit {
+        buyer_amount,
+        fee_amount,
+        seller_amount,
+        buyer_won,
+    })
+}
+
+// Only a settled slice of a finished buying intent can be cleaned up. While the
+// intent takes offers, its accepted offer PDAs must stay in place.
+pub fn require_settled(buying_intent: &BuyingIntent, offer: &Offer) -> Result<()> {
+    require!(
+        matches!(
+            buying_intent.state,
+            BuyingIntentState::FULFILLED | BuyingIntentState::CANCELLED
+        ),
+        BestOfferErrorCode::BuyingIntentNotFinished
+    );
+    require!(
+        matches!(offer.state, OfferState::DELIVERED | OfferState::CANCELLED),
+        BestOfferErrorCode::OfferNotSettled
+    );
+
+    Ok(())
+}
+
+// Count a cleaned up slice, true when the buying intent can be closed with it
+pub fn close_slice(buying_intent: &mut BuyingIntent) -> Result<bool> {
+    buying_intent.closed_offers = buying_intent
+        .closed_offers
+        .checked_add(1)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    // require_settled already checked the buying intent is finished
+    Ok(buying_intent.closed_offers as usize == buying_intent.accepted_offers.len())
+}
+
+// Release everything left in the vault and fulfill the slice
+fn release_remaining(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<u64> {
+    let amount = offer.remaining_escrow()?;
+    offer.released_amount = offer.escrow_amount;
+
+    fulfill(buying_intent, offer, buyer_reputation, seller_reputation)?;
+
+    Ok(amount)
+}
+
+// Both sides get a completed deal, the seller also gets its shipping delay
+fn fulfill(
+    buying_intent: &mut BuyingIntent,
+    offer: &mut Offer,
+    buyer_reputation: &mut Reputation,
+    seller_reputation: &mut Reputation,
+) -> Result<()> {
+    // The offer must have been SHIPPED
+    offer.state = transition_offer(offer.state, OfferState::DELIVERED)?;
+
+    buyer_reputation.record_completed_deal(offer.mint, offer.escrow_amount)?;
+    seller_reputation.record_completed_deal(offer.mint, offer.escrow_amount)?;
+
+    record_shipment(offer, seller_reputation)?;
+
+    settle(buying_intent, true)
+}
+
+fn record_shipment(offer: &Offer, seller_reputation: &mut Reputation) -> Result<()> {
+    if let (Some(accepted_at), Some(shipped_at)) = (offer.accepted_at, offer.shipped_at) {
+        seller_reputation.record_shipment(accepted_at, shipped_at)?;
+    }
+
+    Ok(())
+}
+
+// Count a settled slice and finish the buying intent with the last one
+fn settle(buying_intent: &mut BuyingIntent, delivered: bool) -> Result<()> {
+    buying_intent.settled_offers = buying_intent
+        .settled_offers
+        .checked_add(1)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    if delivered {
+        buying_intent.delivered_offers = buying_intent
+            .delivered_offers
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    }
+
+    finalize(buying_intent)
+}
+
+// FULFILLED when at least one slice was delivered, CANCELLED when all were refunded
+fn finalize(buying_intent: &mut BuyingIntent) -> Result<()> {
+    if buying_intent.is_settled() {
+        let state = if buying_intent.delivered_offers > 0 {
+            BuyingIntentState::FULFILLED
+        } else {
+            BuyingIntentState::CANCELLED
+        };
+        buying_intent.state = transition_buying_intent(buying_intent.state, state)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buying_intent(state: BuyingIntentState, accepted_offers: usize) -> BuyingIntent {
+        BuyingIntent {
+            id: 0,
+            buyer: Pubkey::new_unique(),
+            gtin: 3544056897834,
+            product_name: "Focal Bathys MG".to_string(),
+            shipping_country_code: "FR".to_string(),
+            shipping_state_code: None,
+            accepted_offers: (0..accepted_offers).map(|_| Pubkey::new_unique()).collect(),
+            state,
+            quantity: 10,
+            offer_count: accepted_offers as u16,
+            allocated_quantity: 0,
+            settled_offers: 0,
+            delivered_offers: 0,
+            closed_offers: 0,
+            sealed_bid: None,
+            best_offer: None,
+            expires_at: 0,
+            bump: 0,
+        }
+    }
+
+    fn shipped_offer(last_shipped_at: i64) -> Offer {
+        Offer {
+            id: 0,
+            buying_intent: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            url: "https://www.focal.com/bathys-mg".to_string(),
+            public_price: 1_499,
+            offer_price: 1_299,
+            shipping_price: 0,
+            price_commitment: None,
+            mint: Pubkey::new_unique(),
+            state: OfferState::SHIPPED,
+            quantity: 2,
+            escrow_amount: 2_598,
+            fee: 100,
+            released_amount: 0,
+            accepted_at: Some(0),
+            shipped_at: Some(last_shipped_at),
+            last_shipped_at: Some(last_shipped_at),
+            shipment_count: 1,
+            shipped_units: 2,
+            delivered_units: 0,
+            expires_at: 0,
+            bond_amount: 0,
+            bond_in_tokens: false,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn claim_waits_for_the_delivery_timeout() {
+        let offer = shipped_offer(1_000);
+
+        assert_eq!(
+            check_claimable(&offer, 500, 1_499),
+            Err(BestOfferErrorCode::DeliveryTimeoutNotReached.into())
+        );
+        check_claimable(&offer, 500, 1_500).unwrap();
+
+        assert_eq!(
+            check_claimable(&offer, i64::MAX, 1_500),
+            Err(BestOfferErrorCode::NumericalOverflow.into())
+        );
+    }
+
+    #[test]
+    fn claim_needs_an_undisputed_complete_shipment() {
+        let mut disputed = shipped_offer(1_000);
+        disputed.state = OfferState::DISPUTED;
+        assert_eq!(
+            check_claimable(&disputed, 500, 2_000),
+            Err(BestOfferErrorCode::OfferNotShipped.into())
+        );
+
+        let mut partial = shipped_offer(1_000);
+        partial.shipped_units = 1;
+        assert_eq!(
+            check_claimable(&partial, 500, 2_000),
+            Err(BestOfferErrorCode::ShipmentIncomplete.into())
+        );
+    }
+
+    #[test]
+    fn last_settled_offer_fulfills_the_buying_intent() {
+        let mut buying_intent = buying_intent(BuyingIntentState::CONFIRMED, 2);
+
+        settle(&mut buying_intent, true).unwrap();
+        assert_eq!(buying_intent.state, BuyingIntentState::CONFIRMED);
+
+        settle(&mut buying_intent, false).unwrap();
+        assert_eq!(buying_intent.state, BuyingIntentState::FULFILLED);
+    }
+
+    #[test]
+    fn refunded_offers_cancel_the_buying_intent() {
+        let mut buying_intent = buying_intent(BuyingIntentState::CONFIRMED, 2);
+
+        settle(&mut buying_intent, false).unwrap();
+        settle(&mut buying_intent, false).unwrap();
+
+        assert_eq!(buying_intent.state, BuyingIntentState::CANCELLED);
+    }
+
+    #[test]
+    fn partial_buying_intent_waits_for_confirmation() {
+        let mut buying_intent = buying_intent(BuyingIntentState::PUBLISHED, 1);
+
+        settle(&mut buying_intent, true).unwrap();
+        assert_eq!(buying_intent.state, BuyingIntentState::PUBLISHED);
+
+        confirm(&mut buying_intent).unwrap();
+        assert_eq!(buying_intent.state, BuyingIntentState::FULFILLED);
+    }
+
+    #[test]
+    fn cannot_confirm_without_accepted_offer() {
+        let mut buying_intent = buying_intent(BuyingIntentState::PUBLISHED, 0);
+
+        assert_eq!(
+            confirm(&mut buying_intent),
+            Err(BestOfferErrorCode::NoAcceptedOffer.into())
+        );
+    }
+
+    #[test]
+    fn buyer_wins_from_half_the_escrow() {
+        assert!(!buyer_wins_dispute(0));
+        assert!(!buyer_wins_dispute(4_999));
+        assert!(buyer_wins_dispute(5_000));
+        assert!(buyer_wins_dispute(10_000));
+    }
+
+    #[test]
+    fn cleanup_waits_for_the_buying_intent() {
+        let mut offer = shipped_offer(1_000);
+        offer.state = OfferState::DELIVERED;
+
+        assert_eq!(
+            require_settled(&buying_intent(BuyingIntentState::PUBLISHED, 1), &offer),
+            Err(BestOfferErrorCode::BuyingIntentNotFinished.into())
+        );
+        require_settled(&buying_intent(BuyingIntentState::FULFILLED, 1), &offer).unwrap();
+
+        offer.state = OfferState::SHIPPED;
+        assert_eq!(
+            require_settled(&buying_intent(BuyingIntentState::FULFILLED, 1), &offer),
+            Err(BestOfferErrorCode::OfferNotSettled.into())
+        );
+    }
+
+    #[test]
+    fn buying_intent_closes_with_the_last_offer() {
+        let mut buying_intent = buying_intent(BuyingIntentState::FULFILLED, 2);
+
+        assert!(!close_slice(&mut buying_intent).unwrap());
+        assert!(close_slice(&mut buying_intent).unwrap());
+    }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+// Payment mint allowed by the admin, with its own fee tier
+#[account]
+#[derive(InitSpace)]
+pub struct AcceptedMint {
+    // Token mint
+    pub mint: Pubkey,
+
+    // Fee in basis points charged on deals settled in this mint
+    pub fee: u16,
+
+    // Minimum order total, in base units of the mint
+    pub min_amount: u64,
+
+    // Disabled mints settle running deals but take no new offer
+    pub enabled: bool,
+
+    // Offer price above which the configured seller reputation is required,
+    // in base units of the mint
+    pub reputation_price_threshold: u64,
+
+    // Bump
+    pub bump: u8,
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 pub use crate::buying_intent_state::*;
+use crate::error::BestOfferErrorCode;
 
 #[account]
 #[derive(InitSpace)]
@@ -26,7 +27,9 @@ pub struct BuyingIntent {
     #[max_len(3)]
     pub shipping_state_code: Option<String>, // Needs for country like US / CA
 
-    pub accepted_offer: Option<Pubkey>,
+    // Offers accepted so far, each one covers a slice of the quantity
+    #[max_len(5)]
+    pub accepted_offers: Vec<Pubkey>,
 
     // Buying Intent State
     pub state: BuyingIntentState,
@@ -34,6 +37,239 @@ pub struct BuyingIntent {
     // Quantity
     pub quantity: u16,
 
+    // Live offers, at most one per seller
+    pub offer_count: u16,
+
+    // Units covered by the accepted offers
+    pub allocated_quantity: u16,
+
+    // Accepted offers settled so far, and how many of them were delivered
+    pub settled_offers: u8,
+    pub delivered_offers: u8,
+
+    // Accepted offers whose accounts were cleaned up
+    pub closed_offers: u8,
+
+    // Sealed-bid mode, offer prices stay hidden until the reveal window
+    pub sealed_bid: Option<SealedBid>,
+
+    // Lowest revealed offer, set by highlight_best_offer
+    pub best_offer: Option<Pubkey>,
+
+    // Unix timestamp after which no offer can be created or accepted
+    pub expires_at: i64,
+
     // Bump
     pub bump: u8,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SealedBid {
+    // Sellers commit to a hidden price until this Unix timestamp
+    pub commit_deadline: i64,
+
+    // Then reveal it until this one, unrevealed offers are void
+    pub reveal_deadline: i64,
+
+    // Revealed offers the buyer can still accept
+    pub revealed_offers: u16,
+}
+
+// Lengths of the sealed-bid phases in seconds, chosen by the buyer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SealedBidPeriods {
+    pub commit_period: i64,
+    pub reveal_period: i64,
+}
+
+impl BuyingIntent {
+    // At most this many sellers share one buying intent
+    pub const MAX_ACCEPTED_OFFERS: usize = 5;
+
+    // 30 days, when the buyer does not pick an expiry
+    pub const DEFAULT_LIFETIME: i64 = 30 * 24 * 60 * 60;
+
+    // 90 days
+    pub const MAX_LIFETIME: i64 = 90 * 24 * 60 * 60;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    // Offers become acceptable at creation, or once the sealed prices are revealed
+    pub fn accepting_from(&self, now: i64) -> i64 {
+        match &self.sealed_bid {
+            Some(sealed_bid) => sealed_bid.reveal_deadline.max(now),
+            None => now,
+        }
+    }
+
+    // Units still waiting for an accepted offer
+    pub fn remaining_quantity(&self) -> u16 {
+        self.quantity.saturating_sub(self.allocated_quantity)
+    }
+
+    // Every accepted offer is settled and no more offer can be accepted
+    pub fn is_settled(&self) -> bool {
+        self.state == BuyingIntentState::CONFIRMED
+            && self.settled_offers as usize == self.accepted_offers.len()
+    }
+
+    // Sealed offers are committed until the commit deadline
+    pub fn check_commit_open(&self, now: i64) -> Result<()> {
+        let sealed_bid = self
+            .sealed_bid
+            .as_ref()
+            .ok_or(BestOfferErrorCode::NotSealedBid)?;
+        require!(
+            now < sealed_bid.commit_deadline,
+            BestOfferErrorCode::CommitPhaseClosed
+        );
+
+        Ok(())
+    }
+
+    // Prices are revealed between the commit and the reveal deadlines
+    pub fn check_reveal_open(&self, now: i64) -> Result<()> {
+        let sealed_bid = self
+            .sealed_bid
+            .as_ref()
+            .ok_or(BestOfferErrorCode::NotSealedBid)?;
+        require!(
+            now >= sealed_bid.commit_deadline && now < sealed_bid.reveal_deadline,
+            BestOfferErrorCode::RevealPhaseNotOpen
+        );
+
+        Ok(())
+    }
+
+    // Sealed offers can only be compared once the reveal window is over
+    pub fn check_reveal_over(&self, now: i64) -> Result<()> {
+        if let Some(sealed_bid) = &self.sealed_bid {
+            require!(
+                now >= sealed_bid.reveal_deadline,
+                BestOfferErrorCode::RevealPhaseNotOver
+            );
+        }
+
+        Ok(())
+    }
+
+    // A revealed offer leaves the auction, when accepted or cancelled
+    pub fn remove_revealed_offer(&mut self, offer: Pubkey) -> Result<()> {
+        if let Some(sealed_bid) = self.sealed_bid.as_mut() {
+            sealed_bid.revealed_offers = sealed_bid
+                .revealed_offers
+                .checked_sub(1)
+                .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        }
+
+        if self.best_offer == Some(offer) {
+            self.best_offer = None;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buying_intent(sealed_bid: Option<SealedBid>) -> BuyingIntent {
+        BuyingIntent {
+            id: 0,
+            buyer: Pubkey::new_unique(),
+            gtin: 3544056897834,
+            product_name: "Focal Bathys MG".to_string(),
+            shipping_country_code: "FR".to_string(),
+            shipping_state_code: None,
+            accepted_offers: Vec::new(),
+            state: BuyingIntentState::PUBLISHED,
+            quantity: 1,
+            offer_count: 0,
+            allocated_quantity: 0,
+            settled_offers: 0,
+            delivered_offers: 0,
+            closed_offers: 0,
+            sealed_bid,
+            best_offer: None,
+            expires_at: 1_000,
+            bump: 0,
+        }
+    }
+
+    fn sealed() -> BuyingIntent {
+        buying_intent(Some(SealedBid {
+            commit_deadline: 100,
+            reveal_deadline: 200,
+            revealed_offers: 0,
+        }))
+    }
+
+    #[test]
+    fn sealed_bid_phases_follow_the_deadlines() {
+        let buying_intent = sealed();
+
+        assert!(buying_intent.check_commit_open(99).is_ok());
+        assert_eq!(
+            buying_intent.check_commit_open(100),
+            Err(BestOfferErrorCode::CommitPhaseClosed.into())
+        );
+
+        assert_eq!(
+            buying_intent.check_reveal_open(99),
+            Err(BestOfferErrorCode::RevealPhaseNotOpen.into())
+        );
+        assert!(buying_intent.check_reveal_open(100).is_ok());
+        assert_eq!(
+            buying_intent.check_reveal_open(200),
+            Err(BestOfferErrorCode::RevealPhaseNotOpen.into())
+        );
+
+        assert_eq!(
+            buying_intent.check_reveal_over(199),
+            Err(BestOfferErrorCode::RevealPhaseNotOver.into())
+        );
+        assert!(buying_intent.check_reveal_over(200).is_ok());
+    }
+
+    #[test]
+    fn open_buying_intent_has_no_sealed_phase() {
+        let buying_intent = buying_intent(None);
+
+        assert_eq!(
+            buying_intent.check_commit_open(0),
+            Err(BestOfferErrorCode::NotSealedBid.into())
+        );
+        assert_eq!(
+            buying_intent.check_reveal_open(0),
+            Err(BestOfferErrorCode::NotSealedBid.into())
+        );
+        assert!(buying_intent.check_reveal_over(0).is_ok());
+    }
+
+    #[test]
+    fn sealed_offers_are_acceptable_after_the_reveal() {
+        assert_eq!(buying_intent(None).accepting_from(50), 50);
+        assert_eq!(sealed().accepting_from(50), 200);
+        assert_eq!(sealed().accepting_from(250), 250);
+    }
+
+    #[test]
+    fn removing_the_best_offer_clears_it() {
+        let mut buying_intent = sealed();
+        let offer = Pubkey::new_unique();
+        buying_intent.sealed_bid.as_mut().unwrap().revealed_offers = 2;
+        buying_intent.best_offer = Some(offer);
+
+        buying_intent
+            .remove_revealed_offer(Pubkey::new_unique())
+            .unwrap();
+        assert_eq!(buying_intent.best_offer, Some(offer));
+
+        buying_intent.remove_revealed_offer(offer).unwrap();
+        assert_eq!(buying_intent.best_offer, None);
+        assert_eq!(buying_intent.sealed_bid.unwrap().revealed_offers, 0);
+    }
+}
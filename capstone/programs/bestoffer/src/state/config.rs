@@ -1,11 +1,20 @@
 use anchor_lang::prelude::*;
 
+use crate::error::BestOfferErrorCode;
+use crate::{AcceptedMint, Reputation, NATIVE_SOL_MINT};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
     // Admin pubkey
     pub admin: Pubkey,
 
+    // Admin proposed by the current admin, waiting for acceptance
+    pub pending_admin: Option<Pubkey>,
+
+    // When paused, no new buying intent, offer or acceptance
+    pub paused: bool,
+
     // Fee in basis points
     pub fee: u16,
 
@@ -15,6 +24,208 @@ pub struct Config {
     // Offer increment
     pub offer_increment: u64,
 
+    // Seconds the seller has to ship after the buyer accepted the offer
+    pub shipping_timeout: i64,
+
+    // Seconds the buyer has to accept delivery or open a dispute after shipping
+    pub delivery_timeout: i64,
+
+    // Maximum live offers on a single buying intent
+    pub max_offers_per_intent: u16,
+
+    // Minimum seller reputation to post expensive offers, disabled when None
+    pub reputation_requirement: Option<ReputationRequirement>,
+
+    // Bond sellers lock with every offer, disabled when None
+    pub offer_bond: Option<OfferBond>,
+
+    // Arbiters allowed to resolve disputes (Max 5)
+    #[max_len(5)]
+    pub arbiters: Vec<Pubkey>,
+
     // Bump
     pub bump: u8,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ReputationRequirement {
+    // Completed deals the seller needs
+    pub min_completed_deals: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OfferBond {
+    // Bond per offer, in lamports or in base units of the offer mint
+    pub amount: u64,
+
+    // Locked in the offer mint instead of native SOL
+    pub in_payment_mint: bool,
+
+    // Part of a slashed bond kept by the treasury, the buyer gets the rest
+    pub treasury_share_bps: u16,
+}
+
+impl Config {
+    pub const MAX_ARBITERS: usize = 5;
+
+    // 10%
+    pub const MAX_FEE: u16 = 1_000;
+
+    // 7 days
+    pub const DEFAULT_SHIPPING_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+
+    // 14 days
+    pub const DEFAULT_DELIVERY_TIMEOUT: i64 = 14 * 24 * 60 * 60;
+
+    // Keeps the buyer's choice list short enough to compare
+    pub const DEFAULT_MAX_OFFERS_PER_INTENT: u16 = 20;
+
+    // Offers above the threshold of their mint need a seller with enough completed deals
+    pub fn check_reputation(
+        &self,
+        reputation: &Reputation,
+        accepted_mint: &AcceptedMint,
+        offer_price: u64,
+    ) -> Result<()> {
+        if let Some(requirement) = &self.reputation_requirement {
+            require!(
+                offer_price <= accepted_mint.reputation_price_threshold
+                    || reputation.completed_deals >= requirement.min_completed_deals,
+                BestOfferErrorCode::InsufficientReputation
+            );
+        }
+
+        Ok(())
+    }
+
+    // Bond locked for an offer paid in `mint`, and whether it is held in tokens.
+    // Native SOL offers always lock lamports.
+    pub fn offer_bond_for(&self, mint: Pubkey) -> (u64, bool) {
+        match &self.offer_bond {
+            Some(bond) => (bond.amount, bond.in_payment_mint && mint != NATIVE_SOL_MINT),
+            None => (0, false),
+        }
+    }
+
+    // Bonds slashed after the requirement was lifted all go to the buyer
+    pub fn bond_treasury_share_bps(&self) -> u16 {
+        self.offer_bond
+            .as_ref()
+            .map_or(0, |offer_bond| offer_bond.treasury_share_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(offer_bond: Option<OfferBond>) -> Config {
+        Config {
+            admin: Pubkey::new_unique(),
+            pending_admin: None,
+            paused: false,
+            fee: 100,
+            buying_intent_increment: 0,
+            offer_increment: 0,
+            shipping_timeout: Config::DEFAULT_SHIPPING_TIMEOUT,
+            delivery_timeout: Config::DEFAULT_DELIVERY_TIMEOUT,
+            max_offers_per_intent: Config::DEFAULT_MAX_OFFERS_PER_INTENT,
+            reputation_requirement: None,
+            offer_bond,
+            arbiters: vec![],
+            bump: 0,
+        }
+    }
+
+    fn accepted_mint(reputation_price_threshold: u64) -> AcceptedMint {
+        AcceptedMint {
+            mint: Pubkey::new_unique(),
+            fee: 100,
+            min_amount: 0,
+            enabled: true,
+            reputation_price_threshold,
+            bump: 0,
+        }
+    }
+
+    fn reputation(completed_deals: u64) -> Reputation {
+        Reputation {
+            wallet: Pubkey::new_unique(),
+            completed_deals,
+            disputes_won: 0,
+            disputes_lost: 0,
+            missed_shipping_deadlines: 0,
+            shipped_deals: 0,
+            total_time_to_ship: 0,
+            volumes: vec![],
+            bump: 0,
+        }
+    }
+
+    fn offer_bond(in_payment_mint: bool) -> OfferBond {
+        OfferBond {
+            amount: 1_000,
+            in_payment_mint,
+            treasury_share_bps: 5_000,
+        }
+    }
+
+    #[test]
+    fn reputation_threshold_follows_the_mint() {
+        let mut config = config(None);
+        config.reputation_requirement = Some(ReputationRequirement {
+            min_completed_deals: 3,
+        });
+
+        // 100 USDC (6 decimals) and 1 SOL (9 decimals)
+        let usdc = accepted_mint(100_000_000);
+        let sol = accepted_mint(1_000_000_000);
+
+        config
+            .check_reputation(&reputation(0), &usdc, 100_000_000)
+            .unwrap();
+        assert_eq!(
+            config.check_reputation(&reputation(0), &usdc, 100_000_001),
+            Err(BestOfferErrorCode::InsufficientReputation.into())
+        );
+        config
+            .check_reputation(&reputation(3), &usdc, 100_000_001)
+            .unwrap();
+
+        // The same raw price is cheap in lamports
+        config
+            .check_reputation(&reputation(0), &sol, 100_000_001)
+            .unwrap();
+
+        config.reputation_requirement = None;
+        config
+            .check_reputation(&reputation(0), &usdc, u64::MAX)
+            .unwrap();
+    }
+
+    #[test]
+    fn offer_bond_is_optional() {
+        assert_eq!(
+            config(None).offer_bond_for(Pubkey::new_unique()),
+            (0, false)
+        );
+    }
+
+    #[test]
+    fn offer_bond_follows_the_payment_mint() {
+        let mint = Pubkey::new_unique();
+
+        assert_eq!(
+            config(Some(offer_bond(false))).offer_bond_for(mint),
+            (1_000, false)
+        );
+        assert_eq!(
+            config(Some(offer_bond(true))).offer_bond_for(mint),
+            (1_000, true)
+        );
+        assert_eq!(
+            config(Some(offer_bond(true))).offer_bond_for(NATIVE_SOL_MINT),
+            (1_000, false)
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Dispute {
+    // Disputed buying intent
+    pub buying_intent: Pubkey,
+
+    // Disputed accepted offer
+    pub offer: Pubkey,
+
+    // Why the buyer opened the dispute
+    #[max_len(255)]
+    pub reason: String,
+
+    // Unix timestamp when the dispute was opened
+    pub opened_at: i64,
+
+    // Arbiter who resolved the dispute
+    pub resolved_by: Option<Pubkey>,
+
+    // Share of the escrow refunded to the buyer in basis points
+    pub buyer_share_bps: Option<u16>,
+
+    // Unix timestamp when the dispute was resolved
+    pub resolved_at: Option<i64>,
+
+    // Bump
+    pub bump: u8,
+}
+
+impl Dispute {
+    // Matches the reason max_len
+    pub const MAX_REASON_LEN: usize = 255;
+}
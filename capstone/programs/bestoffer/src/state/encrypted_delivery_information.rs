@@ -1,41 +1,83 @@
 use anchor_lang::prelude::*;
 
+use crate::error::BestOfferErrorCode;
+use crate::DELIVERY_INFORMATION_VERSION;
+
+// Delivery address envelope.
+//
+// The buyer serializes the whole address in one blob and encrypts it with a
+// random symmetric key (crypto_secretbox). The key is then sealed
+// (crypto_box_seal) to the seller and optionally to an arbiter, so both can
+// read the address without sharing keys.
 #[account]
 #[derive(InitSpace)]
 pub struct EncryptedDeliveryInformation {
+    // Envelope format version
+    pub version: u8,
+
+    // Nonce of the address ciphertext
     pub nonce: [u8; 24],
 
-    pub buyer_ephemeral_pubkey: [u8; 32],
+    // max 384 bytes of serialized address + 16 bytes for crypto_secretbox MAC = 400
+    #[max_len(400)]
+    pub encrypted_address: Vec<u8>,
 
-    // max 100 characters × 4 bytes (UTF-8 worst case) + 16 bytes for crypto_box MAC = 416
-    #[max_len(416)]
-    pub encrypted_delivery_lastname: Vec<u8>,
+    // Symmetric key sealed to the seller X25519 key
+    pub seller_sealed_key: [u8; 80],
 
-    // max 100 characters × 4 bytes (UTF-8 worst case) + 16 bytes for crypto_box MAC = 416
-    #[max_len(416)]
-    pub encrypted_delivery_firstname: Vec<u8>,
+    // Symmetric key sealed to an arbiter, readable during disputes
+    pub arbiter_sealed_key: Option<ArbiterSealedKey>,
+}
 
-    // max 150 characters × 4 bytes + 16 = 616
-    #[max_len(616)]
-    pub encrypted_delivery_address_line_1: Vec<u8>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ArbiterSealedKey {
+    // One of the configured arbiters
+    pub arbiter: Pubkey,
+
+    // Symmetric key sealed to the arbiter X25519 key
+    pub sealed_key: [u8; 80],
+}
 
-    // max 150 characters × 4 bytes + 16 = 616
-    #[max_len(616)]
-    pub encrypted_delivery_address_line_2: Option<Vec<u8>>,
+impl EncryptedDeliveryInformation {
+    // Keeps accept_offer below the 1232 bytes transaction limit with both sealed keys
+    pub const MAX_ENCRYPTED_ADDRESS_LEN: usize = 400;
 
-    // max 100 characters × 4 bytes + 16 = 416
-    #[max_len(416)]
-    pub encrypted_delivery_city: Vec<u8>,
+    // Ephemeral public key (32) + MAC (16) + symmetric key (32)
+    pub const SEALED_KEY_LEN: usize = 80;
 
-    // max 50 characters × 4 bytes + 16 = 216
-    #[max_len(216)]
-    pub encrypted_delivery_postal_code: Vec<u8>,
+    // Validate an envelope sent to accept_offer
+    pub fn new(
+        version: u8,
+        nonce: [u8; 24],
+        encrypted_address: Vec<u8>,
+        seller_sealed_key: [u8; 80],
+        arbiter_sealed_key: Option<ArbiterSealedKey>,
+        arbiters: &[Pubkey],
+    ) -> Result<Self> {
+        require!(
+            version == DELIVERY_INFORMATION_VERSION,
+            BestOfferErrorCode::UnsupportedDeliveryInformationVersion
+        );
+        require!(
+            !encrypted_address.is_empty()
+                && encrypted_address.len() <= Self::MAX_ENCRYPTED_ADDRESS_LEN,
+            BestOfferErrorCode::InvalidEncryptedAddressLength
+        );
 
-    // max 2 characters × 4 bytes + 16 = 24
-    #[max_len(24)]
-    pub encrypted_delivery_country_code: Vec<u8>,
+        // The key can only be sealed to a configured arbiter
+        if let Some(arbiter_sealed_key) = &arbiter_sealed_key {
+            require!(
+                arbiters.contains(&arbiter_sealed_key.arbiter),
+                BestOfferErrorCode::UnauthorizedArbiter
+            );
+        }
 
-    // max 50 characters × 4 bytes + 16 = 216
-    #[max_len(216)]
-    pub encrypted_delivery_state_code: Option<Vec<u8>>,
+        Ok(Self {
+            version,
+            nonce,
+            encrypted_address,
+            seller_sealed_key,
+            arbiter_sealed_key,
+        })
+    }
 }
@@ -1,13 +1,19 @@
+pub mod accepted_mint;
 pub mod buying_intent;
 pub mod config;
+pub mod dispute;
 pub mod encrypted_delivery_information;
 pub mod offer;
+pub mod reputation;
 pub mod tracking_details;
 pub mod treasury;
 
+pub use accepted_mint::*;
 pub use buying_intent::*;
 pub use config::*;
+pub use dispute::*;
 pub use encrypted_delivery_information::*;
 pub use offer::*;
+pub use reputation::*;
 pub use tracking_details::*;
 pub use treasury::*;
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 
+use crate::error::BestOfferErrorCode;
 pub use crate::offer_state::*;
 
 #[account]
@@ -8,6 +10,9 @@ pub struct Offer {
     // ID
     pub id: u64,
 
+    // Buying intent the offer answers
+    pub buying_intent: Pubkey,
+
     // Pubkey of the seller
     pub seller: Pubkey,
 
@@ -23,11 +28,282 @@ pub struct Offer {
     // Shipping price
     pub shipping_price: u64,
 
+    // Hash of the sealed prices, cleared once revealed
+    pub price_commitment: Option<[u8; 32]>,
+
     // SPL token the seller wants to receive
     pub mint: Pubkey,
 
     pub state: OfferState,
 
+    // Units of the buying intent covered once accepted
+    pub quantity: u16,
+
+    // Amount locked in the offer vault when the buyer accepted it
+    pub escrow_amount: u64,
+
+    // Fee in basis points of the accepted mint when the buyer accepted the offer
+    pub fee: u16,
+
+    // Part of the escrow already paid out for confirmed shipments
+    pub released_amount: u64,
+
+    // Unix timestamp when the buyer accepted the offer
+    pub accepted_at: Option<i64>,
+
+    // Unix timestamp when the seller sent the first shipment
+    pub shipped_at: Option<i64>,
+
+    // Unix timestamp of the latest shipment, the delivery timeout starts from it
+    pub last_shipped_at: Option<i64>,
+
+    // Tracking details created so far, numbered from 0
+    pub shipment_count: u16,
+
+    // Units sent by the seller, and units the buyer confirmed
+    pub shipped_units: u16,
+    pub delivered_units: u16,
+
+    // Unix timestamp after which the buyer can no longer accept the offer
+    pub expires_at: i64,
+
+    // Seller bond still locked, cleared once returned or slashed
+    pub bond_amount: u64,
+
+    // Bond held in the bond vault in the offer mint, in lamports on the offer account otherwise
+    pub bond_in_tokens: bool,
+
     // Store the bump
     pub bump: u8,
 }
+
+impl Offer {
+    // Sealed offers commit to sha256(offer || offer_price || shipping_price || salt), little
+    // endian prices. The offer address ties the commitment to its seller, a copied commitment
+    // cannot be revealed from another offer.
+    pub fn price_commitment(
+        offer: &Pubkey,
+        offer_price: u64,
+        shipping_price: u64,
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            offer.as_ref(),
+            &offer_price.to_le_bytes(),
+            &shipping_price.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+
+    // Clear the bond before returning or slashing it, so it only moves once
+    pub fn take_bond(&mut self) -> u64 {
+        std::mem::take(&mut self.bond_amount)
+    }
+
+    // The delivery timeout runs from the last parcel
+    pub fn delivery_deadline(&self, delivery_timeout: i64) -> Result<i64> {
+        self.last_shipped_at
+            .ok_or(BestOfferErrorCode::OfferNotShipped)?
+            .checked_add(delivery_timeout)
+            .ok_or(BestOfferErrorCode::NumericalOverflow.into())
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    // Open offers are revealed from the start
+    pub fn is_revealed(&self) -> bool {
+        self.price_commitment.is_none()
+    }
+
+    // The seller opens the commitment, prices are only set when they match
+    pub fn reveal(
+        &mut self,
+        offer: &Pubkey,
+        offer_price: u64,
+        shipping_price: u64,
+        salt: &[u8; 32],
+    ) -> Result<()> {
+        let commitment = self
+            .price_commitment
+            .ok_or(BestOfferErrorCode::OfferAlreadyRevealed)?;
+        require!(
+            Self::price_commitment(offer, offer_price, shipping_price, salt) == commitment,
+            BestOfferErrorCode::InvalidPriceReveal
+        );
+
+        self.offer_price = offer_price;
+        self.shipping_price = shipping_price;
+        self.price_commitment = None;
+
+        Ok(())
+    }
+
+    // Escrow still held in the vault
+    pub fn remaining_escrow(&self) -> Result<u64> {
+        self.escrow_amount
+            .checked_sub(self.released_amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow.into())
+    }
+
+    // Pro-rata part of the escrow released when `units` more units are confirmed.
+    // Computed on the cumulative units so the last parcel gets the rounding dust.
+    pub fn release_for_units(&self, units: u16) -> Result<u64> {
+        let delivered_units = self
+            .delivered_units
+            .checked_add(units)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        require!(
+            delivered_units <= self.quantity,
+            BestOfferErrorCode::InvalidShipmentUnits
+        );
+
+        let released = (self.escrow_amount as u128)
+            .checked_mul(delivered_units as u128)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?
+            .checked_div(self.quantity as u128)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        (released as u64)
+            .checked_sub(self.released_amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(quantity: u16, escrow_amount: u64) -> Offer {
+        Offer {
+            id: 0,
+            buying_intent: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            url: "https://www.focal.com/bathys-mg".to_string(),
+            public_price: 1_499,
+            offer_price: 1_299,
+            shipping_price: 0,
+            price_commitment: None,
+            mint: Pubkey::new_unique(),
+            state: OfferState::SHIPPED,
+            quantity,
+            escrow_amount,
+            fee: 0,
+            released_amount: 0,
+            accepted_at: None,
+            shipped_at: None,
+            last_shipped_at: None,
+            shipment_count: 0,
+            shipped_units: 0,
+            delivered_units: 0,
+            expires_at: 0,
+            bond_amount: 0,
+            bond_in_tokens: false,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn releases_escrow_pro_rata() {
+        let mut offer = offer(3, 1_000);
+
+        for expected in [333, 333, 334] {
+            let amount = offer.release_for_units(1).unwrap();
+            assert_eq!(amount, expected);
+
+            offer.delivered_units += 1;
+            offer.released_amount += amount;
+        }
+
+        assert_eq!(offer.remaining_escrow().unwrap(), 0);
+    }
+
+    #[test]
+    fn release_covers_several_units() {
+        let offer = offer(10, 1_240);
+
+        assert_eq!(offer.release_for_units(4).unwrap(), 496);
+        assert_eq!(offer.release_for_units(10).unwrap(), 1_240);
+    }
+
+    #[test]
+    fn release_rejects_more_units_than_ordered() {
+        let mut offer = offer(2, 1_000);
+        offer.delivered_units = 2;
+
+        assert_eq!(
+            offer.release_for_units(1),
+            Err(BestOfferErrorCode::InvalidShipmentUnits.into())
+        );
+    }
+
+    #[test]
+    fn bond_is_taken_once() {
+        let mut offer = offer(1, 0);
+        offer.bond_amount = 50_000;
+
+        assert_eq!(offer.take_bond(), 50_000);
+        assert_eq!(offer.take_bond(), 0);
+    }
+
+    #[test]
+    fn reveal_checks_the_commitment() {
+        let salt = [7; 32];
+        let key = Pubkey::new_unique();
+        let mut offer = offer(1, 0);
+        offer.offer_price = 0;
+        offer.price_commitment = Some(Offer::price_commitment(&key, 1_199, 40, &salt));
+
+        assert_eq!(
+            offer.reveal(&key, 1_099, 40, &salt),
+            Err(BestOfferErrorCode::InvalidPriceReveal.into())
+        );
+        assert_eq!(
+            offer.reveal(&key, 1_199, 40, &[8; 32]),
+            Err(BestOfferErrorCode::InvalidPriceReveal.into())
+        );
+
+        offer.reveal(&key, 1_199, 40, &salt).unwrap();
+        assert!(offer.is_revealed());
+        assert_eq!(offer.offer_price, 1_199);
+        assert_eq!(offer.shipping_price, 40);
+
+        assert_eq!(
+            offer.reveal(&key, 1_199, 40, &salt),
+            Err(BestOfferErrorCode::OfferAlreadyRevealed.into())
+        );
+    }
+
+    #[test]
+    fn delivery_deadline_starts_from_the_last_parcel() {
+        let mut offer = offer(2, 1_000);
+        assert_eq!(
+            offer.delivery_deadline(500),
+            Err(BestOfferErrorCode::OfferNotShipped.into())
+        );
+
+        offer.shipped_at = Some(1_000);
+        offer.last_shipped_at = Some(1_200);
+        assert_eq!(offer.delivery_deadline(500).unwrap(), 1_700);
+    }
+
+    #[test]
+    fn copied_commitment_cannot_be_revealed_by_another_seller() {
+        let salt = [7; 32];
+        let honest_key = Pubkey::new_unique();
+        let commitment = Offer::price_commitment(&honest_key, 1_199, 40, &salt);
+
+        // A competitor commits the same hash and replays the honest preimage
+        let mut copied = offer(1, 0);
+        copied.offer_price = 0;
+        copied.price_commitment = Some(commitment);
+
+        assert_eq!(
+            copied.reveal(&Pubkey::new_unique(), 1_199, 40, &salt),
+            Err(BestOfferErrorCode::InvalidPriceReveal.into())
+        );
+        assert!(!copied.is_revealed());
+    }
+}
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+
+// Track record of a wallet, as buyer and as seller. Created with the first
+// buying intent or offer, only updated when a deal settles.
+#[account]
+#[derive(InitSpace)]
+pub struct Reputation {
+    // Wallet this reputation belongs to
+    pub wallet: Pubkey,
+
+    // Deals fulfilled, by delivery, timeout or dispute
+    pub completed_deals: u64,
+
+    // Disputes settled in favor of this wallet
+    pub disputes_won: u32,
+
+    // Disputes settled against this wallet
+    pub disputes_lost: u32,
+
+    // Accepted offers refunded because nothing was shipped in time
+    pub missed_shipping_deadlines: u32,
+
+    // Shipped deals as seller, and the sum of their confirmation to shipping delays
+    pub shipped_deals: u64,
+    pub total_time_to_ship: i64,
+
+    // Settled volume per mint (Max 8 mints)
+    #[max_len(8)]
+    pub volumes: Vec<MintVolume>,
+
+    // Bump
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MintVolume {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl Reputation {
+    pub const MAX_MINTS: usize = 8;
+
+    // init_if_needed leaves a zeroed account the first time
+    pub fn initialize_if_needed(&mut self, wallet: Pubkey, bump: u8) {
+        if self.wallet == Pubkey::default() {
+            self.wallet = wallet;
+            self.bump = bump;
+        }
+    }
+
+    pub fn record_completed_deal(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        self.completed_deals = self
+            .completed_deals
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        // Volume in mints past the limit is not tracked, the deal still counts
+        let full = self.volumes.len() >= Self::MAX_MINTS;
+        match self.volumes.iter_mut().find(|volume| volume.mint == mint) {
+            Some(volume) => {
+                volume.amount = volume
+                    .amount
+                    .checked_add(amount)
+                    .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+            }
+            None if !full => self.volumes.push(MintVolume { mint, amount }),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn record_shipment(&mut self, confirmed_at: i64, shipped_at: i64) -> Result<()> {
+        let time_to_ship = shipped_at
+            .checked_sub(confirmed_at)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        self.shipped_deals = self
+            .shipped_deals
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        self.total_time_to_ship = self
+            .total_time_to_ship
+            .checked_add(time_to_ship)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn record_dispute(&mut self, won: bool) -> Result<()> {
+        let counter = if won {
+            &mut self.disputes_won
+        } else {
+            &mut self.disputes_lost
+        };
+        *counter = counter
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn record_missed_shipping_deadline(&mut self) -> Result<()> {
+        self.missed_shipping_deadlines = self
+            .missed_shipping_deadlines
+            .checked_add(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+        Ok(())
+    }
+
+    // Seconds, None until the first shipment
+    pub fn average_time_to_ship(&self) -> Option<i64> {
+        (self.shipped_deals > 0).then(|| self.total_time_to_ship / self.shipped_deals as i64)
+    }
+
+    pub fn volume(&self, mint: &Pubkey) -> u64 {
+        self.volumes
+            .iter()
+            .find(|volume| &volume.mint == mint)
+            .map_or(0, |volume| volume.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_deals_and_volume_per_mint() {
+        let mut reputation = Reputation {
+            wallet: Pubkey::default(),
+            completed_deals: 0,
+            disputes_won: 0,
+            disputes_lost: 0,
+            missed_shipping_deadlines: 0,
+            shipped_deals: 0,
+            total_time_to_ship: 0,
+            volumes: vec![],
+            bump: 0,
+        };
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        reputation.initialize_if_needed(wallet, 254);
+        reputation.initialize_if_needed(Pubkey::new_unique(), 1);
+        assert_eq!(reputation.wallet, wallet);
+        assert_eq!(reputation.bump, 254);
+
+        assert_eq!(reputation.average_time_to_ship(), None);
+        reputation.record_shipment(100, 200).unwrap();
+        reputation.record_shipment(100, 400).unwrap();
+        assert_eq!(reputation.average_time_to_ship(), Some(200));
+
+        reputation.record_completed_deal(mint, 10).unwrap();
+        reputation.record_completed_deal(mint, 5).unwrap();
+        assert_eq!(reputation.completed_deals, 2);
+        assert_eq!(reputation.volume(&mint), 15);
+
+        // Extra mints still count as deals
+        for _ in 0..Reputation::MAX_MINTS {
+            reputation
+                .record_completed_deal(Pubkey::new_unique(), 1)
+                .unwrap();
+        }
+        assert_eq!(reputation.volumes.len(), Reputation::MAX_MINTS);
+        assert_eq!(reputation.completed_deals, 2 + Reputation::MAX_MINTS as u64);
+
+        reputation.record_dispute(true).unwrap();
+        reputation.record_dispute(false).unwrap();
+        reputation.record_dispute(false).unwrap();
+        assert_eq!((reputation.disputes_won, reputation.disputes_lost), (1, 2));
+    }
+}
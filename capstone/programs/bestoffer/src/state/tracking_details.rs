@@ -3,6 +3,18 @@ use anchor_lang::prelude::*;
 #[account]
 #[derive(InitSpace)]
 pub struct TrackingDetails {
+    // Buying intent this shipment belongs to
+    pub buying_intent: Pubkey,
+
+    // Accepted offer shipping the parcel
+    pub offer: Pubkey,
+
+    // Shipment number, starting at 0
+    pub shipment: u16,
+
+    // Units of the offer quantity in this parcel
+    pub units: u16,
+
     #[max_len(100)]
     pub carrier_name: String,
 
@@ -12,6 +24,12 @@ pub struct TrackingDetails {
     #[max_len(255)]
     pub tracking_code: String,
 
+    // Unix timestamp when the seller sent the parcel
+    pub shipped_at: i64,
+
+    // Unix timestamp when the buyer confirmed this parcel
+    pub delivered_at: Option<i64>,
+
     // Store the bump
     pub bump: u8,
 }
@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::{BuyingIntentState, OfferState};
+
+// Allowed Buying Intent moves
+//
+// PUBLISHED -> CONFIRMED -> FULFILLED
+//     |            |
+//     v            v
+// CANCELLED    CANCELLED
+// EXPIRED
+//
+// CONFIRMED once the accepted offers cover the quantity, then settled when
+// every accepted offer is DELIVERED or CANCELLED. Shipping and disputes are
+// tracked per offer, the intent never enters the deprecated SHIPPED or DISPUTED.
+pub fn can_transition_buying_intent(from: BuyingIntentState, to: BuyingIntentState) -> bool {
+    use BuyingIntentState::*;
+
+    matches!(
+        (from, to),
+        (PUBLISHED, CONFIRMED)
+            | (PUBLISHED, CANCELLED)
+            | (PUBLISHED, EXPIRED)
+            | (CONFIRMED, FULFILLED)
+            | (CONFIRMED, CANCELLED)
+    )
+}
+
+// Allowed Offer moves
+//
+// PUBLISHED -> ACCEPTED -> SHIPPED -> DELIVERED
+//     |           |           |
+//     v           v           v
+// CANCELLED   CANCELLED    DISPUTED -> DELIVERED / CANCELLED
+// EXPIRED
+pub fn can_transition_offer(from: OfferState, to: OfferState) -> bool {
+    use OfferState::*;
+
+    matches!(
+        (from, to),
+        (PUBLISHED, ACCEPTED)
+            | (PUBLISHED, CANCELLED)
+            | (PUBLISHED, EXPIRED)
+            | (ACCEPTED, SHIPPED)
+            | (ACCEPTED, CANCELLED)
+            | (SHIPPED, DELIVERED)
+            | (SHIPPED, DISPUTED)
+            | (DISPUTED, DELIVERED)
+            | (DISPUTED, CANCELLED)
+    )
+}
+
+// Validate a Buying Intent move and return the new state
+pub fn transition_buying_intent(
+    from: BuyingIntentState,
+    to: BuyingIntentState,
+) -> Result<BuyingIntentState> {
+    require!(
+        can_transition_buying_intent(from, to),
+        BestOfferErrorCode::InvalidBuyingIntentStateTransition
+    );
+
+    Ok(to)
+}
+
+// Validate an Offer move and return the new state
+pub fn transition_offer(from: OfferState, to: OfferState) -> Result<OfferState> {
+    require!(
+        can_transition_offer(from, to),
+        BestOfferErrorCode::InvalidOfferStateTransition
+    );
+
+    Ok(to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(deprecated)]
+    const BUYING_INTENT_STATES: [BuyingIntentState; 7] = [
+        BuyingIntentState::PUBLISHED,
+        BuyingIntentState::CANCELLED,
+        BuyingIntentState::CONFIRMED,
+        BuyingIntentState::SHIPPED,
+        BuyingIntentState::FULFILLED,
+        BuyingIntentState::DISPUTED,
+        BuyingIntentState::EXPIRED,
+    ];
+
+    const OFFER_STATES: [OfferState; 7] = [
+        OfferState::PUBLISHED,
+        OfferState::ACCEPTED,
+        OfferState::DELIVERED,
+        OfferState::CANCELLED,
+        OfferState::SHIPPED,
+        OfferState::DISPUTED,
+        OfferState::EXPIRED,
+    ];
+
+    #[test]
+    fn buying_intent_transitions() {
+        use BuyingIntentState::*;
+
+        let allowed = [
+            (PUBLISHED, CONFIRMED),
+            (PUBLISHED, CANCELLED),
+            (PUBLISHED, EXPIRED),
+            (CONFIRMED, FULFILLED),
+            (CONFIRMED, CANCELLED),
+        ];
+
+        for from in BUYING_INTENT_STATES {
+            for to in BUYING_INTENT_STATES {
+                let result = transition_buying_intent(from, to);
+
+                if allowed.contains(&(from, to)) {
+                    assert_eq!(result, Ok(to), "{:?} -> {:?} should be allowed", from, to);
+                } else {
+                    assert_eq!(
+                        result,
+                        Err(BestOfferErrorCode::InvalidBuyingIntentStateTransition.into()),
+                        "{:?} -> {:?} should be rejected",
+                        from,
+                        to
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offer_transitions() {
+        use OfferState::*;
+
+        let allowed = [
+            (PUBLISHED, ACCEPTED),
+            (PUBLISHED, CANCELLED),
+            (PUBLISHED, EXPIRED),
+            (ACCEPTED, SHIPPED),
+            (ACCEPTED, CANCELLED),
+            (SHIPPED, DELIVERED),
+            (SHIPPED, DISPUTED),
+            (DISPUTED, DELIVERED),
+            (DISPUTED, CANCELLED),
+        ];
+
+        for from in OFFER_STATES {
+            for to in OFFER_STATES {
+                let result = transition_offer(from, to);
+
+                if allowed.contains(&(from, to)) {
+                    assert_eq!(result, Ok(to), "{:?} -> {:?} should be allowed", from, to);
+                } else {
+                    assert_eq!(
+                        result,
+                        Err(BestOfferErrorCode::InvalidOfferStateTransition.into()),
+                        "{:?} -> {:?} should be rejected",
+                        from,
+                        to
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_states_cannot_move() {
+        for to in BUYING_INTENT_STATES {
+            assert!(!can_transition_buying_intent(
+                BuyingIntentState::FULFILLED,
+                to
+            ));
+            assert!(!can_transition_buying_intent(
+                BuyingIntentState::CANCELLED,
+                to
+            ));
+            assert!(!can_transition_buying_intent(
+                BuyingIntentState::EXPIRED,
+                to
+            ));
+        }
+
+        for to in OFFER_STATES {
+            assert!(!can_transition_offer(OfferState::DELIVERED, to));
+            assert!(!can_transition_offer(OfferState::CANCELLED, to));
+            assert!(!can_transition_offer(OfferState::EXPIRED, to));
+        }
+    }
+
+    #[test]
+    fn buying_intent_discriminants_are_stable() {
+        let discriminants: Vec<u8> = BUYING_INTENT_STATES
+            .iter()
+            .map(|state| state.try_to_vec().unwrap()[0])
+            .collect();
+
+        assert_eq!(discriminants, [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(BuyingIntentState::FULFILLED.try_to_vec().unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn buyer_cannot_accept_twice() {
+        let accepted = transition_offer(OfferState::PUBLISHED, OfferState::ACCEPTED).unwrap();
+
+        assert!(transition_offer(accepted, OfferState::ACCEPTED).is_err());
+    }
+
+    #[test]
+    fn cannot_fulfil_without_shipping() {
+        assert!(transition_offer(OfferState::ACCEPTED, OfferState::DELIVERED).is_err());
+        assert!(transition_offer(OfferState::PUBLISHED, OfferState::DELIVERED).is_err());
+        assert!(transition_buying_intent(
+            BuyingIntentState::PUBLISHED,
+            BuyingIntentState::FULFILLED
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+use crate::error::BestOfferErrorCode;
+use crate::{BuyingIntent, BuyingIntentState, Offer};
+
+// Basis points denominator (100% = 10_000)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Part of an amount expressed in basis points, rounded down
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    require!(
+        bps as u64 <= BPS_DENOMINATOR,
+        BestOfferErrorCode::InvalidBasisPoints
+    );
+
+    let part = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    Ok(part as u64)
+}
+
+// Split a seller amount into (fee, seller net amount)
+pub fn split_fee(amount: u64, fee_bps: u16) -> Result<(u64, u64)> {
+    let fee_amount = bps_of(amount, fee_bps)?;
+
+    let seller_amount = amount
+        .checked_sub(fee_amount)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+
+    Ok((fee_amount, seller_amount))
+}
+
+// Split a treasury withdrawal into (recipient amounts, destination remainder).
+// One share in basis points per recipient, at most 100% in total.
+pub fn split_withdrawal(
+    amount: u64,
+    shares_bps: &[u16],
+    recipients: usize,
+) -> Result<(Vec<u64>, u64)> {
+    require!(
+        shares_bps.len() == recipients,
+        BestOfferErrorCode::InvalidSplit
+    );
+
+    let total_shares = shares_bps
+        .iter()
+        .try_fold(0_u64, |total, share| total.checked_add(*share as u64))
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    require!(
+        total_shares <= BPS_DENOMINATOR,
+        BestOfferErrorCode::InvalidSplit
+    );
+
+    let mut recipient_amounts = Vec::with_capacity(recipients);
+    let mut remaining_amount = amount;
+
+    for share_bps in shares_bps {
+        let recipient_amount = bps_of(amount, *share_bps)?;
+        remaining_amount = remaining_amount
+            .checked_sub(recipient_amount)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        recipient_amounts.push(recipient_amount);
+    }
+
+    Ok((recipient_amounts, remaining_amount))
+}
+
+// Total paid by the buyer: unit price x quantity + shipping
+pub fn order_total(unit_price: u64, quantity: u16, shipping_price: u64) -> Result<u64> {
+    unit_price
+        .checked_mul(quantity as u64)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?
+        .checked_add(shipping_price)
+        .ok_or(BestOfferErrorCode::NumericalOverflow.into())
+}
+
+// Free the slot of a withdrawn offer on a buying intent still collecting offers.
+// The buying intent may already be closed.
+pub fn release_offer_slot(
+    buying_intent: &AccountInfo,
+    offer: Pubkey,
+    was_revealed: bool,
+) -> Result<()> {
+    if buying_intent.owner != &crate::ID || buying_intent.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut buying_intent_data =
+        BuyingIntent::try_deserialize(&mut &buying_intent.try_borrow_data()?[..])?;
+
+    if buying_intent_data.state == BuyingIntentState::PUBLISHED {
+        buying_intent_data.offer_count = buying_intent_data
+            .offer_count
+            .checked_sub(1)
+            .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+        if was_revealed {
+            buying_intent_data.remove_revealed_offer(offer)?;
+        }
+        buying_intent_data.try_serialize(&mut &mut buying_intent.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
+
+// Move tokens out of an accepted offer vault, signed by the offer PDA
+pub fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    offer: &Account<'info, Offer>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let seeds = &[
+        b"offer",
+        offer.buying_intent.as_ref(),
+        offer.seller.as_ref(),
+        &[offer.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let transfer_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: offer.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        transfer_accounts,
+        signer_seeds,
+    );
+
+    transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+// Close the empty vault, the rent goes to the destination
+pub fn close_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    offer: &Account<'info, Offer>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let seeds = &[
+        b"offer",
+        offer.buying_intent.as_ref(),
+        offer.seller.as_ref(),
+        &[offer.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let close_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: destination.clone(),
+        authority: offer.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        close_accounts,
+        signer_seeds,
+    );
+
+    close_account(cpi_ctx)
+}
+
+// Close a PDA of this program if it was ever created, the rent goes to the destination
+pub fn close_if_initialized<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.owner != &crate::ID || account.data_is_empty() {
+        return Ok(());
+    }
+
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(BestOfferErrorCode::NumericalOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+// Close the tracking details of every shipment of an offer, passed in shipment
+// order. The seller paid them, the rent goes back to the destination.
+pub fn close_tracking_details<'info>(
+    offer: &Account<'info, Offer>,
+    tracking_details: &[AccountInfo<'info>],
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(
+        tracking_details.len() == offer.shipment_count as usize,
+        BestOfferErrorCode::InvalidTrackingDetails
+    );
+
+    let offer_key = offer.key();
+
+    for (shipment, tracking_details_info) in (0_u16..).zip(tracking_details) {
+        let (expected_tracking_details, _) = Pubkey::find_program_address(
+            &[
+                b"tracking_details",
+                offer_key.as_ref(),
+                shipment.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        require!(
+            tracking_details_info.key() == expected_tracking_details
+                && tracking_details_info.is_writable,
+            BestOfferErrorCode::InvalidTrackingDetails
+        );
+
+        close_if_initialized(tracking_details_info, destination)?;
+    }
+
+    Ok(())
+}
+
+// Move lamports out of an accepted offer SOL vault, signed by the vault PDA
+pub fn transfer_from_sol_vault<'info>(
+    system_program: &Program<'info, System>,
+    offer: &Account<'info, Offer>,
+    sol_vault: &SystemAccount<'info>,
+    sol_vault_bump: u8,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let offer_key = offer.key();
+    let seeds = &[b"sol_vault".as_ref(), offer_key.as_ref(), &[sol_vault_bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        Transfer {
+            from: sol_vault.to_account_info(),
+            to,
+        },
+        signer_seeds,
+    );
+
+    transfer(cpi_ctx, amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bps_of_rounds_down() {
+        assert_eq!(bps_of(400_000_000, 100).unwrap(), 4_000_000);
+        assert_eq!(bps_of(99, 100).unwrap(), 0);
+        assert_eq!(bps_of(u64::MAX, 10_000).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn bps_of_rejects_more_than_100_percent() {
+        assert_eq!(
+            bps_of(100, 10_001),
+            Err(BestOfferErrorCode::InvalidBasisPoints.into())
+        );
+    }
+
+    #[test]
+    fn order_total_includes_quantity_and_shipping() {
+        assert_eq!(
+            order_total(400_000_000, 1, 40_000_000).unwrap(),
+            440_000_000
+        );
+        assert_eq!(
+            order_total(400_000_000, 3, 40_000_000).unwrap(),
+            1_240_000_000
+        );
+        assert_eq!(order_total(400_000_000, 0, 40_000_000).unwrap(), 40_000_000);
+    }
+
+    #[test]
+    fn order_total_overflow() {
+        assert_eq!(
+            order_total(u64::MAX, 2, 0),
+            Err(BestOfferErrorCode::NumericalOverflow.into())
+        );
+        assert_eq!(
+            order_total(u64::MAX, 1, 1),
+            Err(BestOfferErrorCode::NumericalOverflow.into())
+        );
+    }
+
+    #[test]
+    fn split_fee_keeps_total() {
+        let (fee, seller) = split_fee(440_000_000, 100).unwrap();
+
+        assert_eq!(fee, 4_400_000);
+        assert_eq!(fee + seller, 440_000_000);
+    }
+
+    #[test]
+    fn split_withdrawal_leaves_the_rest_to_the_destination() {
+        let (amounts, rest) = split_withdrawal(1_001, &[5_000, 2_500], 2).unwrap();
+
+        assert_eq!(amounts, vec![500, 250]);
+        assert_eq!(rest, 251);
+
+        assert_eq!(split_withdrawal(1_000, &[], 0).unwrap(), (vec![], 1_000));
+    }
+
+    #[test]
+    fn split_withdrawal_rejects_invalid_splits() {
+        assert_eq!(
+            split_withdrawal(1_000, &[5_000], 2),
+            Err(BestOfferErrorCode::InvalidSplit.into())
+        );
+        assert_eq!(
+            split_withdrawal(1_000, &[5_000, 5_001], 2),
+            Err(BestOfferErrorCode::InvalidSplit.into())
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+
+use crate::error::BestOfferErrorCode;
+use crate::utils::BPS_DENOMINATOR;
+use crate::OfferBond;
+
+// ISO 3166-1 alpha-2 codes, sorted for binary search
+pub const COUNTRY_CODES: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+// ISO 3166-2:US states, district and outlying areas, sorted
+pub const US_STATE_CODES: [&str; 57] = [
+    "AK", "AL", "AR", "AS", "AZ", "CA", "CO", "CT", "DC", "DE", "FL", "GA", "GU", "HI", "IA", "ID",
+    "IL", "IN", "KS", "KY", "LA", "MA", "MD", "ME", "MI", "MN", "MO", "MP", "MS", "MT", "NC", "ND",
+    "NE", "NH", "NJ", "NM", "NV", "NY", "OH", "OK", "OR", "PA", "PR", "RI", "SC", "SD", "TN", "TX",
+    "UM", "UT", "VA", "VI", "VT", "WA", "WI", "WV", "WY",
+];
+
+// ISO 3166-2:CA provinces and territories, sorted
+pub const CA_PROVINCE_CODES: [&str; 13] = [
+    "AB", "BC", "MB", "NB", "NL", "NS", "NT", "NU", "ON", "PE", "QC", "SK", "YT",
+];
+
+// GTIN-14 is the longest format, shorter ones are left padded with zeros
+const MAX_GTIN: u64 = 100_000_000_000_000;
+
+// 30 days, for each sealed-bid phase
+const MAX_SEALED_BID_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+pub fn validate_shipping_destination(country_code: &str, state_code: Option<&str>) -> Result<()> {
+    require!(
+        COUNTRY_CODES.binary_search(&country_code).is_ok(),
+        BestOfferErrorCode::InvalidCountryCode
+    );
+
+    // Carriers need the state or province to ship to the US and Canada
+    let subdivisions: Option<&[&str]> = match country_code {
+        "US" => Some(&US_STATE_CODES),
+        "CA" => Some(&CA_PROVINCE_CODES),
+        _ => None,
+    };
+
+    match (subdivisions, state_code) {
+        (Some(_), None) => err!(BestOfferErrorCode::MissingStateCode),
+        (Some(codes), Some(state_code)) => {
+            require!(
+                codes.binary_search(&state_code).is_ok(),
+                BestOfferErrorCode::InvalidStateCode
+            );
+            Ok(())
+        }
+        // Other subdivisions are only checked against the ISO 3166-2 format
+        (None, Some(state_code)) => {
+            require!(
+                (1..=3).contains(&state_code.len())
+                    && state_code
+                        .bytes()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()),
+                BestOfferErrorCode::InvalidStateCode
+            );
+            Ok(())
+        }
+        (None, None) => Ok(()),
+    }
+}
+
+// GTIN-8, GTIN-12, GTIN-13 and GTIN-14 share the GS1 check digit
+pub fn validate_gtin(gtin: u64) -> Result<()> {
+    require!(gtin > 0 && gtin < MAX_GTIN, BestOfferErrorCode::InvalidGtin);
+
+    let check_digit = gtin % 10;
+    let mut payload = gtin / 10;
+    let mut sum = 0;
+    let mut weight = 3;
+
+    // Weights alternate 3 and 1 starting from the rightmost payload digit
+    while payload > 0 {
+        sum += (payload % 10) * weight;
+        payload /= 10;
+        weight = 4 - weight;
+    }
+
+    require!(
+        (10 - sum % 10) % 10 == check_digit,
+        BestOfferErrorCode::InvalidGtin
+    );
+
+    Ok(())
+}
+
+pub fn validate_quantity(quantity: u16) -> Result<()> {
+    require!(quantity > 0, BestOfferErrorCode::InvalidQuantity);
+
+    Ok(())
+}
+
+pub fn validate_sealed_bid_periods(commit_period: i64, reveal_period: i64) -> Result<()> {
+    for period in [commit_period, reveal_period] {
+        require!(
+            period > 0 && period <= MAX_SEALED_BID_PERIOD,
+            BestOfferErrorCode::InvalidSealedBidPeriods
+        );
+    }
+
+    Ok(())
+}
+
+// Expiry strictly after `earliest`, and no later than `latest`
+pub fn validate_expires_at(expires_at: i64, earliest: i64, latest: i64) -> Result<()> {
+    require!(
+        expires_at > earliest && expires_at <= latest,
+        BestOfferErrorCode::InvalidExpiry
+    );
+
+    Ok(())
+}
+
+// An offer is a discount on the public price, never a markup
+pub fn validate_offer_price(public_price: u64, offer_price: u64) -> Result<()> {
+    require!(offer_price > 0, BestOfferErrorCode::InvalidOfferPrice);
+    require!(
+        offer_price <= public_price,
+        BestOfferErrorCode::OfferPriceAbovePublicPrice
+    );
+
+    Ok(())
+}
+
+// A required bond locks something, and the treasury cannot take more than the whole bond
+pub fn validate_offer_bond(offer_bond: &OfferBond) -> Result<()> {
+    require!(offer_bond.amount > 0, BestOfferErrorCode::InvalidOfferBond);
+    require!(
+        offer_bond.treasury_share_bps as u64 <= BPS_DENOMINATOR,
+        BestOfferErrorCode::InvalidBasisPoints
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_error(result: Result<()>, expected: BestOfferErrorCode) {
+        assert_eq!(result.unwrap_err(), expected.into());
+    }
+
+    #[test]
+    fn code_lists_are_sorted() {
+        for codes in [
+            &COUNTRY_CODES[..],
+            &US_STATE_CODES[..],
+            &CA_PROVINCE_CODES[..],
+        ] {
+            assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn validates_shipping_destination() {
+        assert!(validate_shipping_destination("FR", None).is_ok());
+        assert!(validate_shipping_destination("FR", Some("75")).is_ok());
+        assert!(validate_shipping_destination("US", Some("NY")).is_ok());
+        assert!(validate_shipping_destination("CA", Some("QC")).is_ok());
+
+        assert_error(
+            validate_shipping_destination("XX", None),
+            BestOfferErrorCode::InvalidCountryCode,
+        );
+        assert_error(
+            validate_shipping_destination("fr", None),
+            BestOfferErrorCode::InvalidCountryCode,
+        );
+        assert_error(
+            validate_shipping_destination("US", None),
+            BestOfferErrorCode::MissingStateCode,
+        );
+        assert_error(
+            validate_shipping_destination("CA", None),
+            BestOfferErrorCode::MissingStateCode,
+        );
+        assert_error(
+            validate_shipping_destination("US", Some("QC")),
+            BestOfferErrorCode::InvalidStateCode,
+        );
+        assert_error(
+            validate_shipping_destination("DE", Some("be")),
+            BestOfferErrorCode::InvalidStateCode,
+        );
+        assert_error(
+            validate_shipping_destination("DE", Some("")),
+            BestOfferErrorCode::InvalidStateCode,
+        );
+    }
+
+    #[test]
+    fn validates_gtin_check_digit() {
+        // GTIN-13, GTIN-12, GTIN-8 and GTIN-14
+        for gtin in [3544056897834, 36000291452, 96385074, 10614141000415] {
+            assert!(validate_gtin(gtin).is_ok(), "{gtin}");
+        }
+
+        for gtin in [0, 3544056897835, 96385075, MAX_GTIN + 7] {
+            assert_error(validate_gtin(gtin), BestOfferErrorCode::InvalidGtin);
+        }
+    }
+
+    #[test]
+    fn validates_quantity_and_price() {
+        assert!(validate_quantity(1).is_ok());
+        assert_error(validate_quantity(0), BestOfferErrorCode::InvalidQuantity);
+
+        assert!(validate_offer_price(100, 100).is_ok());
+        assert!(validate_offer_price(100, 1).is_ok());
+        assert_error(
+            validate_offer_price(100, 0),
+            BestOfferErrorCode::InvalidOfferPrice,
+        );
+        assert_error(
+            validate_offer_price(100, 101),
+            BestOfferErrorCode::OfferPriceAbovePublicPrice,
+        );
+    }
+
+    #[test]
+    fn validates_expiry_window() {
+        assert!(validate_expires_at(101, 100, 200).is_ok());
+        assert!(validate_expires_at(200, 100, 200).is_ok());
+        assert_error(
+            validate_expires_at(100, 100, 200),
+            BestOfferErrorCode::InvalidExpiry,
+        );
+        assert_error(
+            validate_expires_at(201, 100, 200),
+            BestOfferErrorCode::InvalidExpiry,
+        );
+    }
+
+    #[test]
+    fn validates_offer_bond() {
+        let offer_bond = |amount, treasury_share_bps| OfferBond {
+            amount,
+            in_payment_mint: false,
+            treasury_share_bps,
+        };
+
+        assert!(validate_offer_bond(&offer_bond(1, 10_000)).is_ok());
+        assert_error(
+            validate_offer_bond(&offer_bond(0, 0)),
+            BestOfferErrorCode::InvalidOfferBond,
+        );
+        assert_error(
+            validate_offer_bond(&offer_bond(1, 10_001)),
+            BestOfferErrorCode::InvalidBasisPoints,
+        );
+    }
+}
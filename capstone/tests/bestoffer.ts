@@ -8,14 +8,14 @@ import {
 import {Bestoffer} from "../target/types/bestoffer";
 
 import {Keypair, LAMPORTS_PER_SOL, PublicKey} from "@solana/web3.js";
+import {createHash} from "crypto";
 
 import {assert} from "chai";
 
-import sodium from 'libsodium-wrappers';
-import bs58 from 'bs58';
 
 import {BUYING_INTENT_STATES, OFFER_STATES} from "./enums";
-import {confirm, numberToLeBytes} from "./utils";
+import {confirm, expectError, numberToLeBytes} from "./utils";
+import {DeliveryAddress, DELIVERY_INFORMATION_VERSION, openDeliveryAddress, sealDeliveryAddress} from "./delivery";
 
 import buyerWallet from "../buyer-wallet.json";
 import sellerWallet from "../seller-wallet.json";
@@ -158,6 +158,30 @@ describe("bestoffer", () => {
         }
     });
 
+    step("Accept the test mint", async () => {
+
+        const acceptedMint = PublicKey.findProgramAddressSync(
+            [Buffer.from("accepted_mint"), mintKeypair.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        // Fee defaults to the marketplace fee
+        await confirm(connection, await program.methods
+            .addAcceptedMint(null, new anchor.BN(1_000_000), null)
+            .accounts({
+                admin: admin.publicKey,
+                mint: mintKeypair.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        const acceptedMintData = await program.account.acceptedMint.fetch(acceptedMint);
+        assert.equal(acceptedMintData.mint.toString(), mintKeypair.publicKey.toString());
+        assert.equal(acceptedMintData.fee, 100);
+        assert.equal(acceptedMintData.minAmount.toNumber(), 1_000_000);
+        assert.isTrue(acceptedMintData.enabled);
+    });
+
     //  Create Buying Intent
     step("Create Buying Intent", async () => {
 
@@ -181,7 +205,9 @@ describe("bestoffer", () => {
                 productName,
                 shippingCountryCode,
                 null,
-                quantity
+                quantity,
+                null,
+                null
             )
             .accounts({
                 buyer: buyer.publicKey,
@@ -246,10 +272,8 @@ describe("bestoffer", () => {
         const offerPrice: number = 400_000_000;
         const shippingPrice: number = 40_000_000;
 
-        // USDC For testing
-        const mint: PublicKey = new PublicKey(
-            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
-        );
+        // Mint created for testing, the buyer must pay with it
+        const mint: PublicKey = mintKeypair.publicKey;
 
         const offerSignature = await program.methods
             .createOffer(
@@ -257,7 +281,8 @@ describe("bestoffer", () => {
                 new anchor.BN(publicPrice),
                 new anchor.BN(offerPrice),
                 new anchor.BN(shippingPrice),
-                mint
+                mint,
+                null
             )
             .accounts({
                 seller: seller1.publicKey,
@@ -276,7 +301,6 @@ describe("bestoffer", () => {
                     Buffer.from("offer"),
                     buyingIntent.toBuffer(),
                     seller1.publicKey.toBuffer(),
-                    numberToLeBytes(beforeTestConfigData.offerIncrement.toNumber()),
                 ],
                 program.programId
             )[0]
@@ -300,16 +324,28 @@ describe("bestoffer", () => {
 
         // Offer increment should be +1
         assert.equal(afterConfigData.offerIncrement.toNumber(), beforeTestConfigData.offerIncrement.toNumber() + 1);
-    });
 
-    step("Accept offer", async () => {
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.equal(buyingIntentData.offerCount, 1);
 
-        await sodium.ready;
+        // The seller updates the offer instead of stacking a second one
+        let duplicated = false;
+        try {
+            await program.methods
+                .createOffer(url, new anchor.BN(publicPrice), new anchor.BN(offerPrice - 1), new anchor.BN(shippingPrice), mint, null)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller1])
+                .rpc();
+        } catch (e) {
+            duplicated = e.logs?.some((log: string) => log.includes("already in use")) ?? false;
+        }
+        assert.isTrue(duplicated);
+    });
 
-        const sellerX25519PublicKey = sodium.crypto_sign_ed25519_pk_to_curve25519(bs58.decode(seller1.publicKey.toBase58()));
-        const sellerX25519SecretKey = sodium.crypto_sign_ed25519_sk_to_curve25519(seller1.secretKey);
-        const buyerEphemeral = sodium.crypto_box_keypair();
-        const nonce = sodium.randombytes_buf(sodium.crypto_box_NONCEBYTES);
+    step("Accept offer", async () => {
 
         const config = PublicKey.findProgramAddressSync(
             [Buffer.from("config")],
@@ -328,62 +364,50 @@ describe("bestoffer", () => {
                 Buffer.from("offer"),
                 buyingIntent.toBuffer(),
                 seller1.publicKey.toBuffer(),
-                numberToLeBytes(beforeTestConfigData.offerIncrement.toNumber() - 1)
             ],
             program.programId
         )[0];
 
 
-        const encodeForSeller = (message): Buffer => {
-            return Buffer.from(sodium.crypto_box_easy(
-                new TextEncoder().encode(message),
-                nonce,
-                sellerX25519PublicKey,
-                buyerEphemeral.privateKey
-            ));
-        };
-
-
         // Dériver l'adresse PDA pour encrypted_delivery_information
         const encryptedDeliveryInformation = PublicKey.findProgramAddressSync(
-            [Buffer.from("encrypted_delivery_information"), buyingIntent.toBuffer()],
+            [Buffer.from("encrypted_delivery_information"), offer.toBuffer()],
             program.programId
         )[0];
 
-        // Dériver l'adresse du vault (compte de token associé pour le buying intent)
+        // Dériver l'adresse du vault (compte de token associé pour l'offre)
 
         const vault = await getAssociatedTokenAddress(
             mintKeypair.publicKey,
-            buyingIntent,
+            offer,
             true,
             TOKEN_PROGRAM_ID,
             ASSOCIATED_TOKEN_PROGRAM_ID
         );
 
-        const address = {
+        const address: DeliveryAddress = {
             firstname: 'Pete',
             lastname: 'Jones',
             address_line_1: '123 Main St',
-            address_line_2: '',
+            address_line_2: null,
             city: 'New York',
             postal_code: '10001',
             country_code: 'US',
             state_code: 'NY',
         }
 
+        // The admin is the default arbiter, it can read the address during disputes
+        const envelope = await sealDeliveryAddress(address, seller1.publicKey, admin.publicKey);
+
         const acceptOfferSignature = await program.methods
             .acceptOffer(
                 offer,
-                Array.from(nonce),
-                Array.from(buyerEphemeral.publicKey),
-                encodeForSeller(address.lastname),
-                encodeForSeller(address.firstname),
-                encodeForSeller(address.address_line_1),
-                encodeForSeller(address.address_line_2),
-                encodeForSeller(address.city),
-                encodeForSeller(address.postal_code),
-                encodeForSeller(address.country_code),
-                encodeForSeller(address.state_code)
+                1,
+                envelope.version,
+                envelope.nonce,
+                envelope.encryptedAddress,
+                envelope.sellerSealedKey,
+                envelope.arbiterSealedKey
             )
             .accounts({
                 buyer: buyer.publicKey,
@@ -403,32 +427,26 @@ describe("bestoffer", () => {
             encryptedDeliveryInformation
         );
 
-        const decodeFromBuyer = (encryptedMessage: Uint8Array): string => {
-            const decrypted = sodium.crypto_box_open_easy(
-                encryptedMessage,
-                Uint8Array.from(encryptedDeliveryInformationData.nonce),
-                Uint8Array.from(encryptedDeliveryInformationData.buyerEphemeralPubkey),
-                sellerX25519SecretKey
-            );
-
-            return new TextDecoder().decode(decrypted);
-        };
-
+        assert.equal(encryptedDeliveryInformationData.version, DELIVERY_INFORMATION_VERSION);
 
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryFirstname), address.firstname);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryLastname), address.lastname);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryAddressLine1), address.address_line_1);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryAddressLine2), address.address_line_2);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryCity), address.city);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryPostalCode), address.postal_code);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryCountryCode), address.country_code);
-        assert.equal(decodeFromBuyer(encryptedDeliveryInformationData.encryptedDeliveryStateCode), address.state_code);
+        // Seller and arbiter both open the same envelope
+        assert.deepEqual(await openDeliveryAddress(encryptedDeliveryInformationData, seller1), address);
+        assert.deepEqual(await openDeliveryAddress(encryptedDeliveryInformationData, admin), address);
 
         const buyerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.buyer.address);
-        assert.equal(buyerBalance.value.uiAmount, 600); // 1000 - 400 lock in vault
+        assert.equal(buyerBalance.value.uiAmount, 560); // 1000 - (400 x 1 + 40 shipping) lock in vault
 
         const vaultBalance = await connection.getTokenAccountBalance(vault);
-        assert.equal(vaultBalance.value.uiAmount, 400); // 400 lock in vault
+        assert.equal(vaultBalance.value.uiAmount, 440); // 440 lock in vault
+
+        const offerData = await program.account.offer.fetch(offer);
+        assert.equal(offerData.escrowAmount.toNumber(), 440_000_000);
+        assert.equal(offerData.quantity, 1);
+
+        // The whole quantity is allocated, the intent is confirmed
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.CONFIRMED);
+        assert.equal(buyingIntentData.allocatedQuantity, 1);
     })
 
     step("Create tracking detail", async () => {
@@ -445,6 +463,18 @@ describe("bestoffer", () => {
             program.programId
         )[0];
 
+        const offer = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                buyingIntent.toBuffer(),
+                seller1.publicKey.toBuffer(),
+            ],
+            program.programId
+        )[0];
+
+        // Everything ships in a single parcel
+        const quantity = (await program.account.buyingIntent.fetch(buyingIntent)).quantity;
+
         const trackingDetails = {
             carrier_name: 'UPS',
             tracking_url: 'https://www.ups.com/track?loc=en_US&requester=ST&trackingNumber=1Z000000000000000',
@@ -456,10 +486,12 @@ describe("bestoffer", () => {
                 trackingDetails.carrier_name,
                 trackingDetails.tracking_url,
                 trackingDetails.tracking_code,
+                quantity,
             )
             .accounts({
                 seller: seller1.publicKey,
                 buyingIntent: buyingIntent,
+                offer: offer,
             })
             .signers([seller1])
             .rpc();
@@ -468,7 +500,8 @@ describe("bestoffer", () => {
             PublicKey.findProgramAddressSync(
                 [
                     Buffer.from("tracking_details"),
-                    buyingIntent.toBuffer(),
+                    offer.toBuffer(),
+                    numberToLeBytes(0, 2),
                 ],
                 program.programId
             )[0]
@@ -477,6 +510,8 @@ describe("bestoffer", () => {
         assert.equal(trackingDetailsData.carrierName, trackingDetails.carrier_name);
         assert.equal(trackingDetailsData.trackingUrl, trackingDetails.tracking_url);
         assert.equal(trackingDetailsData.trackingCode, trackingDetails.tracking_code);
+        assert.equal(trackingDetailsData.shipment, 0);
+        assert.equal(trackingDetailsData.units, quantity);
 
     });
 
@@ -501,7 +536,6 @@ describe("bestoffer", () => {
                 Buffer.from("offer"),
                 buyingIntent.toBuffer(),
                 seller1.publicKey.toBuffer(),
-                numberToLeBytes(beforeTestConfigData.offerIncrement.toNumber() - 1)
             ],
             program.programId
         )[0];
@@ -509,7 +543,7 @@ describe("bestoffer", () => {
         // Get Vault Account
         const vault = await getAssociatedTokenAddress(
             mintKeypair.publicKey,
-            buyingIntent,
+            offer,
             true,
             TOKEN_PROGRAM_ID,
             ASSOCIATED_TOKEN_PROGRAM_ID
@@ -544,7 +578,6 @@ describe("bestoffer", () => {
             .accounts({
                 buyer: buyer.publicKey,
                 seller: seller1.publicKey,
-                config: config,
                 buyingIntent: buyingIntent,
                 offer: offer,
                 treasury: treasury,
@@ -565,12 +598,17 @@ describe("bestoffer", () => {
         const finalTreasuryBalance = await connection.getTokenAccountBalance(treasuryAta.address);
         const finalSellerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address);
 
-        // Get configs
-        const configData = await program.account.config.fetch(config);
+        // Fee tier of the payment mint
+        const acceptedMintData = await program.account.acceptedMint.fetch(
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("accepted_mint"), mintKeypair.publicKey.toBuffer()],
+                program.programId
+            )[0]
+        );
 
         // Treasury should receive 1% of vault balance
 
-        const expectedFee = (initialVaultBalance.value.uiAmount * configData.fee) / 10000;
+        const expectedFee = (initialVaultBalance.value.uiAmount * acceptedMintData.fee) / 10000;
         assert.equal(
             finalTreasuryBalance.value.uiAmount - initialTreasuryBalance.value.uiAmount,
             expectedFee
@@ -590,4 +628,2675 @@ describe("bestoffer", () => {
         assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.FULFILLED);
         assert.deepEqual(offerData.state, OFFER_STATES.DELIVERED);
     });
-});
\ No newline at end of file
+
+    step("Buyer cancel buying intent", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // Create a new buying intent to cancel
+        await confirm(connection, await program.methods
+            .createBuyingIntent(
+                new anchor.BN(3544056897834),
+                "Focal Bathys MG",
+                "FR",
+                null,
+                1,
+                null,
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        // A seller already posted an offer on it
+        await confirm(connection, await program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(599_000_000),
+                new anchor.BN(450_000_000),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                buyingIntent.toBuffer(),
+                seller1.publicKey.toBuffer(),
+            ],
+            program.programId
+        )[0];
+
+        // Every live offer must be cancelled with the buying intent
+        await expectError(program.methods
+            .cancelBuyingIntent()
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([buyer])
+            .rpc(), "IncompleteOffers");
+
+        const cancelSignature = await program.methods
+            .cancelBuyingIntent()
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .remainingAccounts([{pubkey: offer, isWritable: true, isSigner: false}])
+            .signers([buyer])
+            .rpc();
+
+        await confirm(connection, cancelSignature);
+
+        // Buying intent PDA should be closed
+        const buyingIntentInfo = await connection.getAccountInfo(buyingIntent);
+        assert.isNull(buyingIntentInfo);
+
+        // Offer should not be usable anymore
+        const offerData = await program.account.offer.fetch(offer);
+        assert.deepEqual(offerData.state, OFFER_STATES.CANCELLED);
+    });
+
+    step("Seller update and cancel offer", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(
+                new anchor.BN(3544056897834),
+                "Focal Bathys MG",
+                "FR",
+                null,
+                1,
+                null,
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(599_000_000),
+                new anchor.BN(450_000_000),
+                new anchor.BN(10_000_000),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                buyingIntent.toBuffer(),
+                seller1.publicKey.toBuffer(),
+            ],
+            program.programId
+        )[0];
+
+        // Seller lowers the price to beat a competitor
+        await confirm(connection, await program.methods
+            .updateOffer(
+                "https://www.focal.com/bathys-mg?promo=1",
+                new anchor.BN(420_000_000),
+                new anchor.BN(0)
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const updatedOfferData = await program.account.offer.fetch(offer);
+        assert.equal(updatedOfferData.url, "https://www.focal.com/bathys-mg?promo=1");
+        assert.equal(updatedOfferData.offerPrice.toNumber(), 420_000_000);
+        assert.equal(updatedOfferData.shippingPrice.toNumber(), 0);
+        assert.deepEqual(updatedOfferData.state, OFFER_STATES.PUBLISHED);
+
+        // Seller withdraws the offer
+        await confirm(connection, await program.methods
+            .cancelOffer()
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        // Offer PDA should be closed
+        const offerInfo = await connection.getAccountInfo(offer);
+        assert.isNull(offerInfo);
+    });
+
+    step("Buyer open dispute and arbiter resolves", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(
+                new anchor.BN(3544056897834),
+                "Focal Bathys MG",
+                "FR",
+                null,
+                1,
+                null,
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(200_000_000),
+                new anchor.BN(100_000_000),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                buyingIntent.toBuffer(),
+                seller1.publicKey.toBuffer(),
+            ],
+            program.programId
+        )[0];
+
+        const vault = await getAssociatedTokenAddress(
+            mintKeypair.publicKey,
+            offer,
+            true,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        // Encrypted content is not checked in this scenario
+        const encrypted = Buffer.alloc(32);
+
+        await confirm(connection, await program.methods
+            .acceptOffer(
+                offer,
+                1,
+                DELIVERY_INFORMATION_VERSION,
+                Array.from(Buffer.alloc(24)),
+                encrypted,
+                Array.from(Buffer.alloc(80)),
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        await confirm(connection, await program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000001", 1)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        // Parcel never arrived
+        await confirm(connection, await program.methods
+            .openDispute("Parcel never arrived")
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const disputedOfferData = await program.account.offer.fetch(offer);
+        assert.deepEqual(disputedOfferData.state, OFFER_STATES.DISPUTED);
+
+        const treasuryAta = await getOrCreateAssociatedTokenAccount(
+            connection,
+            admin,
+            mintKeypair.publicKey,
+            treasury,
+            true,
+            'confirmed',
+            {commitment: 'confirmed'},
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        const initialBuyerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.buyer.address);
+        const initialSellerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address);
+        const initialTreasuryBalance = await connection.getTokenAccountBalance(treasuryAta.address);
+
+        // Admin is the default arbiter, refund half of the escrow
+        await confirm(connection, await program.methods
+            .resolveDispute(5_000)
+            .accounts({
+                arbiter: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                treasuryAta: treasuryAta.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc());
+
+        const finalBuyerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.buyer.address);
+        const finalSellerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address);
+        const finalTreasuryBalance = await connection.getTokenAccountBalance(treasuryAta.address);
+
+        // Buyer gets 50, fee is only charged on the seller 50
+        assert.equal(finalBuyerBalance.value.uiAmount - initialBuyerBalance.value.uiAmount, 50);
+        assert.equal(finalTreasuryBalance.value.uiAmount - initialTreasuryBalance.value.uiAmount, 0.5);
+        assert.equal(finalSellerBalance.value.uiAmount - initialSellerBalance.value.uiAmount, 49.5);
+
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.FULFILLED);
+    });
+
+    step("Refund buyer after shipping timeout", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // Shorten the timeouts for the test
+        await confirm(connection, await program.methods
+            .setTimeouts(new anchor.BN(1), new anchor.BN(1))
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(
+                new anchor.BN(3544056897834),
+                "Focal Bathys MG",
+                "FR",
+                null,
+                1,
+                null,
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(200_000_000),
+                new anchor.BN(100_000_000),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                buyingIntent.toBuffer(),
+                seller1.publicKey.toBuffer(),
+            ],
+            program.programId
+        )[0];
+
+        const vault = await getAssociatedTokenAddress(
+            mintKeypair.publicKey,
+            offer,
+            true,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        const encrypted = Buffer.alloc(32);
+
+        const initialBuyerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.buyer.address);
+
+        await confirm(connection, await program.methods
+            .acceptOffer(
+                offer,
+                1,
+                DELIVERY_INFORMATION_VERSION,
+                Array.from(Buffer.alloc(24)),
+                encrypted,
+                Array.from(Buffer.alloc(80)),
+                null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        // Seller never ships
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+        // Anyone can crank the refund
+        await confirm(connection, await program.methods
+            .refundAfterTimeout()
+            .accounts({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc());
+
+        const finalBuyerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.buyer.address);
+        assert.equal(finalBuyerBalance.value.uiAmount, initialBuyerBalance.value.uiAmount);
+
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        const offerData = await program.account.offer.fetch(offer);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.CANCELLED);
+        assert.deepEqual(offerData.state, OFFER_STATES.CANCELLED);
+
+        // Restore the timeouts
+        await confirm(connection, await program.methods
+            .setTimeouts(beforeTestConfigData.shippingTimeout, beforeTestConfigData.deliveryTimeout)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+    });
+
+    step("Reject spoofed accounts", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        const createBuyingIntent = async (id: number): Promise<PublicKey> => {
+            await confirm(connection, await program.methods
+                .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                })
+                .signers([buyer])
+                .rpc());
+
+            return PublicKey.findProgramAddressSync(
+                [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(id)],
+                program.programId
+            )[0];
+        };
+
+        const createOffer = async (buyingIntent: PublicKey, seller: Keypair): Promise<PublicKey> => {
+            await confirm(connection, await program.methods
+                .createOffer(
+                    "https://www.focal.com/bathys-mg",
+                    new anchor.BN(200_000_000),
+                    new anchor.BN(10_000_000),
+                    new anchor.BN(0),
+                    mintKeypair.publicKey,
+                null
+                )
+                .accounts({
+                    seller: seller.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller])
+                .rpc());
+
+            return PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller.publicKey.toBuffer()],
+                program.programId
+            )[0];
+        };
+
+        const buyingIntentId = beforeTestConfigData.buyingIntentIncrement.toNumber();
+
+        // Buying intent with two competing offers, and another buying intent with its own offer
+        const buyingIntent = await createBuyingIntent(buyingIntentId);
+        const otherBuyingIntent = await createBuyingIntent(buyingIntentId + 1);
+        const offer = await createOffer(buyingIntent, seller1);
+        const competingOffer = await createOffer(buyingIntent, admin);
+        const foreignOffer = await createOffer(otherBuyingIntent, seller1);
+
+        // Another mint the seller never asked for
+        const otherMintKeypair = Keypair.generate();
+        await createRandomMint(connection, otherMintKeypair, admin, admin);
+
+        const vault = await getAssociatedTokenAddress(
+            mintKeypair.publicKey,
+            offer,
+            true,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        const encrypted = Buffer.alloc(32);
+
+        const acceptOffer = (offerArgument: PublicKey, accounts, signer: Keypair, envelope = {}) => program.methods
+            .acceptOffer(
+                offerArgument,
+                envelope["quantity"] ?? 1,
+                envelope["version"] ?? DELIVERY_INFORMATION_VERSION,
+                Array.from(Buffer.alloc(24)),
+                envelope["encryptedAddress"] ?? encrypted,
+                Array.from(Buffer.alloc(80)),
+                envelope["arbiterSealedKey"] ?? null
+            )
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                ...accounts,
+            })
+            .signers([signer])
+            .rpc();
+
+        // Offer argument does not match the offer account
+        await expectError(acceptOffer(competingOffer, {}, buyer), "OfferMismatch");
+
+        // Offer derived from another buying intent
+        await expectError(acceptOffer(foreignOffer, {offer: foreignOffer}, buyer), "ConstraintSeeds");
+
+        // Paying with another mint
+        await expectError(acceptOffer(offer, {mint: otherMintKeypair.publicKey}, buyer), "InvalidMint");
+
+        // Someone else than the buyer accepts the offer
+        await expectError(acceptOffer(offer, {buyer: admin.publicKey, buyerAta: associatedTokenAccounts.admin.address}, admin), "UnauthorizedBuyer");
+
+        // Unknown delivery information envelope
+        await expectError(acceptOffer(offer, {}, buyer, {version: DELIVERY_INFORMATION_VERSION + 1}), "UnsupportedDeliveryInformationVersion");
+
+        // Envelope larger than the account
+        await expectError(acceptOffer(offer, {}, buyer, {encryptedAddress: Buffer.alloc(401)}), "InvalidEncryptedAddressLength");
+
+        // Key sealed to someone who is not an arbiter
+        await expectError(acceptOffer(offer, {}, buyer, {arbiterSealedKey: {arbiter: buyer.publicKey, sealedKey: Array.from(Buffer.alloc(80))}}), "UnauthorizedArbiter");
+
+        // Legit acceptance
+        await confirm(connection, await acceptOffer(offer, {}, buyer));
+
+        const createTrackingDetails = (accounts, signer: Keypair) => program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000002", 1)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                ...accounts,
+            })
+            .signers([signer])
+            .rpc();
+
+        // A competing seller ships instead of the accepted one
+        await expectError(createTrackingDetails({seller: admin.publicKey}, admin), "UnauthorizedSeller");
+        await expectError(createTrackingDetails({seller: admin.publicKey, offer: competingOffer}, admin), "OfferNotAccepted");
+
+        // Legit shipment
+        await confirm(connection, await createTrackingDetails({}, seller1));
+
+        const treasuryAta = await getOrCreateAssociatedTokenAccount(
+            connection,
+            admin,
+            mintKeypair.publicKey,
+            treasury,
+            true,
+            'confirmed',
+            {commitment: 'confirmed'},
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        const acceptDelivery = (accounts) => program.methods
+            .acceptDelivery()
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                treasury: treasury,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                treasuryAta: treasuryAta.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                ...accounts,
+            })
+            .signers([buyer])
+            .rpc();
+
+        // Funds redirected to another seller
+        await expectError(acceptDelivery({seller: admin.publicKey, sellerAta: associatedTokenAccounts.admin.address}), "InvalidSeller");
+
+        // Offer that was never accepted
+        await expectError(acceptDelivery({seller: admin.publicKey, offer: competingOffer, sellerAta: associatedTokenAccounts.admin.address}), "OfferNotAccepted");
+
+        // Paying out in another mint
+        await expectError(acceptDelivery({mint: otherMintKeypair.publicKey}), "InvalidMint");
+
+        // Legit delivery
+        await confirm(connection, await acceptDelivery({}));
+
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.FULFILLED);
+    });
+
+    step("Admin updates config", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // Fee is bounded
+        await expectError(program.methods
+            .updateConfig(10_001, null)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc(), "FeeTooHigh");
+
+        // Only the admin can update
+        await expectError(program.methods
+            .updateConfig(0, null)
+            .accounts({
+                admin: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc(), "UnauthorizedAdmin");
+
+        await confirm(connection, await program.methods
+            .updateConfig(200, true)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        const pausedConfigData = await program.account.config.fetch(config);
+        assert.equal(pausedConfigData.fee, 200);
+        assert.isTrue(pausedConfigData.paused);
+
+        // No new buying intent while paused
+        await expectError(program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc(), "MarketplacePaused");
+
+        await confirm(connection, await program.methods
+            .updateConfig(beforeTestConfigData.fee, false)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        // Two steps admin rotation
+        await confirm(connection, await program.methods
+            .proposeAdmin(seller1.publicKey)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        await expectError(program.methods
+            .acceptAdmin()
+            .accounts({
+                newAdmin: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc(), "UnauthorizedPendingAdmin");
+
+        await confirm(connection, await program.methods
+            .acceptAdmin()
+            .accounts({
+                newAdmin: seller1.publicKey,
+            })
+            .signers([seller1])
+            .rpc());
+
+        assert.equal((await program.account.config.fetch(config)).admin.toString(), seller1.publicKey.toString());
+
+        // Give the admin role back
+        await confirm(connection, await program.methods
+            .proposeAdmin(admin.publicKey)
+            .accounts({
+                admin: seller1.publicKey,
+            })
+            .signers([seller1])
+            .rpc());
+
+        await confirm(connection, await program.methods
+            .acceptAdmin()
+            .accounts({
+                newAdmin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        const afterConfigData = await program.account.config.fetch(config);
+        assert.equal(afterConfigData.admin.toString(), admin.publicKey.toString());
+        assert.isNull(afterConfigData.pendingAdmin);
+        assert.isFalse(afterConfigData.paused);
+    });
+
+    step("Admin withdraws treasury", async () => {
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const treasuryAta = await getAssociatedTokenAddress(
+            mintKeypair.publicKey,
+            treasury,
+            true,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        );
+
+        const initialTreasuryBalance = await connection.getTokenAccountBalance(treasuryAta);
+        const initialAdminBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.admin.address);
+        const initialSellerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address);
+
+        // Only the admin can withdraw
+        await expectError(program.methods
+            .withdrawTreasury(null, [])
+            .accounts({
+                admin: buyer.publicKey,
+                mint: mintKeypair.publicKey,
+                treasuryAta: treasuryAta,
+                destination: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc(), "UnauthorizedAdmin");
+
+        // 30% to a split recipient, the rest to the admin
+        await confirm(connection, await program.methods
+            .withdrawTreasury(null, [3_000])
+            .accounts({
+                admin: admin.publicKey,
+                mint: mintKeypair.publicKey,
+                treasuryAta: treasuryAta,
+                destination: associatedTokenAccounts.admin.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts([{pubkey: associatedTokenAccounts.seller1.address, isWritable: true, isSigner: false}])
+            .signers([admin])
+            .rpc());
+
+        const treasuryAmount = Number(initialTreasuryBalance.value.amount);
+        const splitAmount = Math.floor(treasuryAmount * 3_000 / 10_000);
+
+        const finalTreasuryBalance = await connection.getTokenAccountBalance(treasuryAta);
+        const finalAdminBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.admin.address);
+        const finalSellerBalance = await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address);
+
+        assert.equal(Number(finalTreasuryBalance.value.amount), 0);
+        assert.equal(Number(finalSellerBalance.value.amount) - Number(initialSellerBalance.value.amount), splitAmount);
+        assert.equal(Number(finalAdminBalance.value.amount) - Number(initialAdminBalance.value.amount), treasuryAmount - splitAmount);
+    });
+
+    step("Emit versioned events", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        let createdEvent = null;
+        const listener = program.addEventListener("buyingIntentCreated", (event) => {
+            createdEvent = event;
+        });
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 2, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        // Give the websocket some time to deliver the logs
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+        await program.removeEventListener(listener);
+
+        assert.isNotNull(createdEvent);
+        assert.equal(createdEvent.version, 8);
+        assert.equal(createdEvent.buyer.toString(), buyer.publicKey.toString());
+        assert.equal(createdEvent.id.toNumber(), beforeTestConfigData.buyingIntentIncrement.toNumber());
+        assert.equal(createdEvent.quantity, 2);
+    });
+
+    step("Cleanup finished buying intents", async () => {
+
+        const pda = (seed: string, offer: PublicKey) => PublicKey.findProgramAddressSync(
+            [Buffer.from(seed), offer.toBuffer()],
+            program.programId
+        )[0];
+
+        // One tracking details account per shipment
+        const trackingDetails = (offer: PublicKey, shipmentCount: number) => Array.from(
+            {length: shipmentCount},
+            (_, shipment) => PublicKey.findProgramAddressSync(
+                [Buffer.from("tracking_details"), offer.toBuffer(), numberToLeBytes(shipment, 2)],
+                program.programId
+            )[0]
+        );
+
+        const buyingIntents = (await program.account.buyingIntent.all())
+            .filter(({account}) => account.acceptedOffers.length > 0);
+
+        let cleaned = 0;
+
+        for (const {publicKey: buyingIntent, account} of buyingIntents) {
+            const finished = "fulfilled" in account.state || "cancelled" in account.state;
+
+            // Each accepted offer is a slice with its own vault and delivery information
+            for (const offer of account.acceptedOffers) {
+                const offerData = await program.account.offer.fetchNullable(offer);
+                if (offerData === null) {
+                    continue;
+                }
+
+                const vault = await getAssociatedTokenAddress(offerData.mint, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+                const buyerAta = await getAssociatedTokenAddress(offerData.mint, account.buyer, false, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+                // Anyone can crank, here the admin
+                const cleanup = () => program.methods
+                    .cleanupBuyingIntent()
+                    .accountsPartial({
+                        payer: admin.publicKey,
+                        buyer: account.buyer,
+                        seller: offerData.seller,
+                        buyingIntent: buyingIntent,
+                        offer: offer,
+                        encryptedDeliveryInformation: pda("encrypted_delivery_information", offer),
+                        dispute: pda("dispute", offer),
+                        mint: offerData.mint,
+                        vault: vault,
+                        buyerAta: buyerAta,
+                        tokenProgram: TOKEN_PROGRAM_ID,
+                    })
+                    .remainingAccounts(trackingDetails(offer, offerData.shipmentCount)
+                        .map((pubkey) => ({pubkey, isWritable: true, isSigner: false})))
+                    .signers([admin])
+                    .rpc();
+
+                // Slices stay while the buying intent can still accept offers
+                if (!finished) {
+                    await expectError(cleanup(), "BuyingIntentNotFinished");
+                    continue;
+                }
+
+                const settled = "delivered" in offerData.state || "cancelled" in offerData.state;
+                if (!settled) {
+                    await expectError(cleanup(), "OfferNotSettled");
+                    continue;
+                }
+
+                const initialBuyerLamports = await connection.getBalance(account.buyer);
+
+                await confirm(connection, await cleanup());
+
+                // Every account of the slice is gone
+                for (const address of [
+                    offer,
+                    vault,
+                    pda("encrypted_delivery_information", offer),
+                    pda("dispute", offer),
+                    ...trackingDetails(offer, offerData.shipmentCount),
+                ]) {
+                    assert.isNull(await connection.getAccountInfo(address, "confirmed"));
+                }
+
+                // The buyer got the rent of the vault and delivery information back
+                assert.isAbove(await connection.getBalance(account.buyer, "confirmed"), initialBuyerLamports);
+
+                cleaned++;
+            }
+
+            // The buying intent goes with its last slice
+            if (finished) {
+                assert.isNull(await connection.getAccountInfo(buyingIntent, "confirmed"));
+            }
+        }
+
+        assert.isAbove(cleaned, 0);
+    });
+
+    step("Validate buying intent and offer inputs", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const createBuyingIntent = (gtin: number, countryCode: string, stateCode: string | null, quantity: number) => program.methods
+            .createBuyingIntent(new anchor.BN(gtin), "Focal Bathys MG", countryCode, stateCode, quantity, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc();
+
+        await expectError(createBuyingIntent(3544056897834, "XX", null, 1), "InvalidCountryCode");
+        await expectError(createBuyingIntent(3544056897834, "US", null, 1), "MissingStateCode");
+        await expectError(createBuyingIntent(3544056897834, "CA", "NY", 1), "InvalidStateCode");
+        await expectError(createBuyingIntent(3544056897835, "FR", null, 1), "InvalidGtin");
+        await expectError(createBuyingIntent(3544056897834, "FR", null, 0), "InvalidQuantity");
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // GTIN-12 shipped to New York
+        await confirm(connection, await createBuyingIntent(36000291452, "US", "NY", 1));
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const createOffer = (publicPrice: number, offerPrice: number) => program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(publicPrice),
+                new anchor.BN(offerPrice),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc();
+
+        await expectError(createOffer(599_000_000, 0), "InvalidOfferPrice");
+        await expectError(createOffer(599_000_000, 600_000_000), "OfferPriceAbovePublicPrice");
+
+        await confirm(connection, await createOffer(599_000_000, 599_000_000));
+
+        const offer = PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        const updateOffer = (offerPrice: number) => program.methods
+            .updateOffer("https://www.focal.com/bathys-mg", new anchor.BN(offerPrice), new anchor.BN(0))
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc();
+
+        // The public price cannot be raised afterwards to allow a markup
+        await expectError(updateOffer(600_000_000), "OfferPriceAbovePublicPrice");
+
+        // Updates run the same minimum order total as creation
+        await expectError(updateOffer(999_999), "AmountBelowMinimum");
+    });
+
+    step("Limit offers per buying intent", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        assert.equal(beforeTestConfigData.maxOffersPerIntent, 20);
+
+        const setMaxOffersPerIntent = (max: number) => program.methods
+            .setMaxOffersPerIntent(max)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        await expectError(setMaxOffersPerIntent(0), "InvalidMaxOffers");
+        await confirm(connection, await setMaxOffersPerIntent(1));
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const createOffer = (seller: Keypair) => program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(599_000_000),
+                new anchor.BN(450_000_000),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller])
+            .rpc();
+
+        await confirm(connection, await createOffer(seller1));
+        await expectError(createOffer(admin), "TooManyOffers");
+
+        // Cancelling frees the slot for another seller
+        await confirm(connection, await program.methods
+            .cancelOffer()
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: PublicKey.findProgramAddressSync(
+                    [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+            })
+            .signers([seller1])
+            .rpc());
+
+        assert.equal((await program.account.buyingIntent.fetch(buyingIntent)).offerCount, 0);
+
+        await confirm(connection, await createOffer(admin));
+        assert.equal((await program.account.buyingIntent.fetch(buyingIntent)).offerCount, 1);
+
+        await confirm(connection, await setMaxOffersPerIntent(beforeTestConfigData.maxOffersPerIntent));
+    });
+
+    step("Track reputation and require it for expensive offers", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const reputation = (wallet: PublicKey) => PublicKey.findProgramAddressSync(
+            [Buffer.from("reputation"), wallet.toBuffer()],
+            program.programId
+        )[0];
+
+        // Earlier steps settled deals between the buyer and seller1
+        const sellerReputation = await program.account.reputation.fetch(reputation(seller1.publicKey));
+        assert.equal(sellerReputation.wallet.toString(), seller1.publicKey.toString());
+        assert.isAbove(sellerReputation.completedDeals.toNumber(), 0);
+        assert.isAbove(sellerReputation.shippedDeals.toNumber(), 0);
+        assert.isAbove(sellerReputation.volumes.find(({mint}) => mint.equals(mintKeypair.publicKey)).amount.toNumber(), 0);
+
+        const buyerReputation = await program.account.reputation.fetch(reputation(buyer.publicKey));
+        assert.isAbove(buyerReputation.completedDeals.toNumber(), 0);
+
+        const setReputationRequirement = (requirement) => program.methods
+            .setReputationRequirement(requirement)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        // The price threshold is set per mint, in its own base units
+        const setReputationPriceThreshold = (threshold: anchor.BN) => program.methods
+            .updateAcceptedMint(null, null, null, threshold)
+            .accounts({
+                admin: admin.publicKey,
+                acceptedMint: PublicKey.findProgramAddressSync(
+                    [Buffer.from("accepted_mint"), mintKeypair.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+            })
+            .signers([admin])
+            .rpc();
+
+        await confirm(connection, await setReputationPriceThreshold(new anchor.BN(100_000_000)));
+        await confirm(connection, await setReputationRequirement({
+            minCompletedDeals: new anchor.BN(1_000),
+        }));
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const createOffer = (offerPrice: number) => program.methods
+            .createOffer(
+                "https://www.focal.com/bathys-mg",
+                new anchor.BN(599_000_000),
+                new anchor.BN(offerPrice),
+                new anchor.BN(0),
+                mintKeypair.publicKey,
+                null
+            )
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc();
+
+        // Above the threshold the seller needs 1000 completed deals
+        await expectError(createOffer(450_000_000), "InsufficientReputation");
+        await confirm(connection, await createOffer(100_000_000));
+
+        await confirm(connection, await setReputationRequirement(null));
+        await confirm(connection, await setReputationPriceThreshold(new anchor.BN("18446744073709551615")));
+    });
+
+    step("Only accept registered payment mints", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const acceptedMint = PublicKey.findProgramAddressSync(
+            [Buffer.from("accepted_mint"), mintKeypair.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const createOffer = (offerPrice: number, mint: PublicKey) => program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(599_000_000), new anchor.BN(offerPrice), new anchor.BN(0), mint, null)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc();
+
+        const updateAcceptedMint = (fee: number | null, minAmount: number | null, enabled: boolean | null) => program.methods
+            .updateAcceptedMint(fee, minAmount === null ? null : new anchor.BN(minAmount), enabled, null)
+            .accounts({
+                admin: admin.publicKey,
+                acceptedMint: acceptedMint,
+            })
+            .signers([admin])
+            .rpc();
+
+        // A mint the admin never registered
+        const unknownMintKeypair = Keypair.generate();
+        await createRandomMint(connection, unknownMintKeypair, admin, admin);
+        await expectError(createOffer(450_000_000, unknownMintKeypair.publicKey), "AccountNotInitialized");
+
+        // Below the minimum order total of the mint
+        await expectError(createOffer(999_999, mintKeypair.publicKey), "AmountBelowMinimum");
+
+        // Disabled mints take no new offer
+        await confirm(connection, await updateAcceptedMint(null, null, false));
+        await expectError(createOffer(450_000_000, mintKeypair.publicKey), "MintNotAccepted");
+
+        // Fee tiers are bounded like the marketplace fee
+        await expectError(updateAcceptedMint(5_000, null, null), "FeeTooHigh");
+
+        await confirm(connection, await updateAcceptedMint(50, null, true));
+        assert.equal((await program.account.acceptedMint.fetch(acceptedMint)).fee, 50);
+
+        await confirm(connection, await createOffer(450_000_000, mintKeypair.publicKey));
+
+        await confirm(connection, await updateAcceptedMint(100, null, null));
+    });
+
+    step("Settle a deal in native SOL", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        // The system program id stands for native SOL in the mint registry
+        const nativeSolMint = anchor.web3.SystemProgram.programId;
+
+        // Any other account that is not a token mint is rejected
+        await expectError(program.methods
+            .addAcceptedMint(null, new anchor.BN(0), null)
+            .accounts({
+                admin: admin.publicKey,
+                mint: buyer.publicKey,
+            })
+            .signers([admin])
+            .rpc(), "InvalidMint");
+
+        await confirm(connection, await program.methods
+            .addAcceptedMint(null, new anchor.BN(1_000_000), null)
+            .accounts({
+                admin: admin.publicKey,
+                mint: nativeSolMint,
+            })
+            .signers([admin])
+            .rpc());
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), nativeSolMint, null)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        const solVault = PublicKey.findProgramAddressSync(
+            [Buffer.from("sol_vault"), offer.toBuffer()],
+            program.programId
+        )[0];
+
+        // The token path refuses a native SOL offer
+        await expectError(program.methods
+            .acceptOffer(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc(), "InvalidMint");
+
+        await confirm(connection, await program.methods
+            .acceptOfferSol(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        // The vault holds the escrow on top of its own rent
+        const vaultRent = await connection.getMinimumBalanceForRentExemption(0);
+        assert.equal(await connection.getBalance(solVault), 100_000_000 + vaultRent);
+
+        await confirm(connection, await program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000003", 1)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const sellerBalanceBefore = await connection.getBalance(seller1.publicKey);
+        const treasuryBalanceBefore = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .acceptDeliverySol()
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        // 1% fee to the treasury PDA, the rest to the seller wallet
+        assert.equal(await connection.getBalance(seller1.publicKey) - sellerBalanceBefore, 99_000_000);
+        assert.equal(await connection.getBalance(treasury) - treasuryBalanceBefore, 1_000_000);
+
+        // 30% of the withdrawal to a split recipient, the rest to the admin
+        const buyerBalanceBefore = await connection.getBalance(buyer.publicKey);
+        const treasuryBalanceBeforeWithdrawal = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .withdrawTreasurySol(new anchor.BN(1_000_000), [3_000])
+            .accounts({
+                admin: admin.publicKey,
+                destination: admin.publicKey,
+            })
+            .remainingAccounts([{pubkey: buyer.publicKey, isWritable: true, isSigner: false}])
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(buyer.publicKey) - buyerBalanceBefore, 300_000);
+        assert.equal(treasuryBalanceBeforeWithdrawal - await connection.getBalance(treasury), 1_000_000);
+
+        const trackingDetails = PublicKey.findProgramAddressSync(
+            [Buffer.from("tracking_details"), offer.toBuffer(), numberToLeBytes(0, 2)],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .cleanupBuyingIntentSol()
+            .accounts({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .remainingAccounts([{pubkey: trackingDetails, isWritable: true, isSigner: false}])
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(solVault), 0);
+        assert.isNull(await connection.getAccountInfo(buyingIntent));
+    });
+
+    step("Ship and settle an order in several parcels", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // Bulk order of 3 units
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 3, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        const vault = await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        await confirm(connection, await program.methods
+            .acceptOffer(offer, 3, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const ship = (units: number) => program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000004", units)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc();
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+        const treasuryAta = await getAssociatedTokenAddress(mintKeypair.publicKey, treasury, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        const acceptShipment = (shipment: number) => program.methods
+            .acceptShipment(shipment)
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                treasuryAta: treasuryAta,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc();
+
+        const sellerBalance = async () => Number((await connection.getTokenAccountBalance(associatedTokenAccounts.seller1.address)).value.amount);
+
+        // Parcels cannot be empty or carry more than the order
+        await expectError(ship(0), "InvalidShipmentUnits");
+        await expectError(ship(4), "InvalidShipmentUnits");
+
+        await confirm(connection, await ship(2));
+        await expectError(ship(2), "InvalidShipmentUnits");
+        await confirm(connection, await ship(1));
+
+        let offerData = await program.account.offer.fetch(offer);
+        assert.equal(offerData.shipmentCount, 2);
+        assert.equal(offerData.shippedUnits, 3);
+
+        // First parcel releases 2/3 of the escrow, minus the 1% fee
+        const initialSellerBalance = await sellerBalance();
+        await confirm(connection, await acceptShipment(0));
+        assert.equal(await sellerBalance() - initialSellerBalance, 198_000_000);
+        await expectError(acceptShipment(0), "ShipmentAlreadyDelivered");
+
+        offerData = await program.account.offer.fetch(offer);
+        assert.deepEqual(offerData.state, OFFER_STATES.SHIPPED);
+        assert.equal(offerData.deliveredUnits, 2);
+        assert.equal(offerData.releasedAmount.toNumber(), 200_000_000);
+
+        // The last parcel releases the rest and fulfills the deal
+        await confirm(connection, await acceptShipment(1));
+        assert.equal(await sellerBalance() - initialSellerBalance, 297_000_000);
+        assert.equal(Number((await connection.getTokenAccountBalance(vault)).value.amount), 0);
+
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.FULFILLED);
+        assert.deepEqual((await program.account.offer.fetch(offer)).state, OFFER_STATES.DELIVERED);
+    });
+
+    step("Split one buying intent between two sellers", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 3, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const createOffer = async (seller: Keypair) => {
+            await confirm(connection, await program.methods
+                .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+                .accounts({
+                    seller: seller.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller])
+                .rpc());
+
+            return PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller.publicKey.toBuffer()],
+                program.programId
+            )[0];
+        };
+
+        const firstOffer = await createOffer(seller1);
+        const secondOffer = await createOffer(admin);
+
+        const acceptOffer = async (offer: PublicKey, quantity: number) => program.methods
+            .acceptOffer(offer, quantity, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID),
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc();
+
+        // The first seller covers two units, the intent stays open for the rest
+        await confirm(connection, await acceptOffer(firstOffer, 2));
+
+        let buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.PUBLISHED);
+        assert.equal(buyingIntentData.allocatedQuantity, 2);
+
+        await expectError(acceptOffer(secondOffer, 2), "QuantityExceedsRemaining");
+
+        // The second seller covers the last unit, each slice has its own escrow
+        await confirm(connection, await acceptOffer(secondOffer, 1));
+
+        buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.deepEqual(buyingIntentData.state, BUYING_INTENT_STATES.CONFIRMED);
+        assert.equal(buyingIntentData.acceptedOffers.length, 2);
+
+        assert.equal((await program.account.offer.fetch(firstOffer)).escrowAmount.toNumber(), 200_000_000);
+        assert.equal((await program.account.offer.fetch(secondOffer)).escrowAmount.toNumber(), 100_000_000);
+    });
+
+    step("Sealed-bid reverse auction", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+
+        // Short phases so the test can wait them out
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, {
+                commitPeriod: new anchor.BN(4),
+                revealPeriod: new anchor.BN(4),
+            }, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        // sha256(offer || offer_price || shipping_price || salt), prices as u64 little endian
+        const commitment = (offer: PublicKey, offerPrice: number, shippingPrice: number, salt: Buffer) => Array.from(createHash("sha256")
+            .update(offer.toBuffer())
+            .update(new anchor.BN(offerPrice).toArrayLike(Buffer, "le", 8))
+            .update(new anchor.BN(shippingPrice).toArrayLike(Buffer, "le", 8))
+            .update(salt)
+            .digest());
+
+        const bids = [
+            {seller: seller1, offerPrice: 180_000_000, salt: Keypair.generate().publicKey.toBuffer()},
+            {seller: admin, offerPrice: 170_000_000, salt: Keypair.generate().publicKey.toBuffer()},
+        ];
+
+        // The cheaper bid expires right after the reveal window
+        const revealDeadline = (await program.account.buyingIntent.fetch(buyingIntent)).sealedBid.revealDeadline.toNumber();
+        const expiresAt = (seller: Keypair) => seller === admin ? new anchor.BN(revealDeadline + 2) : null;
+
+        const offerOf = (seller: Keypair) => PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        // Open offers are refused, prices must be committed
+        await expectError(program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(180_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc(), "SealedBidRequired");
+
+        for (const {seller, offerPrice, salt} of bids) {
+            await confirm(connection, await program.methods
+                .commitOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), commitment(offerOf(seller), offerPrice, 0, salt), mintKeypair.publicKey, expiresAt(seller))
+                .accounts({
+                    seller: seller.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller])
+                .rpc());
+        }
+
+        // Nothing is visible before the reveal
+        assert.equal((await program.account.offer.fetch(offerOf(seller1))).offerPrice.toNumber(), 0);
+
+        const reveal = (seller: Keypair, offerPrice: number, salt: Buffer) => program.methods
+            .revealOffer(new anchor.BN(offerPrice), new anchor.BN(0), Array.from(salt))
+            .accounts({
+                seller: seller.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offerOf(seller),
+            })
+            .signers([seller])
+            .rpc();
+
+        await expectError(reveal(seller1, bids[0].offerPrice, bids[0].salt), "RevealPhaseNotOpen");
+        await new Promise((resolve) => setTimeout(resolve, 5_000));
+
+        // A wrong price is refused
+        await expectError(reveal(seller1, bids[0].offerPrice - 1, bids[0].salt), "InvalidPriceReveal");
+        for (const {seller, offerPrice, salt} of bids) {
+            await confirm(connection, await reveal(seller, offerPrice, salt));
+        }
+        assert.equal((await program.account.offer.fetch(offerOf(seller1))).offerPrice.toNumber(), bids[0].offerPrice);
+
+        const acceptOffer = async (offer: PublicKey) => program.methods
+            .acceptOffer(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID),
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc();
+
+        await expectError(acceptOffer(offerOf(seller1)), "RevealPhaseNotOver");
+        await new Promise((resolve) => setTimeout(resolve, 5_000));
+
+        // The cheaper offer has expired, it can't be accepted nor highlighted
+        await expectError(acceptOffer(offerOf(admin)), "OfferExpired");
+
+        const highlightBestOffer = async (mint: PublicKey) => program.methods
+            .highlightBestOffer(mint)
+            .accounts({
+                buyingIntent: buyingIntent,
+            })
+            .remainingAccounts(bids.map(({seller}) => ({pubkey: offerOf(seller), isWritable: false, isSigner: false})))
+            .rpc();
+
+        // No offer in another mint competes
+        await expectError(highlightBestOffer(anchor.web3.SystemProgram.programId), "NoOfferInMint");
+        await confirm(connection, await highlightBestOffer(mintKeypair.publicKey));
+
+        const buyingIntentData = await program.account.buyingIntent.fetch(buyingIntent);
+        assert.equal(buyingIntentData.bestOffer.toString(), offerOf(seller1).toString());
+
+        await confirm(connection, await acceptOffer(buyingIntentData.bestOffer));
+        assert.deepEqual((await program.account.buyingIntent.fetch(buyingIntent)).state, BUYING_INTENT_STATES.CONFIRMED);
+    });
+
+    step("Expire a buying intent and its leftover offer", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const expiresAt = Math.floor(Date.now() / 1000) + 4;
+
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, new anchor.BN(expiresAt))
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        const offer = PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+        // The offer cannot outlive the buying intent
+        const createOffer = (offerExpiresAt: number) => program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(599_000_000), new anchor.BN(450_000_000), new anchor.BN(0), mintKeypair.publicKey, new anchor.BN(offerExpiresAt))
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc();
+
+        await expectError(createOffer(expiresAt + 1), "InvalidExpiry");
+        await confirm(connection, await createOffer(expiresAt));
+
+        const expireBuyingIntent = () => program.methods
+            .expireBuyingIntent()
+            .accounts({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([admin])
+            .rpc();
+
+        const expireOffer = () => program.methods
+            .expireOffer()
+            .accounts({
+                payer: admin.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([admin])
+            .rpc();
+
+        await expectError(expireBuyingIntent(), "NotExpired");
+        await new Promise((resolve) => setTimeout(resolve, 6_000));
+
+        // Nobody accepted, the crank closes the buying intent then the offer left behind
+        await confirm(connection, await expireBuyingIntent());
+        assert.isNull(await connection.getAccountInfo(buyingIntent));
+
+        await confirm(connection, await expireOffer());
+        assert.isNull(await connection.getAccountInfo(offer));
+    });
+
+    step("Lock a seller bond, return it on cancel and slash it on a missed shipping deadline", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const bondAmount = 10_000_000;
+
+        const setOfferBond = (offerBond) => program.methods
+            .setOfferBond(offerBond)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        const setTimeouts = (shippingTimeout: anchor.BN, deliveryTimeout: anchor.BN) => program.methods
+            .setTimeouts(shippingTimeout, deliveryTimeout)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        await expectError(setOfferBond({amount: new anchor.BN(0), inPaymentMint: false, treasuryShareBps: 0}), "InvalidOfferBond");
+
+        // Half of a slashed bond goes to the treasury, half to the buyer
+        await confirm(connection, await setOfferBond({amount: new anchor.BN(bondAmount), inPaymentMint: false, treasuryShareBps: 5_000}));
+        await confirm(connection, await setTimeouts(new anchor.BN(1), new anchor.BN(1)));
+
+        const createBuyingIntentWithOffer = async (id: number) => {
+            await confirm(connection, await program.methods
+                .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                })
+                .signers([buyer])
+                .rpc());
+
+            const buyingIntent = PublicKey.findProgramAddressSync(
+                [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(id)],
+                program.programId
+            )[0];
+
+            await confirm(connection, await program.methods
+                .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller1])
+                .rpc());
+
+            const offer = PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+                program.programId
+            )[0];
+
+            return {buyingIntent, offer};
+        };
+
+        // The bond sits on top of the offer rent and comes back with it
+        const cancelled = await createBuyingIntentWithOffer(beforeTestConfigData.buyingIntentIncrement.toNumber());
+        assert.equal((await program.account.offer.fetch(cancelled.offer)).bondAmount.toNumber(), bondAmount);
+
+        const offerLamports = await connection.getBalance(cancelled.offer);
+        const sellerBalance = await connection.getBalance(seller1.publicKey);
+
+        await confirm(connection, await program.methods
+            .cancelOffer()
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: cancelled.buyingIntent,
+                offer: cancelled.offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalance + offerLamports);
+
+        // The seller never ships the accepted offer
+        const refunded = await createBuyingIntentWithOffer(beforeTestConfigData.buyingIntentIncrement.toNumber() + 1);
+        const vault = await getAssociatedTokenAddress(mintKeypair.publicKey, refunded.offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        await confirm(connection, await program.methods
+            .acceptOffer(refunded.offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: refunded.buyingIntent,
+                offer: refunded.offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+        const buyerBalance = await connection.getBalance(buyer.publicKey);
+        const treasuryBalance = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .refundAfterTimeout()
+            .accounts({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                buyingIntent: refunded.buyingIntent,
+                offer: refunded.offer,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(buyer.publicKey), buyerBalance + bondAmount / 2);
+        assert.equal(await connection.getBalance(treasury), treasuryBalance + bondAmount / 2);
+        assert.equal((await program.account.offer.fetch(refunded.offer)).bondAmount.toNumber(), 0);
+
+        // Restore the config
+        await confirm(connection, await setOfferBond(null));
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, beforeTestConfigData.deliveryTimeout));
+    });
+
+    step("Return the bond on a timeout claim and slash it when the seller loses a dispute", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const bondAmount = 10_000_000;
+
+        const treasuryAta = await getAssociatedTokenAddress(mintKeypair.publicKey, treasury, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        const setTimeouts = (shippingTimeout: anchor.BN, deliveryTimeout: anchor.BN) => program.methods
+            .setTimeouts(shippingTimeout, deliveryTimeout)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        // Half of a slashed bond goes to the treasury, half to the buyer
+        await confirm(connection, await program.methods
+            .setOfferBond({amount: new anchor.BN(bondAmount), inPaymentMint: false, treasuryShareBps: 5_000})
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+
+        // Accepted and shipped offer of a new buying intent
+        const createShippedOffer = async (id: number) => {
+            await confirm(connection, await program.methods
+                .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                })
+                .signers([buyer])
+                .rpc());
+
+            const buyingIntent = PublicKey.findProgramAddressSync(
+                [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(id)],
+                program.programId
+            )[0];
+
+            await confirm(connection, await program.methods
+                .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller1])
+                .rpc());
+
+            const offer = PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+                program.programId
+            )[0];
+            const vault = await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+            await confirm(connection, await program.methods
+                .acceptOffer(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                    buyingIntent: buyingIntent,
+                    offer: offer,
+                    mint: mintKeypair.publicKey,
+                    buyerAta: associatedTokenAccounts.buyer.address,
+                    vault: vault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([buyer])
+                .rpc());
+
+            await confirm(connection, await program.methods
+                .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000001", 1)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                    offer: offer,
+                })
+                .signers([seller1])
+                .rpc());
+
+            return {buyingIntent, offer, vault};
+        };
+
+        // Nobody complains during the delivery timeout, the claim returns the bond
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, new anchor.BN(1)));
+        const claimed = await createShippedOffer(beforeTestConfigData.buyingIntentIncrement.toNumber());
+
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+        const sellerBalance = await connection.getBalance(seller1.publicKey);
+
+        await confirm(connection, await program.methods
+            .claimAfterTimeout()
+            .accounts({
+                payer: admin.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: claimed.buyingIntent,
+                offer: claimed.offer,
+                mint: mintKeypair.publicKey,
+                vault: claimed.vault,
+                treasuryAta: treasuryAta,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalance + bondAmount);
+        assert.equal((await program.account.offer.fetch(claimed.offer)).bondAmount.toNumber(), 0);
+
+        // The arbiter refunds the whole escrow, the seller loses the dispute and the bond
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, beforeTestConfigData.deliveryTimeout));
+        const disputed = await createShippedOffer(beforeTestConfigData.buyingIntentIncrement.toNumber() + 1);
+
+        await confirm(connection, await program.methods
+            .openDispute("Parcel never arrived")
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: disputed.buyingIntent,
+                offer: disputed.offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyerBalance = await connection.getBalance(buyer.publicKey);
+        const treasuryBalance = await connection.getBalance(treasury);
+        const sellerBalanceBeforeDispute = await connection.getBalance(seller1.publicKey);
+
+        await confirm(connection, await program.methods
+            .resolveDispute(10_000)
+            .accounts({
+                arbiter: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: disputed.buyingIntent,
+                offer: disputed.offer,
+                mint: mintKeypair.publicKey,
+                vault: disputed.vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                treasuryAta: treasuryAta,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(buyer.publicKey), buyerBalance + bondAmount / 2);
+        assert.equal(await connection.getBalance(treasury), treasuryBalance + bondAmount / 2);
+        assert.equal((await program.account.offer.fetch(disputed.offer)).bondAmount.toNumber(), 0);
+
+        // Cleanup only gives the seller the offer rent back, not the slashed bond
+        const offerLamports = await connection.getBalance(disputed.offer);
+
+        await confirm(connection, await program.methods
+            .cleanupBuyingIntent()
+            .accountsPartial({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: disputed.buyingIntent,
+                offer: disputed.offer,
+                encryptedDeliveryInformation: PublicKey.findProgramAddressSync(
+                    [Buffer.from("encrypted_delivery_information"), disputed.offer.toBuffer()],
+                    program.programId
+                )[0],
+                dispute: PublicKey.findProgramAddressSync(
+                    [Buffer.from("dispute"), disputed.offer.toBuffer()],
+                    program.programId
+                )[0],
+                mint: mintKeypair.publicKey,
+                vault: disputed.vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts([{
+                pubkey: PublicKey.findProgramAddressSync(
+                    [Buffer.from("tracking_details"), disputed.offer.toBuffer(), numberToLeBytes(0, 2)],
+                    program.programId
+                )[0],
+                isWritable: true,
+                isSigner: false,
+            }])
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalanceBeforeDispute + offerLamports);
+
+        // Restore the config
+        await confirm(connection, await program.methods
+            .setOfferBond(null)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc());
+    });
+
+    step("Claim the escrow after the delivery timeout", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const treasuryAta = await getAssociatedTokenAddress(mintKeypair.publicKey, treasury, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        const acceptedMint = PublicKey.findProgramAddressSync(
+            [Buffer.from("accepted_mint"), mintKeypair.publicKey.toBuffer()],
+            program.programId
+        )[0];
+        const acceptedMintData = await program.account.acceptedMint.fetch(acceptedMint);
+
+        const setFee = (fee: number) => program.methods
+            .updateAcceptedMint(fee, null, null, null)
+            .accounts({
+                admin: admin.publicKey,
+                acceptedMint: acceptedMint,
+            })
+            .signers([admin])
+            .rpc();
+
+        const setTimeouts = (shippingTimeout: anchor.BN, deliveryTimeout: anchor.BN) => program.methods
+            .setTimeouts(shippingTimeout, deliveryTimeout)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        // Accepted and shipped offer of a new buying intent
+        const createShippedOffer = async (id: number) => {
+            await confirm(connection, await program.methods
+                .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 1, null, null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                })
+                .signers([buyer])
+                .rpc());
+
+            const buyingIntent = PublicKey.findProgramAddressSync(
+                [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(id)],
+                program.programId
+            )[0];
+
+            await confirm(connection, await program.methods
+                .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller1])
+                .rpc());
+
+            const offer = PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+                program.programId
+            )[0];
+            const vault = await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+            await confirm(connection, await program.methods
+                .acceptOffer(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                    buyingIntent: buyingIntent,
+                    offer: offer,
+                    mint: mintKeypair.publicKey,
+                    buyerAta: associatedTokenAccounts.buyer.address,
+                    vault: vault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([buyer])
+                .rpc());
+
+            await confirm(connection, await program.methods
+                .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000001", 1)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                    offer: offer,
+                })
+                .signers([seller1])
+                .rpc());
+
+            return {buyingIntent, offer, vault};
+        };
+
+        const claimAfterTimeout = ({buyingIntent, offer, vault}: {buyingIntent: PublicKey, offer: PublicKey, vault: PublicKey}) => program.methods
+            .claimAfterTimeout()
+            .accounts({
+                payer: admin.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                treasuryAta: treasuryAta,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([admin])
+            .rpc();
+
+        const tokenBalance = async (account: PublicKey) => Number((await connection.getTokenAccountBalance(account)).value.amount);
+
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, new anchor.BN(5)));
+        const claimed = await createShippedOffer(beforeTestConfigData.buyingIntentIncrement.toNumber());
+        const disputed = await createShippedOffer(beforeTestConfigData.buyingIntentIncrement.toNumber() + 1);
+
+        // Deals keep the fee tier they were accepted with
+        assert.equal((await program.account.offer.fetch(claimed.offer)).fee, acceptedMintData.fee);
+        await confirm(connection, await setFee(acceptedMintData.fee * 5));
+
+        const openDispute = ({buyingIntent, offer}: {buyingIntent: PublicKey, offer: PublicKey}, reason: string) => program.methods
+            .openDispute(reason)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([buyer])
+            .rpc();
+
+        // The buyer still has time to complain
+        await expectError(claimAfterTimeout(claimed), "DeliveryTimeoutNotReached");
+
+        await expectError(openDispute(disputed, "x".repeat(256)), "DisputeReasonTooLong");
+        await confirm(connection, await openDispute(disputed, "Parcel never arrived"));
+
+        await new Promise((resolve) => setTimeout(resolve, 6_000));
+
+        // Too late to complain, the seller can claim
+        await expectError(openDispute(claimed, "Parcel never arrived"), "DisputeWindowClosed");
+
+        // An open dispute freezes the vault, even after the timeout
+        await expectError(claimAfterTimeout(disputed), "OfferNotShipped");
+
+        // Nobody complained, the seller gets the escrow minus the fee
+        const escrow = await tokenBalance(claimed.vault);
+        const sellerBalance = await tokenBalance(associatedTokenAccounts.seller1.address);
+        const treasuryBalance = await tokenBalance(treasuryAta);
+
+        await confirm(connection, await claimAfterTimeout(claimed));
+
+        const fee = Math.floor(escrow * acceptedMintData.fee / 10_000);
+        assert.equal(await tokenBalance(claimed.vault), 0);
+        assert.equal(await tokenBalance(treasuryAta), treasuryBalance + fee);
+        assert.equal(await tokenBalance(associatedTokenAccounts.seller1.address), sellerBalance + escrow - fee);
+
+        assert.deepEqual((await program.account.offer.fetch(claimed.offer)).state, OFFER_STATES.DELIVERED);
+        assert.deepEqual((await program.account.buyingIntent.fetch(claimed.buyingIntent)).state, BUYING_INTENT_STATES.FULFILLED);
+
+        // A settled offer cannot be claimed twice
+        await expectError(claimAfterTimeout(claimed), "OfferNotShipped");
+
+        // Restore the config
+        await confirm(connection, await setFee(acceptedMintData.fee));
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, beforeTestConfigData.deliveryTimeout));
+    });
+
+    step("Settle native SOL offers after timeouts, disputes and partial shipments", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const nativeSolMint = anchor.web3.SystemProgram.programId;
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const vaultRent = await connection.getMinimumBalanceForRentExemption(0);
+
+        const acceptedMintData = await program.account.acceptedMint.fetch(
+            PublicKey.findProgramAddressSync(
+                [Buffer.from("accepted_mint"), nativeSolMint.toBuffer()],
+                program.programId
+            )[0]
+        );
+        const feeOf = (amount: number) => Math.floor(amount * acceptedMintData.fee / 10_000);
+
+        const setTimeouts = (shippingTimeout: anchor.BN, deliveryTimeout: anchor.BN) => program.methods
+            .setTimeouts(shippingTimeout, deliveryTimeout)
+            .accounts({
+                admin: admin.publicKey,
+            })
+            .signers([admin])
+            .rpc();
+
+        let nextId = beforeTestConfigData.buyingIntentIncrement.toNumber();
+
+        // Accepted native SOL offer of 10_000_000 lamports per unit on a new buying intent
+        const createAcceptedOffer = async (quantity: number) => {
+            await confirm(connection, await program.methods
+                .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, quantity, null, null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                })
+                .signers([buyer])
+                .rpc());
+
+            const buyingIntent = PublicKey.findProgramAddressSync(
+                [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(nextId++)],
+                program.programId
+            )[0];
+
+            await confirm(connection, await program.methods
+                .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(20_000_000), new anchor.BN(10_000_000), new anchor.BN(0), nativeSolMint, null)
+                .accounts({
+                    seller: seller1.publicKey,
+                    buyingIntent: buyingIntent,
+                })
+                .signers([seller1])
+                .rpc());
+
+            const offer = PublicKey.findProgramAddressSync(
+                [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+                program.programId
+            )[0];
+
+            await confirm(connection, await program.methods
+                .acceptOfferSol(offer, quantity, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+                .accounts({
+                    buyer: buyer.publicKey,
+                    buyingIntent: buyingIntent,
+                    offer: offer,
+                })
+                .signers([buyer])
+                .rpc());
+
+            const solVault = PublicKey.findProgramAddressSync(
+                [Buffer.from("sol_vault"), offer.toBuffer()],
+                program.programId
+            )[0];
+
+            return {buyingIntent, offer, solVault};
+        };
+
+        const ship = async ({buyingIntent, offer}: {buyingIntent: PublicKey, offer: PublicKey}, units: number) => confirm(connection, await program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000004", units)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        // Nothing shipped before the shipping timeout, the buyer gets the whole escrow back
+        await confirm(connection, await setTimeouts(new anchor.BN(1), beforeTestConfigData.deliveryTimeout));
+        const refunded = await createAcceptedOffer(1);
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+        let buyerBalance = await connection.getBalance(buyer.publicKey);
+
+        await confirm(connection, await program.methods
+            .refundAfterTimeoutSol()
+            .accounts({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                buyingIntent: refunded.buyingIntent,
+                offer: refunded.offer,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(buyer.publicKey), buyerBalance + 10_000_000);
+        assert.equal(await connection.getBalance(refunded.solVault), vaultRent);
+        assert.deepEqual((await program.account.offer.fetch(refunded.offer)).state, OFFER_STATES.CANCELLED);
+
+        // Nobody complained during the delivery timeout, the seller claims the escrow minus the fee
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, new anchor.BN(1)));
+        const claimed = await createAcceptedOffer(1);
+        await ship(claimed, 1);
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+        let sellerBalance = await connection.getBalance(seller1.publicKey);
+        let treasuryBalance = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .claimAfterTimeoutSol()
+            .accounts({
+                payer: admin.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: claimed.buyingIntent,
+                offer: claimed.offer,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalance + 10_000_000 - feeOf(10_000_000));
+        assert.equal(await connection.getBalance(treasury), treasuryBalance + feeOf(10_000_000));
+        assert.equal(await connection.getBalance(claimed.solVault), vaultRent);
+        assert.deepEqual((await program.account.offer.fetch(claimed.offer)).state, OFFER_STATES.DELIVERED);
+
+        await confirm(connection, await setTimeouts(beforeTestConfigData.shippingTimeout, beforeTestConfigData.deliveryTimeout));
+
+        // The arbiter refunds a quarter, the fee is only charged on the seller part
+        const disputed = await createAcceptedOffer(1);
+        await ship(disputed, 1);
+
+        await confirm(connection, await program.methods
+            .openDispute("Parcel arrived damaged")
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: disputed.buyingIntent,
+                offer: disputed.offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        buyerBalance = await connection.getBalance(buyer.publicKey);
+        sellerBalance = await connection.getBalance(seller1.publicKey);
+        treasuryBalance = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .resolveDisputeSol(2_500)
+            .accounts({
+                arbiter: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: disputed.buyingIntent,
+                offer: disputed.offer,
+            })
+            .signers([admin])
+            .rpc());
+
+        assert.equal(await connection.getBalance(buyer.publicKey), buyerBalance + 2_500_000);
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalance + 7_500_000 - feeOf(7_500_000));
+        assert.equal(await connection.getBalance(treasury), treasuryBalance + feeOf(7_500_000));
+        assert.equal(await connection.getBalance(disputed.solVault), vaultRent);
+
+        // Confirming one parcel out of two releases half of the escrow
+        const parcels = await createAcceptedOffer(2);
+        await ship(parcels, 1);
+
+        sellerBalance = await connection.getBalance(seller1.publicKey);
+        treasuryBalance = await connection.getBalance(treasury);
+
+        await confirm(connection, await program.methods
+            .acceptShipmentSol(0)
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: parcels.buyingIntent,
+                offer: parcels.offer,
+            })
+            .signers([buyer])
+            .rpc());
+
+        assert.equal(await connection.getBalance(seller1.publicKey), sellerBalance + 10_000_000 - feeOf(10_000_000));
+        assert.equal(await connection.getBalance(treasury), treasuryBalance + feeOf(10_000_000));
+        assert.equal(await connection.getBalance(parcels.solVault), vaultRent + 10_000_000);
+        assert.deepEqual((await program.account.offer.fetch(parcels.offer)).state, OFFER_STATES.SHIPPED);
+    });
+
+    step("Clean up a partial buying intent once the buyer confirms it", async () => {
+
+        const config = PublicKey.findProgramAddressSync(
+            [Buffer.from("config")],
+            program.programId
+        )[0];
+
+        const treasury = PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury")],
+            program.programId
+        )[0];
+
+        const beforeTestConfigData = await program.account.config.fetch(config);
+        const treasuryAta = await getAssociatedTokenAddress(mintKeypair.publicKey, treasury, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        // Two units wanted, only one seller answers
+        await confirm(connection, await program.methods
+            .createBuyingIntent(new anchor.BN(3544056897834), "Focal Bathys MG", "FR", null, 2, null, null)
+            .accounts({
+                buyer: buyer.publicKey,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const buyingIntent = PublicKey.findProgramAddressSync(
+            [Buffer.from("buy_intent"), buyer.publicKey.toBuffer(), numberToLeBytes(beforeTestConfigData.buyingIntentIncrement.toNumber())],
+            program.programId
+        )[0];
+
+        await confirm(connection, await program.methods
+            .createOffer("https://www.focal.com/bathys-mg", new anchor.BN(200_000_000), new anchor.BN(100_000_000), new anchor.BN(0), mintKeypair.publicKey, null)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([seller1])
+            .rpc());
+
+        const offer = PublicKey.findProgramAddressSync(
+            [Buffer.from("offer"), buyingIntent.toBuffer(), seller1.publicKey.toBuffer()],
+            program.programId
+        )[0];
+        const vault = await getAssociatedTokenAddress(mintKeypair.publicKey, offer, true, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID);
+
+        await confirm(connection, await program.methods
+            .acceptOffer(offer, 1, DELIVERY_INFORMATION_VERSION, Array.from(Buffer.alloc(24)), Buffer.alloc(32), Array.from(Buffer.alloc(80)), null)
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                mint: mintKeypair.publicKey,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                vault: vault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        await confirm(connection, await program.methods
+            .createTrackingDetails("UPS", "https://www.ups.com", "1Z000000000000005", 1)
+            .accounts({
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+            })
+            .signers([seller1])
+            .rpc());
+
+        await confirm(connection, await program.methods
+            .acceptDelivery()
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                treasury: treasury,
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                sellerAta: associatedTokenAccounts.seller1.address,
+                treasuryAta: treasuryAta,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc());
+
+        const cleanup = () => program.methods
+            .cleanupBuyingIntent()
+            .accountsPartial({
+                payer: admin.publicKey,
+                buyer: buyer.publicKey,
+                seller: seller1.publicKey,
+                buyingIntent: buyingIntent,
+                offer: offer,
+                encryptedDeliveryInformation: PublicKey.findProgramAddressSync(
+                    [Buffer.from("encrypted_delivery_information"), offer.toBuffer()],
+                    program.programId
+                )[0],
+                dispute: PublicKey.findProgramAddressSync(
+                    [Buffer.from("dispute"), offer.toBuffer()],
+                    program.programId
+                )[0],
+                mint: mintKeypair.publicKey,
+                vault: vault,
+                buyerAta: associatedTokenAccounts.buyer.address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts([{
+                pubkey: PublicKey.findProgramAddressSync(
+                    [Buffer.from("tracking_details"), offer.toBuffer(), numberToLeBytes(0, 2)],
+                    program.programId
+                )[0],
+                isWritable: true,
+                isSigner: false,
+            }])
+            .signers([admin])
+            .rpc();
+
+        // The slice is settled but the buying intent still takes offers
+        assert.deepEqual((await program.account.buyingIntent.fetch(buyingIntent)).state, BUYING_INTENT_STATES.PUBLISHED);
+        await expectError(cleanup(), "BuyingIntentNotFinished");
+
+        await confirm(connection, await program.methods
+            .confirmBuyingIntent()
+            .accounts({
+                buyer: buyer.publicKey,
+                buyingIntent: buyingIntent,
+            })
+            .signers([buyer])
+            .rpc());
+        assert.deepEqual((await program.account.buyingIntent.fetch(buyingIntent)).state, BUYING_INTENT_STATES.FULFILLED);
+
+        // The last slice closes the buying intent
+        await confirm(connection, await cleanup());
+        assert.isNull(await connection.getAccountInfo(offer));
+        assert.isNull(await connection.getAccountInfo(buyingIntent));
+    });
+});
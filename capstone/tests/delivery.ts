@@ -0,0 +1,110 @@
+import {Keypair, PublicKey} from "@solana/web3.js";
+import sodium from 'libsodium-wrappers';
+
+// Same layout as the Rust client, see crates/bestoffer-client/src/delivery.rs
+type DeliveryAddress = {
+    lastname: string,
+    firstname: string,
+    address_line_1: string,
+    address_line_2: string | null,
+    city: string,
+    postal_code: string,
+    country_code: string,
+    state_code: string | null,
+};
+
+const DELIVERY_INFORMATION_VERSION = 1;
+
+const FIELDS: [keyof DeliveryAddress, boolean][] = [
+    ["lastname", false],
+    ["firstname", false],
+    ["address_line_1", false],
+    ["address_line_2", true],
+    ["city", false],
+    ["postal_code", false],
+    ["country_code", false],
+    ["state_code", true],
+];
+
+// Borsh: string = u32 LE length + UTF-8 bytes, option = u8 tag + value
+const serializeDeliveryAddress = (address: DeliveryAddress): Buffer => {
+    const chunks: Buffer[] = [];
+
+    for (const [field, optional] of FIELDS) {
+        const value = address[field];
+        if (optional) {
+            chunks.push(Buffer.from([value === null ? 0 : 1]));
+            if (value === null) continue;
+        }
+
+        const bytes = Buffer.from(value, "utf8");
+        const length = Buffer.alloc(4);
+        length.writeUInt32LE(bytes.length);
+        chunks.push(length, bytes);
+    }
+
+    return Buffer.concat(chunks);
+};
+
+const deserializeDeliveryAddress = (data: Uint8Array): DeliveryAddress => {
+    const buffer = Buffer.from(data);
+    const address = {} as DeliveryAddress;
+    let offset = 0;
+
+    for (const [field, optional] of FIELDS) {
+        if (optional && buffer[offset++] === 0) {
+            address[field] = null;
+            continue;
+        }
+
+        const length = buffer.readUInt32LE(offset);
+        offset += 4;
+        address[field] = buffer.subarray(offset, offset + length).toString("utf8");
+        offset += length;
+    }
+
+    return address;
+};
+
+// Envelope v1: the address is encrypted with a random secretbox key, sealed to the seller and the arbiter
+const sealDeliveryAddress = async (address: DeliveryAddress, seller: PublicKey, arbiter: PublicKey | null = null) => {
+    await sodium.ready;
+
+    const key = sodium.crypto_secretbox_keygen();
+    const nonce = sodium.randombytes_buf(sodium.crypto_secretbox_NONCEBYTES);
+
+    const sealKey = (recipient: PublicKey): number[] => Array.from(sodium.crypto_box_seal(
+        key,
+        sodium.crypto_sign_ed25519_pk_to_curve25519(recipient.toBytes())
+    ));
+
+    return {
+        version: DELIVERY_INFORMATION_VERSION,
+        nonce: Array.from(nonce),
+        encryptedAddress: Buffer.from(sodium.crypto_secretbox_easy(serializeDeliveryAddress(address), nonce, key)),
+        sellerSealedKey: sealKey(seller),
+        arbiterSealedKey: arbiter === null ? null : {arbiter, sealedKey: sealKey(arbiter)},
+    };
+};
+
+// Open a fetched EncryptedDeliveryInformation account as the seller or the arbiter
+const openDeliveryAddress = async (information, recipient: Keypair): Promise<DeliveryAddress> => {
+    await sodium.ready;
+
+    const publicKey = sodium.crypto_sign_ed25519_pk_to_curve25519(recipient.publicKey.toBytes());
+    const secretKey = sodium.crypto_sign_ed25519_sk_to_curve25519(recipient.secretKey);
+
+    const sealedKey = information.arbiterSealedKey?.arbiter.equals(recipient.publicKey)
+        ? information.arbiterSealedKey.sealedKey
+        : information.sellerSealedKey;
+
+    const key = sodium.crypto_box_seal_open(Uint8Array.from(sealedKey), publicKey, secretKey);
+
+    return deserializeDeliveryAddress(sodium.crypto_secretbox_open_easy(
+        Uint8Array.from(information.encryptedAddress),
+        Uint8Array.from(information.nonce),
+        key
+    ));
+};
+
+export {DeliveryAddress, DELIVERY_INFORMATION_VERSION, sealDeliveryAddress, openDeliveryAddress};
@@ -4,9 +4,10 @@ const BUYING_INTENT_STATES = {
     PUBLISHED: {published: {}},
     CANCELLED: {cancelled: {}},
     CONFIRMED: {confirmed: {}},
-    SHIPPED: {shipped: {}},
+    SHIPPED: {shipped: {}}, // Deprecated, kept for the discriminants
     FULFILLED: {fulfilled: {}},
-    DISPUTED: {disputed: {}},
+    DISPUTED: {disputed: {}}, // Deprecated, kept for the discriminants
+    EXPIRED: {expired: {}},
 };
 
 const OFFER_STATES = {
@@ -14,6 +15,9 @@ const OFFER_STATES = {
     ACCEPTED: {accepted: {}},
     DELIVERED: {delivered: {}},
     CANCELLED: {cancelled: {}},
+    SHIPPED: {shipped: {}},
+    DISPUTED: {disputed: {}},
+    EXPIRED: {expired: {}},
 };
 
 export {BUYING_INTENT_STATES, OFFER_STATES};
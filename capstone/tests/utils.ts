@@ -1,5 +1,6 @@
 import {Connection} from "@solana/web3.js";
 import * as anchor from "@coral-xyz/anchor";
+import {assert} from "chai";
 
 const log = async (signature: string): Promise<string> => {
     console.log(
@@ -43,9 +44,23 @@ const fundWallet = async function fundWallet(
 
 function numberToLeBytes(num: number, length: number = 8): Buffer {
     const buffer = Buffer.alloc(length);
-    buffer.writeBigUInt64LE(BigInt(num), 0);
+    if (length === 8) {
+        buffer.writeBigUInt64LE(BigInt(num), 0);
+    } else {
+        buffer.writeUIntLE(num, 0, length);
+    }
     return buffer;
 }
 
+const expectError = async (promise: Promise<unknown>, code: string): Promise<void> => {
+    try {
+        await promise;
+    } catch (e) {
+        assert.equal(e.error?.errorCode?.code, code);
+        return;
+    }
+
+    assert.fail(`Expected error ${code}`);
+};
 
-export {log, confirm, fundWallet, numberToLeBytes};
+export {log, confirm, expectError, fundWallet, numberToLeBytes};